@@ -0,0 +1,115 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use etterna::*;
+
+/// Generates a replay with `num_notes` 16th notes at 120 BPM, jumping around the four columns, with
+/// deterministic pseudo-random deviations and a few misses
+fn generate_replay_bytes(num_notes: u32) -> Vec<u8> {
+	let mut bytes = Vec::new();
+	let mut rng_state: u32 = 12345;
+	for i in 0..num_notes {
+		rng_state = rng_state.wrapping_mul(1103515245).wrapping_add(12345);
+		let deviation = if rng_state.is_multiple_of(100) {
+			1.0
+		} else {
+			((rng_state >> 8) % 100) as f32 / 1000.0 - 0.05
+		};
+		bytes
+			.extend_from_slice(format!("{} {:.6} {}\n", i * 12, deviation, (i * 7) % 4).as_bytes());
+	}
+	bytes
+}
+
+fn replay_parsing(c: &mut Criterion) {
+	let bytes = generate_replay_bytes(10_000);
+
+	c.bench_function("parse_replay_v2_fast", |b| {
+		b.iter(|| parse_replay_v2_fast(black_box(&bytes)))
+	});
+	c.bench_function("parse_replay_v2", |b| {
+		b.iter(|| parse_replay_v2(black_box(&bytes)))
+	});
+	c.bench_function("split_newlines", |b| {
+		b.iter(|| etterna::util::split_newlines(black_box(&bytes), 5).count())
+	});
+}
+
+fn rescoring(c: &mut Criterion) {
+	let replay = parse_replay_v2_fast(&generate_replay_bytes(10_000)).unwrap();
+	let timing_info = TimingInfo::from_constant_bpm(120.0);
+	let lanes = replay.split_into_lanes(&timing_info).unwrap();
+
+	c.bench_function("split_into_lanes", |b| {
+		b.iter(|| black_box(&replay).split_into_lanes(&timing_info))
+	});
+	c.bench_function("split_into_lanes_preallocated", |b| {
+		let mut lanes = Default::default();
+		b.iter(|| black_box(&replay).split_into_lanes_preallocated(&timing_info, &mut lanes))
+	});
+	c.bench_function("rescore_from_note_hits", |b| {
+		b.iter(|| rescore_from_note_hits::<Wife3, _>(black_box(&replay).iter_hits(), 0, 0, J7))
+	});
+	c.bench_function("rescore NaiveScorer", |b| {
+		b.iter(|| rescore::<NaiveScorer, Wife3>(black_box(&lanes), 0, 0, J7))
+	});
+	c.bench_function("rescore MatchingScorer", |b| {
+		b.iter(|| rescore::<MatchingScorer, Wife3>(black_box(&lanes), 0, 0, J7))
+	});
+
+	// A dense file, where the miss window of each hit spans many notes
+	let dense_replay = parse_replay_v2_fast(&generate_replay_bytes(10_000)).unwrap();
+	let dense_lanes = dense_replay
+		.split_into_lanes(&TimingInfo::from_constant_bpm(1920.0))
+		.unwrap();
+	c.bench_function("rescore NaiveScorer dense", |b| {
+		b.iter(|| rescore::<NaiveScorer, Wife3>(black_box(&dense_lanes), 0, 0, J7))
+	});
+	c.bench_function("rescore MatchingScorer dense", |b| {
+		b.iter(|| rescore::<MatchingScorer, Wife3>(black_box(&dense_lanes), 0, 0, J7))
+	});
+
+	let replays = vec![replay.clone(); 100];
+	c.bench_function("rescore_many 100", |b| {
+		b.iter(|| rescore_many::<Wife3>(black_box(&replays), J7))
+	});
+}
+
+fn rating_calc(c: &mut Criterion) {
+	let scores: Vec<Skillsets7> = (0..1000)
+		.map(|i| {
+			Skillsets7::generate(|ss| 10.0 + ((i * 37 + ss as usize * 11) % 200) as f32 / 10.0)
+		})
+		.collect();
+	let ssrs: Vec<f32> = scores.iter().map(|score| score.stream).collect();
+
+	c.bench_function("calculate_player_skillset_rating", |b| {
+		b.iter(|| calculate_player_skillset_rating(black_box(&ssrs)))
+	});
+	c.bench_function("calculate_score_overall", |b| {
+		b.iter(|| calculate_score_overall(black_box(&[20.0, 21.0, 22.0, 23.0, 24.0, 25.0, 26.0])))
+	});
+
+	let players = vec![&scores[..]; 10];
+	c.bench_function("calculate_many_ratings 10", |b| {
+		b.iter(|| calculate_many_ratings(black_box(&players)))
+	});
+}
+
+fn note_subsets(c: &mut Criterion) {
+	let replay = parse_replay_v2_fast(&generate_replay_bytes(10_000)).unwrap();
+	let lanes = replay
+		.split_into_lanes(&TimingInfo::from_constant_bpm(120.0))
+		.unwrap();
+
+	c.bench_function("find_fastest_note_subset", |b| {
+		b.iter(|| find_fastest_note_subset(black_box(&lanes[0].hit_seconds), 50, 100))
+	});
+}
+
+criterion_group!(
+	benches,
+	replay_parsing,
+	rescoring,
+	rating_calc,
+	note_subsets
+);
+criterion_main!(benches);
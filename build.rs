@@ -0,0 +1,22 @@
+fn main() {
+	println!("cargo:rerun-if-env-changed=MINACALC_LIB_DIR");
+
+	// The minacalc feature links against a prebuilt MinaCalc static library. It's only linked if
+	// its location is known, so that the crate still builds and tests without it as long as
+	// nothing calls into MinaCalc
+	if std::env::var_os("CARGO_FEATURE_MINACALC").is_some() {
+		if let Some(lib_dir) = std::env::var_os("MINACALC_LIB_DIR") {
+			println!(
+				"cargo:rustc-link-search=native={}",
+				lib_dir.to_string_lossy()
+			);
+			println!("cargo:rustc-link-lib=static=minacalc");
+			// MinaCalc is written in C++, so it needs the C++ standard library
+			let cpp_stdlib = match std::env::var("CARGO_CFG_TARGET_OS").as_deref() {
+				Ok("macos") | Ok("ios") => "c++",
+				_ => "stdc++",
+			};
+			println!("cargo:rustc-link-lib=dylib={}", cpp_stdlib);
+		}
+	}
+}
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	if let Ok(string) = std::str::from_utf8(data) {
+		// beyond that, f32 can't represent every multiple of 0.05 anymore
+		let rate = etterna::Rate::from_string(string).filter(|rate| rate.as_f32() < 100_000.0);
+		if let Some(rate) = rate {
+			assert_eq!(etterna::Rate::from_string(&rate.to_string()), Some(rate));
+		}
+	}
+});
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	let fast = etterna::parse_replay_v2_fast(data);
+	let _ = etterna::parse_replay_v2_full(data);
+	let _ = etterna::parse_replay_v2_lossy(data);
+	if let Ok(strict) = etterna::parse_replay_v2(data) {
+		// a replay without malformed lines must parse the same either way
+		assert_eq!(fast.as_ref(), Some(&strict));
+	}
+});
@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	if let Ok(timing_info) = etterna::TimingInfo::from_sm_bpm_string(data) {
+		let _ = timing_info.ticks_to_seconds(&[0, 48, 4800]);
+		let _ = timing_info.to_sm_bpm_string();
+	}
+});
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+/// Normalizes user input for case-insensitive matching. Shared by the built-in spellings and
+/// [`AliasTable`], so both agree on what counts as the same spelling
+pub(crate) fn normalize_user_input(input: &str) -> String {
+	input.to_lowercase()
+}
+
+/// Additional spellings for parsing user input, on top of the built-in ones. For example, an
+/// application can register the names of difficulties or skillsets in other languages, and pass
+/// the table to [`crate::Difficulty::from_user_input_with_aliases`] or
+/// [`crate::Skillset8::from_user_input_with_aliases`].
+///
+/// Aliases are matched case-insensitively. Built-in spellings always take precedence.
+///
+/// ```rust
+/// # use etterna::*;
+/// let aliases = AliasTable::new()
+/// 	.with("Schwer", Difficulty::Hard)
+/// 	.with("Experte", Difficulty::Challenge);
+///
+/// assert_eq!(
+/// 	Difficulty::from_user_input_with_aliases("schwer", &aliases),
+/// 	Some(Difficulty::Hard),
+/// );
+/// assert_eq!(
+/// 	Difficulty::from_user_input_with_aliases("IN", &aliases),
+/// 	Some(Difficulty::Challenge),
+/// );
+/// assert_eq!(Difficulty::from_user_input("schwer"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasTable<T> {
+	aliases: HashMap<String, T>,
+}
+
+impl<T: Copy> AliasTable<T> {
+	pub fn new() -> Self {
+		Self {
+			aliases: HashMap::new(),
+		}
+	}
+
+	/// Registers an alias. Returns the value the alias previously stood for, if any
+	pub fn insert(&mut self, alias: &str, value: T) -> Option<T> {
+		self.aliases.insert(normalize_user_input(alias), value)
+	}
+
+	/// Builder-style variant of [`Self::insert`]
+	pub fn with(mut self, alias: &str, value: T) -> Self {
+		self.insert(alias, value);
+		self
+	}
+
+	/// Looks up user input in the registered aliases only, case-insensitively
+	pub fn get(&self, input: &str) -> Option<T> {
+		self.aliases.get(&normalize_user_input(input)).copied()
+	}
+
+	/// Number of registered aliases
+	pub fn len(&self) -> usize {
+		self.aliases.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.aliases.is_empty()
+	}
+}
+
+impl<T: Copy> Default for AliasTable<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<S: AsRef<str>, T: Copy> std::iter::FromIterator<(S, T)> for AliasTable<T> {
+	fn from_iter<I: IntoIterator<Item = (S, T)>>(iter: I) -> Self {
+		let mut table = Self::new();
+		for (alias, value) in iter {
+			table.insert(alias.as_ref(), value);
+		}
+		table
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_alias_table() {
+		let mut aliases: AliasTable<crate::Skillset8> =
+			vec![("Vitesse", crate::Skillset8::Jackspeed)]
+				.into_iter()
+				.collect();
+		assert_eq!(
+			aliases.insert("VITESSE", crate::Skillset8::Chordjack),
+			Some(crate::Skillset8::Jackspeed)
+		);
+		assert_eq!(aliases.len(), 1);
+		assert_eq!(aliases.get("vitesse"), Some(crate::Skillset8::Chordjack));
+
+		// non-ASCII aliases are case-insensitive too
+		aliases.insert("Ausdauer", crate::Skillset8::Stamina);
+		aliases.insert("ВЫНОСЛИВОСТЬ", crate::Skillset8::Stamina);
+		assert_eq!(aliases.get("выносливость"), Some(crate::Skillset8::Stamina));
+
+		// built-in spellings can't be overridden
+		aliases.insert("js", crate::Skillset8::Technical);
+		assert_eq!(
+			crate::Skillset8::from_user_input_with_aliases("JS", &aliases),
+			Some(crate::Skillset8::Jumpstream)
+		);
+		assert_eq!(
+			crate::Skillset8::from_user_input_with_aliases("ausdauer", &aliases),
+			Some(crate::Skillset8::Stamina)
+		);
+		assert_eq!(
+			crate::Skillset8::from_user_input_with_aliases("x", &aliases),
+			None
+		);
+	}
+
+	#[test]
+	fn test_alias_table_from_owned_strings() {
+		// e.g. aliases loaded from a config file at runtime
+		let loaded = vec![("Schwer".to_owned(), crate::Difficulty::Hard)];
+		let aliases: AliasTable<crate::Difficulty> = loaded.into_iter().collect();
+		assert_eq!(aliases.get("SCHWER"), Some(crate::Difficulty::Hard));
+	}
+}
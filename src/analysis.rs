@@ -0,0 +1,1141 @@
+/// A cluster of combo breakers within a short timespan. See [`find_cb_clumps`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CbClump {
+	/// Time of the first combo breaker in the clump, in seconds
+	pub start_second: f32,
+	/// Time of the last combo breaker in the clump, in seconds
+	pub end_second: f32,
+	pub num_cbs: u32,
+}
+
+/// Finds clusters of combo breakers, i.e. the parts of a chart that destroyed a score.
+///
+/// A clump is formed wherever at least `min_cbs` combo breakers occur within `window_seconds`.
+/// Overlapping and adjacent clumps are merged. The input is an iterator over each note's time in
+/// seconds and the hit on that note, sorted by time, for example from
+/// [`crate::ReplayV2Fast::scatter_plot`].
+///
+/// ```rust
+/// # use etterna::*;
+/// let hits = vec![
+/// 	(1.0, Hit::Miss),
+/// 	(1.5, Hit::Hit { deviation: 0.12 }),
+/// 	(2.0, Hit::Hit { deviation: 0.01 }),
+/// 	(2.5, Hit::Miss),
+/// 	(10.0, Hit::Miss),
+/// ];
+/// let clumps = find_cb_clumps(hits, J4, 3, 2.0);
+/// assert_eq!(clumps, vec![CbClump { start_second: 1.0, end_second: 2.5, num_cbs: 3 }]);
+/// ```
+pub fn find_cb_clumps(
+	hits: impl IntoIterator<Item = (f32, crate::Hit)>,
+	judge: &crate::Judge,
+	min_cbs: u32,
+	window_seconds: f32,
+) -> Vec<CbClump> {
+	let min_cbs = min_cbs.max(1) as usize;
+
+	let cb_seconds: Vec<f32> = hits
+		.into_iter()
+		.filter(|(_, hit)| hit.is_cb(judge))
+		.map(|(second, _)| second)
+		.collect();
+
+	// Mark all CBs which are part of a window with enough CBs
+	let mut is_in_clump = vec![false; cb_seconds.len()];
+	for i in 0..cb_seconds.len().saturating_sub(min_cbs - 1) {
+		if cb_seconds[i + min_cbs - 1] - cb_seconds[i] <= window_seconds {
+			for is_in_clump in &mut is_in_clump[i..(i + min_cbs)] {
+				*is_in_clump = true;
+			}
+		}
+	}
+
+	let mut clumps: Vec<CbClump> = Vec::new();
+	let mut prev_cb_was_in_clump = false;
+	for (&second, &is_in_clump) in cb_seconds.iter().zip(&is_in_clump) {
+		if is_in_clump {
+			match clumps.last_mut() {
+				Some(clump)
+					if prev_cb_was_in_clump && second - clump.end_second <= window_seconds =>
+				{
+					clump.end_second = second;
+					clump.num_cbs += 1;
+				}
+				_ => clumps.push(CbClump {
+					start_second: second,
+					end_second: second,
+					num_cbs: 1,
+				}),
+			}
+		}
+		prev_cb_was_in_clump = is_in_clump;
+	}
+
+	clumps
+}
+
+/// A section of a replay in [`SectionBias`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BiasSection {
+	pub start_second: f32,
+	pub end_second: f32,
+	/// Number of notes in this section that were hit, i.e. not missed
+	pub num_hits: u32,
+	/// Mean deviation of all hits in this section, in seconds. Negative means early, positive
+	/// means late. `None` if no note in this section was hit
+	pub mean_deviation: Option<f32>,
+}
+
+/// Early/late bias of a replay over time, for example to see whether a player drifts late as their
+/// stamina fades.
+///
+/// ```rust
+/// # use etterna::*;
+/// let hits = (0..100).map(|i| (i as f32, Hit::Hit { deviation: i as f32 / 1000.0 }));
+/// let bias = SectionBias::calculate(hits, 4);
+/// assert_eq!(bias.sections.len(), 4);
+/// assert!(bias.drift().unwrap() > 0.0); // the player drifted late
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectionBias {
+	pub sections: Vec<BiasSection>,
+}
+
+impl SectionBias {
+	/// Splits the replay into `num_sections` sections of equal duration and calculates the mean
+	/// deviation in each. Misses are not included in the means.
+	///
+	/// The input is an iterator over each note's time in seconds and the hit on that note, for
+	/// example from [`crate::ReplayV2Fast::scatter_plot`]. The sections span from the first to the
+	/// last note. If there are no notes, no sections are returned.
+	pub fn calculate(
+		hits: impl IntoIterator<Item = (f32, crate::Hit)>,
+		num_sections: usize,
+	) -> Self {
+		let hits: Vec<(f32, crate::Hit)> = hits.into_iter().collect();
+		if hits.is_empty() || num_sections == 0 {
+			return Self::default();
+		}
+
+		let first_second = hits.iter().map(|&(s, _)| s).fold(f32::INFINITY, f32::min);
+		let last_second = hits
+			.iter()
+			.map(|&(s, _)| s)
+			.fold(f32::NEG_INFINITY, f32::max);
+		let section_duration = (last_second - first_second) / num_sections as f32;
+
+		let mut deviation_sums = vec![0.0; num_sections];
+		let mut num_hits = vec![0; num_sections];
+		for &(second, hit) in &hits {
+			let deviation = crate::some_or_continue!(hit.deviation());
+			let section_index = if section_duration > 0.0 {
+				(((second - first_second) / section_duration) as usize).min(num_sections - 1)
+			} else {
+				0
+			};
+			deviation_sums[section_index] += deviation;
+			num_hits[section_index] += 1;
+		}
+
+		let sections = (0..num_sections)
+			.map(|i| BiasSection {
+				start_second: first_second + section_duration * i as f32,
+				end_second: first_second + section_duration * (i + 1) as f32,
+				num_hits: num_hits[i],
+				mean_deviation: if num_hits[i] > 0 {
+					Some(deviation_sums[i] / num_hits[i] as f32)
+				} else {
+					None
+				},
+			})
+			.collect();
+
+		Self { sections }
+	}
+
+	/// Difference between the mean deviation of the last and the first section which contain hits.
+	/// Positive values mean the player drifted late over the course of the replay.
+	///
+	/// Returns `None` if there are no hits
+	pub fn drift(&self) -> Option<f32> {
+		let mut mean_deviations = self.sections.iter().filter_map(|s| s.mean_deviation);
+		let first = mean_deviations.next()?;
+		let last = mean_deviations.next_back().unwrap_or(first);
+		Some(last - first)
+	}
+}
+
+/// Windows with fewer hits than this are never considered mashing
+const MIN_MASH_HITS: usize = 4;
+
+/// A span of time in which a lane was likely mashed. See [`find_mash_segments`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MashSegment {
+	/// Index of the lane in the input slice
+	pub column: usize,
+	pub start_second: f32,
+	pub end_second: f32,
+	pub num_notes: u32,
+	pub num_hits: u32,
+	/// Proportion of hits in this segment which can't be attributed to any note, from 0.0 to 1.0.
+	/// Higher values mean the segment is more likely to be mashed
+	pub confidence: f32,
+}
+
+fn count_in_range(sorted_seconds: &[f32], start: f32, end: f32) -> usize {
+	sorted_seconds.partition_point(|&s| s < end) - sorted_seconds.partition_point(|&s| s < start)
+}
+
+/// Heuristically detects mashing (or vibro) by comparing the density of hits to the density of
+/// notes in each lane. This is a heuristic; it can't prove that a score is invalid.
+///
+/// Each lane is divided into windows of `window_seconds`. A window is flagged if it contains more
+/// than `min_hit_ratio` times as many hits as notes. Adjacent flagged windows are merged into a
+/// single [`MashSegment`]. The `hit_seconds` of each lane should contain every key press, not only
+/// the ones that were matched to a note.
+///
+/// Returns no segments if `window_seconds` is not positive, or NaN. Lanes whose timestamps are so
+/// large that adding `window_seconds` doesn't change them are skipped as well.
+///
+/// ```rust
+/// # use etterna::*;
+/// let lane = NoteAndHitSeconds {
+/// 	note_seconds: vec![0.0, 1.0, 2.0, 3.0],
+/// 	hit_seconds: vec![0.0, 1.0, 2.0, 2.05, 2.1, 2.15, 2.2, 2.25, 2.3, 3.0],
+/// };
+/// let segments = find_mash_segments(&[lane], 1.0, 2.0);
+/// assert_eq!(segments.len(), 1);
+/// assert_eq!(segments[0].start_second, 2.0);
+/// ```
+pub fn find_mash_segments(
+	lanes: &[crate::NoteAndHitSeconds],
+	window_seconds: f32,
+	min_hit_ratio: f32,
+) -> Vec<MashSegment> {
+	let mut segments = Vec::new();
+	if window_seconds.is_nan() || window_seconds <= 0.0 {
+		return segments;
+	}
+
+	for (column, lane) in lanes.iter().enumerate() {
+		let mut note_seconds = lane.note_seconds.clone();
+		let mut hit_seconds = lane.hit_seconds.clone();
+		note_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+		hit_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+		let (first_hit, last_hit) = match (hit_seconds.first(), hit_seconds.last()) {
+			(Some(&first), Some(&last)) => (first, last),
+			_ => continue,
+		};
+
+		// the windows wouldn't advance in f32 precision
+		if last_hit + window_seconds == last_hit {
+			continue;
+		}
+
+		let mut current_segment: Option<MashSegment> = None;
+		// window boundaries are computed from an integer index instead of accumulated, so that
+		// rounding errors can't add up or stall the loop. The index is 64-bit, because a tiny window
+		// over a long span needs more windows than fit into a u32
+		let window_boundary = |i: u64| first_hit + i as f32 * window_seconds;
+		let mut window_index = 0;
+		while window_boundary(window_index) <= last_hit {
+			let window_start = window_boundary(window_index);
+			let window_end = window_boundary(window_index + 1);
+			let num_notes = count_in_range(&note_seconds, window_start, window_end);
+			let num_hits = count_in_range(&hit_seconds, window_start, window_end);
+
+			let is_mashed = num_hits >= MIN_MASH_HITS
+				&& num_hits as f32 > num_notes.max(1) as f32 * min_hit_ratio;
+			if is_mashed {
+				let segment = current_segment.get_or_insert(MashSegment {
+					column,
+					start_second: window_start,
+					..Default::default()
+				});
+				segment.end_second = window_end;
+				segment.num_notes += num_notes as u32;
+				segment.num_hits += num_hits as u32;
+			} else if let Some(segment) = current_segment.take() {
+				segments.push(segment);
+			}
+
+			window_index += 1;
+		}
+		segments.extend(current_segment);
+	}
+
+	for segment in &mut segments {
+		segment.confidence = 1.0 - segment.num_notes as f32 / segment.num_hits as f32;
+		segment.confidence = segment.confidence.clamp(0.0, 1.0);
+	}
+
+	segments
+}
+
+/// Number of notes and combo breakers among them. See [`CbBreakdown`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CbCount {
+	pub num_notes: u32,
+	pub num_cbs: u32,
+}
+
+impl CbCount {
+	/// Proportion of notes which were combo breakers, from 0.0 to 1.0. Returns `None` if there are
+	/// no notes
+	pub fn cb_rate(&self) -> Option<f32> {
+		if self.num_notes == 0 {
+			None
+		} else {
+			Some(self.num_cbs as f32 / self.num_notes as f32)
+		}
+	}
+
+	fn add(&mut self, is_cb: bool) {
+		self.num_notes += 1;
+		self.num_cbs += is_cb as u32;
+	}
+}
+
+/// Combo breakers split up by the size of the chord they occurred in and by column, to answer
+/// questions like "do I only CB on chords?"
+///
+/// ```rust
+/// # use etterna::*;
+/// let points = vec![
+/// 	ScatterPoint::new(0.0, 0, Hit::Hit { deviation: 0.01 }, J4),
+/// 	ScatterPoint::new(0.5, 0, Hit::Miss, J4),
+/// 	ScatterPoint::new(0.5, 3, Hit::Hit { deviation: 0.02 }, J4),
+/// ];
+///
+/// let breakdown = CbBreakdown::calculate(&points, J4);
+/// assert_eq!(breakdown.singles, CbCount { num_notes: 1, num_cbs: 0 });
+/// assert_eq!(breakdown.jumps, CbCount { num_notes: 2, num_cbs: 1 });
+/// assert_eq!(breakdown.by_column[0].cb_rate(), Some(0.5));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CbBreakdown {
+	/// Notes that are alone on their row
+	pub singles: CbCount,
+	/// Notes in two-note chords
+	pub jumps: CbCount,
+	/// Notes in three-note chords
+	pub hands: CbCount,
+	/// Notes in chords of four or more notes
+	pub quads: CbCount,
+	/// Indexed by column. Has as many entries as needed to cover the highest column in the input
+	pub by_column: Vec<CbCount>,
+}
+
+impl CbBreakdown {
+	/// Counts the combo breakers on the given judge. The input must be sorted chronologically, like
+	/// the output of [`crate::ReplayV2Fast::scatter_plot`]. Notes with the exact same timestamp are
+	/// considered a chord.
+	pub fn calculate(points: &[crate::ScatterPoint], judge: &crate::Judge) -> Self {
+		let mut breakdown = Self::default();
+
+		let mut chord_start = 0;
+		while chord_start < points.len() {
+			let chord_second = points[chord_start].second;
+			let chord_len = points[chord_start..]
+				.iter()
+				.take_while(|point| point.second == chord_second)
+				.count();
+
+			for point in &points[chord_start..(chord_start + chord_len)] {
+				let is_cb = point.hit.is_cb(judge);
+
+				let by_chord_size = match chord_len {
+					1 => &mut breakdown.singles,
+					2 => &mut breakdown.jumps,
+					3 => &mut breakdown.hands,
+					_ => &mut breakdown.quads,
+				};
+				by_chord_size.add(is_cb);
+
+				let column = point.column as usize;
+				if breakdown.by_column.len() <= column {
+					breakdown.by_column.resize(column + 1, CbCount::default());
+				}
+				breakdown.by_column[column].add(is_cb);
+			}
+
+			chord_start += chord_len;
+		}
+
+		breakdown
+	}
+}
+
+/// Consecutive notes on a single column, each following the previous one within a short time. See
+/// [`JackAnalysis`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Jack {
+	pub column: u8,
+	pub start_second: f32,
+	pub end_second: f32,
+	/// At least two
+	pub num_notes: u32,
+	pub num_cbs: u32,
+}
+
+impl Jack {
+	/// Speed of this jack in notes per second on its column. Zero if all notes are at the same
+	/// second
+	pub fn notes_per_second(&self) -> f32 {
+		if self.end_second == self.start_second {
+			return 0.0;
+		}
+		self.num_notes.saturating_sub(1) as f32 / (self.end_second - self.start_second)
+	}
+}
+
+/// How well a player hit the jacks in a chart. Unlike [`crate::find_fastest_note_subset`], this
+/// only looks at same-column patterns.
+///
+/// ```rust
+/// # use etterna::*;
+/// let points = vec![
+/// 	ScatterPoint::new(0.0, 0, Hit::Hit { deviation: 0.01 }, J4),
+/// 	ScatterPoint::new(0.1, 0, Hit::Hit { deviation: 0.01 }, J4),
+/// 	ScatterPoint::new(0.2, 0, Hit::Hit { deviation: 0.01 }, J4),
+/// 	ScatterPoint::new(0.3, 1, Hit::Hit { deviation: 0.01 }, J4),
+/// ];
+///
+/// let analysis = JackAnalysis::calculate(&points, J4, 0.25);
+/// assert_eq!(analysis.jacks.len(), 1);
+/// assert_eq!(analysis.judgements.marvelouses, 3);
+/// assert_eq!(analysis.max_clean_speed, Some(10.0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JackAnalysis {
+	/// Sorted by start time
+	pub jacks: Vec<Jack>,
+	/// Judgements on all notes that are part of a jack
+	pub judgements: crate::TapJudgements,
+	/// Speed of the fastest jack without combo breakers, in notes per second. `None` if every jack
+	/// contained a combo breaker, or if there are no jacks
+	pub max_clean_speed: Option<f32>,
+}
+
+impl JackAnalysis {
+	/// Finds all jacks, i.e. runs of notes on the same column where each note is at most
+	/// `max_gap_seconds` after the previous, and evaluates the hits on them using the given judge.
+	///
+	/// The input must be sorted chronologically, like the output of
+	/// [`crate::ReplayV2Fast::scatter_plot`].
+	pub fn calculate(
+		points: &[crate::ScatterPoint],
+		judge: &crate::Judge,
+		max_gap_seconds: f32,
+	) -> Self {
+		let mut analysis = Self::default();
+
+		let num_columns = points
+			.iter()
+			.map(|p| p.column as usize + 1)
+			.max()
+			.unwrap_or(0);
+		for column in 0..num_columns {
+			let mut current_jack: Option<Jack> = None;
+			let mut previous_point: Option<&crate::ScatterPoint> = None;
+			for point in points.iter().filter(|p| p.column as usize == column) {
+				match previous_point {
+					Some(prev) if point.second - prev.second <= max_gap_seconds => {
+						let jack = current_jack.get_or_insert_with(|| Jack {
+							column: prev.column,
+							start_second: prev.second,
+							num_notes: 1,
+							num_cbs: prev.hit.is_cb(judge) as u32,
+							..Default::default()
+						});
+						jack.end_second = point.second;
+						jack.num_notes += 1;
+						jack.num_cbs += point.hit.is_cb(judge) as u32;
+					}
+					_ => analysis.jacks.extend(current_jack.take()),
+				}
+				previous_point = Some(point);
+			}
+			analysis.jacks.extend(current_jack);
+		}
+		analysis.jacks.sort_by(|a, b| {
+			a.start_second
+				.partial_cmp(&b.start_second)
+				.unwrap_or(std::cmp::Ordering::Equal)
+		});
+
+		for jack in &analysis.jacks {
+			let jack_points = points.iter().filter(|p| {
+				p.column == jack.column
+					&& p.second >= jack.start_second
+					&& p.second <= jack.end_second
+			});
+			for point in jack_points {
+				analysis.judgements[point.hit.classify(judge)] += 1;
+			}
+		}
+
+		analysis.max_clean_speed = analysis
+			.jacks
+			.iter()
+			.filter(|jack| jack.num_cbs == 0)
+			.map(|jack| jack.notes_per_second())
+			.fold(None, |max: Option<f32>, speed| {
+				Some(max.map_or(speed, |max| max.max(speed)))
+			});
+
+		analysis
+	}
+}
+
+/// A window of a replay in [`StaminaDrain`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrainWindow {
+	pub start_second: f32,
+	pub end_second: f32,
+	pub num_notes: u32,
+	pub wifescore: crate::Wifescore,
+}
+
+/// Accuracy decay over the course of a replay, a key stamina indicator.
+///
+/// ```rust
+/// # use etterna::*;
+/// // The player gets sloppier over time
+/// let hits = (0..100).map(|i| (i as f32, Hit::Hit { deviation: i as f32 / 1000.0 }));
+/// let drain = StaminaDrain::calculate::<Wife3>(hits, J4, 20.0, 10.0);
+/// assert_eq!(drain.windows.len(), 9);
+/// assert!(drain.slope.unwrap() < 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StaminaDrain {
+	/// Sorted by start time. Windows without notes are skipped
+	pub windows: Vec<DrainWindow>,
+	/// Slope of a line fitted through the wifescores of the windows over their center times, in
+	/// wifescore percentage points per second. Negative values mean the accuracy decayed. `None` if
+	/// there are less than two windows
+	pub slope: Option<f32>,
+}
+
+impl StaminaDrain {
+	/// Slides a window of `window_seconds` over the replay in steps of `step_seconds`, and
+	/// calculates the wifescore of the notes in each window. Mine hits and hold drops are not
+	/// included.
+	///
+	/// The input is an iterator over each note's time in seconds and the hit on that note, for
+	/// example from [`crate::ReplayV2Fast::scatter_plot`].
+	///
+	/// Returns no windows if `window_seconds` or `step_seconds` is not positive or NaN, or if
+	/// `step_seconds` is too small to make a difference at the replay's timestamps.
+	pub fn calculate<W: crate::Wife>(
+		hits: impl IntoIterator<Item = (f32, crate::Hit)>,
+		judge: &crate::Judge,
+		window_seconds: f32,
+		step_seconds: f32,
+	) -> Self {
+		let mut hits: Vec<(f32, crate::Hit)> = hits.into_iter().collect();
+		hits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+		let (first_second, last_second) = match (hits.first(), hits.last()) {
+			(Some(first), Some(last)) => (first.0, last.0),
+			_ => return Self::default(),
+		};
+		// negated comparisons to catch NaN. If a step doesn't change the last timestamp, there are
+		// too many windows to be useful, or they wouldn't even advance in f32 precision
+		if !(window_seconds > 0.0 && step_seconds > 0.0)
+			|| last_second + step_seconds == last_second
+		{
+			return Self::default();
+		}
+
+		let mut windows = Vec::new();
+		// window starts are computed from an integer index instead of accumulated, so that
+		// rounding errors can't add up or stall the loop. The index is 64-bit, because a small step
+		// over a long replay needs more windows than fit into a u32
+		for window_index in 0u64.. {
+			let start_second = first_second + window_index as f32 * step_seconds;
+			let end_second = start_second + window_seconds;
+			let start_index = hits.partition_point(|&(s, _)| s < start_second);
+			let end_index = hits.partition_point(|&(s, _)| s < end_second);
+			let window_hits = hits[start_index..end_index].iter().map(|&(_, hit)| hit);
+			if let Some(wifescore) = W::apply(window_hits, 0, 0, judge) {
+				windows.push(DrainWindow {
+					start_second,
+					end_second,
+					num_notes: (end_index - start_index) as u32,
+					wifescore,
+				});
+			}
+
+			if end_second > last_second {
+				break;
+			}
+		}
+
+		let slope = if windows.len() >= 2 {
+			let points: Vec<(f32, f32)> = windows
+				.iter()
+				.map(|w| {
+					let center_second = (w.start_second + w.end_second) / 2.0;
+					(center_second, w.wifescore.as_percent())
+				})
+				.collect();
+			let n = points.len() as f32;
+			let mean_x = points.iter().map(|&(x, _)| x).sum::<f32>() / n;
+			let mean_y = points.iter().map(|&(_, y)| y).sum::<f32>() / n;
+			let covariance: f32 = points
+				.iter()
+				.map(|&(x, y)| (x - mean_x) * (y - mean_y))
+				.sum();
+			let variance: f32 = points.iter().map(|&(x, _)| (x - mean_x).powi(2)).sum();
+			Some(covariance / variance)
+		} else {
+			None
+		};
+
+		Self { windows, slope }
+	}
+}
+
+/// A section of a chart that can be jumptrilled. See [`JumptrillAnalysis`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JumptrillSpan {
+	/// Position of the first row, at 1.00x
+	pub start_second: f32,
+	/// Position of the last row, at 1.00x
+	pub end_second: f32,
+	pub num_rows: u32,
+	pub num_notes: u32,
+}
+
+/// Finds the parts of a chart that can be trivially jumptrilled, i.e. where the rows alternate
+/// between two sets of lanes that don't overlap, like `[12]3[12]4[12]3`. Spamming both chords in
+/// alternation hits every note of such a section.
+///
+/// Sequences where one of the two sets has only a single lane are plain trills or rolls and are
+/// not reported.
+///
+/// ```rust
+/// # use etterna::*;
+/// let mut note_data = NoteData::new(4);
+/// for (i, columns) in [[0, 1], [2, 3], [0, 1], [2, 3], [0, 2]].iter().enumerate() {
+/// 	for &column in columns {
+/// 		note_data.add_note(i as u32 * 12, column, NoteType::Tap);
+/// 	}
+/// }
+///
+/// let timing_info = TimingInfo::from_constant_bpm(120.0);
+/// let analysis = JumptrillAnalysis::calculate(&note_data, &timing_info, 4);
+/// assert_eq!(analysis.spans.len(), 1);
+/// assert_eq!(analysis.spans[0].num_rows, 4);
+/// assert_eq!(analysis.jumptrillable_proportion, 0.8);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JumptrillAnalysis {
+	/// Sorted chronologically, not overlapping
+	pub spans: Vec<JumptrillSpan>,
+	/// Proportion of the chart's taps which are inside a jumptrillable span, from 0.0 to 1.0
+	pub jumptrillable_proportion: f32,
+}
+
+impl JumptrillAnalysis {
+	/// Only rows containing taps or hold heads are considered. Spans with fewer than `min_rows`
+	/// rows are not reported
+	pub fn calculate(
+		note_data: &crate::NoteData,
+		timing_info: &crate::TimingInfo,
+		min_rows: usize,
+	) -> Self {
+		let rows: Vec<&crate::ChartRow> = note_data
+			.rows()
+			.iter()
+			.filter(|row| row.taps.bits() != 0)
+			.collect();
+		let ticks: Vec<u32> = rows.iter().map(|row| row.tick).collect();
+		let seconds = timing_info.ticks_to_seconds(&ticks);
+
+		let mut analysis = Self::default();
+		let mut push_span = |span: &[&crate::ChartRow], start: usize, lanes: [u32; 2]| {
+			let is_jumptrill = lanes.iter().all(|lanes| lanes.count_ones() >= 2);
+			if span.len() >= min_rows.max(2) && is_jumptrill {
+				analysis.spans.push(JumptrillSpan {
+					start_second: seconds[start],
+					end_second: seconds[start + span.len() - 1],
+					num_rows: span.len() as u32,
+					num_notes: span.iter().map(|row| row.taps.bits().count_ones()).sum(),
+				});
+				true
+			} else {
+				false
+			}
+		};
+
+		let mut start = 0;
+		while start < rows.len() {
+			// Union of the lanes of every other row, starting with the first row of the span
+			let mut lanes = [rows[start].taps.bits(), 0];
+			let mut end = start + 1;
+			while end < rows.len() {
+				let row_lanes = rows[end].taps.bits();
+				let own = (end - start) % 2;
+				if row_lanes & lanes[1 - own] != 0 {
+					break;
+				}
+				lanes[own] |= row_lanes;
+				end += 1;
+			}
+			let was_pushed = push_span(&rows[start..end], start, lanes);
+
+			// The row that broke the span may still form a new span together with the row before,
+			// unless that row is already part of a reported span
+			start = if end - start >= 2 && !was_pushed {
+				end - 1
+			} else {
+				end
+			};
+		}
+
+		let num_notes: u32 = rows.iter().map(|row| row.taps.bits().count_ones()).sum();
+		let num_jumptrillable_notes: u32 = analysis.spans.iter().map(|span| span.num_notes).sum();
+		if num_notes > 0 {
+			analysis.jumptrillable_proportion = num_jumptrillable_notes as f32 / num_notes as f32;
+		}
+
+		analysis
+	}
+}
+
+/// A long run of dense notes on a single lane. See [`find_anchors`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Anchor {
+	/// Index of the lane in the input slice
+	pub column: usize,
+	pub start_second: f32,
+	pub end_second: f32,
+	pub num_notes: u32,
+}
+
+impl Anchor {
+	/// Note density of this anchor in notes per second. Zero if all notes are at the same second
+	pub fn notes_per_second(&self) -> f32 {
+		if self.end_second == self.start_second {
+			return 0.0;
+		}
+		self.num_notes.saturating_sub(1) as f32 / (self.end_second - self.start_second)
+	}
+}
+
+/// Finds anchors, i.e. spans where a single lane has at least `min_notes` notes within
+/// `window_seconds`. Overlapping spans on the same lane are merged.
+///
+/// Unlike [`crate::find_fastest_note_subset`], this reports every anchor with its location, and
+/// since it operates on the chart, anchors aren't cut short by combo breaks. The input contains the
+/// sorted note positions of each lane, for example from [`crate::NoteData::lane_note_seconds`].
+/// The result is sorted by start time.
+///
+/// ```rust
+/// # use etterna::*;
+/// let lanes = vec![
+/// 	vec![0.0, 0.2, 0.4, 0.6, 0.8, 5.0],
+/// 	vec![0.1, 0.5, 0.9],
+/// ];
+/// let anchors = find_anchors(&lanes, 0.5, 3);
+/// assert_eq!(anchors, vec![Anchor { column: 0, start_second: 0.0, end_second: 0.8, num_notes: 5 }]);
+/// assert_eq!(anchors[0].notes_per_second(), 5.0);
+/// ```
+pub fn find_anchors(lanes: &[Vec<f32>], window_seconds: f32, min_notes: u32) -> Vec<Anchor> {
+	let min_notes = min_notes.max(2) as usize;
+
+	let mut anchors = Vec::new();
+	for (column, note_seconds) in lanes.iter().enumerate() {
+		let mut current_anchor: Option<(usize, usize)> = None;
+		for start in 0..note_seconds.len() {
+			let window_end = note_seconds[start] + window_seconds;
+			let end = start + note_seconds[start..].partition_point(|&s| s <= window_end);
+			if end - start < min_notes {
+				continue;
+			}
+
+			current_anchor = match current_anchor {
+				Some((anchor_start, anchor_end)) if start < anchor_end => {
+					Some((anchor_start, anchor_end.max(end)))
+				}
+				other => {
+					anchors.extend(other.map(|range| (column, range)));
+					Some((start, end))
+				}
+			};
+		}
+		anchors.extend(current_anchor.map(|range| (column, range)));
+	}
+
+	let mut anchors: Vec<Anchor> = anchors
+		.into_iter()
+		.map(|(column, (start, end))| Anchor {
+			column,
+			start_second: lanes[column][start],
+			end_second: lanes[column][end - 1],
+			num_notes: (end - start) as u32,
+		})
+		.collect();
+	anchors.sort_by(|a, b| {
+		a.start_second
+			.partial_cmp(&b.start_second)
+			.unwrap_or(std::cmp::Ordering::Equal)
+	});
+	anchors
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Hit;
+
+	/// Scatter point judged on J4
+	fn point(second: f32, column: u8, hit: Hit) -> crate::ScatterPoint {
+		crate::ScatterPoint::new(second, column, hit, crate::J4)
+	}
+
+	#[test]
+	fn test_find_cb_clumps() {
+		let hits = vec![
+			(0.0, Hit::Miss),
+			(0.5, Hit::Miss),
+			(1.0, Hit::Miss),
+			(1.8, Hit::Miss),
+			(2.6, Hit::Miss),
+			(5.0, Hit::Hit { deviation: 0.0 }),
+			(6.0, Hit::Miss),
+			(9.0, Hit::Miss),
+			(9.1, Hit::Hit { deviation: -0.15 }),
+			(9.2, Hit::Hit { deviation: 0.15 }),
+		];
+
+		assert_eq!(
+			find_cb_clumps(hits.clone(), crate::J4, 3, 1.0),
+			vec![
+				CbClump {
+					start_second: 0.0,
+					end_second: 1.0,
+					num_cbs: 3,
+				},
+				CbClump {
+					start_second: 9.0,
+					end_second: 9.2,
+					num_cbs: 3,
+				},
+			],
+		);
+		// With a larger window, the first clump extends further
+		assert_eq!(
+			find_cb_clumps(hits.clone(), crate::J4, 3, 2.0)[0],
+			CbClump {
+				start_second: 0.0,
+				end_second: 2.6,
+				num_cbs: 5,
+			},
+		);
+		assert!(find_cb_clumps(hits, crate::J4, 4, 0.5).is_empty());
+	}
+
+	#[test]
+	fn test_section_bias() {
+		let hits = vec![
+			(0.0, Hit::Hit { deviation: -0.01 }),
+			(1.0, Hit::Hit { deviation: 0.01 }),
+			(2.0, Hit::Miss),
+			(3.0, Hit::Hit { deviation: 0.02 }),
+			(4.0, Hit::Hit { deviation: 0.04 }),
+		];
+
+		let bias = SectionBias::calculate(hits, 4);
+		assert_eq!(
+			bias.sections
+				.iter()
+				.map(|s| (s.start_second, s.num_hits, s.mean_deviation))
+				.collect::<Vec<_>>(),
+			vec![
+				(0.0, 1, Some(-0.01)),
+				(1.0, 1, Some(0.01)),
+				(2.0, 0, None),
+				(3.0, 2, Some(0.03)),
+			],
+		);
+		assert_eq!(bias.drift(), Some(0.04));
+
+		assert!(SectionBias::calculate(vec![], 4).sections.is_empty());
+		assert_eq!(SectionBias::default().drift(), None);
+	}
+
+	#[test]
+	fn test_find_mash_segments() {
+		let clean_lane = crate::NoteAndHitSeconds {
+			note_seconds: vec![0.0, 0.5, 1.0, 1.5, 2.0],
+			hit_seconds: vec![0.01, 0.49, 1.02, 1.5, 2.0],
+		};
+		let mashed_lane = crate::NoteAndHitSeconds {
+			note_seconds: vec![0.0, 2.0, 4.0],
+			hit_seconds: vec![
+				0.0, // clean
+				2.0, 2.1, 2.2, 2.3, 2.4, 2.5, 2.6, 2.7, 2.8, 2.9, 3.0, 3.1, 3.2,
+				3.3, // mashed
+				4.0, // clean
+			],
+		};
+
+		let segments = find_mash_segments(&[clean_lane, mashed_lane], 1.0, 2.0);
+		assert_eq!(segments.len(), 1);
+		let segment = &segments[0];
+		assert_eq!(segment.column, 1);
+		assert_eq!((segment.start_second, segment.end_second), (2.0, 4.0));
+		assert_eq!((segment.num_notes, segment.num_hits), (1, 14));
+		assert!(segment.confidence > 0.9);
+	}
+
+	#[test]
+	fn test_find_mash_segments_degenerate_windows() {
+		let lanes = [crate::NoteAndHitSeconds {
+			note_seconds: vec![100_000.0],
+			hit_seconds: vec![100_000.0, 100_000.01, 100_001.0],
+		}];
+		// used to loop forever, because the window start stopped advancing in f32 precision
+		assert!(find_mash_segments(&lanes, 1e-6, 2.0).is_empty());
+		assert!(find_mash_segments(&lanes, f32::NAN, 2.0).is_empty());
+		assert!(find_mash_segments(&lanes, 0.0, 2.0).is_empty());
+	}
+
+	#[test]
+	fn test_cb_breakdown() {
+		let points = vec![
+			point(0.0, 0, Hit::Miss),
+			point(0.0, 1, Hit::Hit { deviation: 0.0 }),
+			point(0.0, 2, Hit::Hit { deviation: 0.0 }),
+			point(1.0, 0, Hit::Hit { deviation: 0.1 }),
+			point(1.0, 1, Hit::Hit { deviation: 0.0 }),
+			point(1.0, 2, Hit::Hit { deviation: 0.0 }),
+			point(1.0, 3, Hit::Hit { deviation: -0.1 }),
+			point(2.0, 5, Hit::Hit { deviation: 0.0 }),
+		];
+
+		let breakdown = CbBreakdown::calculate(&points, crate::J4);
+		let counts = |count: CbCount| (count.num_notes, count.num_cbs);
+		assert_eq!(counts(breakdown.singles), (1, 0));
+		assert_eq!(counts(breakdown.jumps), (0, 0));
+		assert_eq!(counts(breakdown.hands), (3, 1));
+		assert_eq!(counts(breakdown.quads), (4, 2));
+		assert_eq!(breakdown.by_column.len(), 6);
+		assert_eq!(breakdown.by_column[0].cb_rate(), Some(1.0));
+		assert_eq!(breakdown.by_column[4].cb_rate(), None);
+		assert!(CbBreakdown::calculate(&[], crate::J4).by_column.is_empty());
+	}
+
+	#[test]
+	fn test_jack_analysis() {
+		let points = vec![
+			// Slow, clean jack
+			point(0.0, 0, Hit::Hit { deviation: 0.0 }),
+			point(0.0, 1, Hit::Hit { deviation: 0.0 }),
+			point(0.2, 0, Hit::Hit { deviation: 0.0 }),
+			point(0.4, 0, Hit::Hit { deviation: 0.03 }),
+			// Not a jack: the gap is too large
+			point(1.0, 1, Hit::Hit { deviation: 0.0 }),
+			// Fast jack with a CB
+			point(2.0, 2, Hit::Hit { deviation: 0.0 }),
+			point(2.1, 2, Hit::Miss),
+		];
+
+		let analysis = JackAnalysis::calculate(&points, crate::J4, 0.3);
+		assert_eq!(
+			analysis.jacks,
+			vec![
+				Jack {
+					column: 0,
+					start_second: 0.0,
+					end_second: 0.4,
+					num_notes: 3,
+					num_cbs: 0,
+				},
+				Jack {
+					column: 2,
+					start_second: 2.0,
+					end_second: 2.1,
+					num_notes: 2,
+					num_cbs: 1,
+				},
+			]
+		);
+		assert_eq!(analysis.judgements.marvelouses, 3);
+		assert_eq!(analysis.judgements.perfects, 1);
+		assert_eq!(analysis.judgements.misses, 1);
+		assert_eq!(analysis.max_clean_speed, Some(5.0));
+		assert_eq!(
+			JackAnalysis::calculate(&points, crate::J4, 0.05).jacks,
+			vec![]
+		);
+	}
+
+	#[test]
+	fn test_jack_without_duration() {
+		let points = vec![
+			point(1.0, 0, Hit::Hit { deviation: 0.0 }),
+			point(1.0, 0, Hit::Hit { deviation: 0.0 }),
+		];
+		let analysis = JackAnalysis::calculate(&points, crate::J4, 0.3);
+		assert_eq!(analysis.jacks[0].notes_per_second(), 0.0);
+		assert_eq!(analysis.max_clean_speed, Some(0.0));
+	}
+
+	#[test]
+	fn test_stamina_drain() {
+		let hits = vec![
+			(0.0, Hit::Hit { deviation: 0.0 }),
+			(1.0, Hit::Hit { deviation: 0.0 }),
+			(2.0, Hit::Miss),
+			(3.0, Hit::Hit { deviation: 0.0 }),
+			(10.0, Hit::Miss),
+		];
+
+		let drain = StaminaDrain::calculate::<crate::Wife3>(hits.clone(), crate::J4, 2.0, 2.0);
+		assert_eq!(
+			drain
+				.windows
+				.iter()
+				.map(|w| (w.start_second, w.num_notes, w.wifescore.as_percent()))
+				.collect::<Vec<_>>(),
+			vec![(0.0, 2, 100.0), (2.0, 2, -87.5), (10.0, 1, -275.0)],
+		);
+		assert!(drain.slope.unwrap() < -30.0);
+
+		let single_window = StaminaDrain::calculate::<crate::Wife3>(hits, crate::J4, 20.0, 1.0);
+		assert_eq!(single_window.windows.len(), 1);
+		assert_eq!(single_window.slope, None);
+		assert_eq!(
+			StaminaDrain::calculate::<crate::Wife3>(vec![], crate::J4, 1.0, 1.0),
+			StaminaDrain::default()
+		);
+	}
+
+	#[test]
+	fn test_stamina_drain_tiny_step() {
+		let hits = vec![
+			(100.0, Hit::Hit { deviation: 0.0 }),
+			(101.0, Hit::Hit { deviation: 0.0 }),
+		];
+		// used to loop forever, because the window start stopped advancing in f32 precision
+		let drain = StaminaDrain::calculate::<crate::Wife3>(hits.clone(), crate::J4, 0.5, 1e-6);
+		assert_eq!(drain, StaminaDrain::default());
+		let drain = StaminaDrain::calculate::<crate::Wife3>(hits.clone(), crate::J4, f32::NAN, 1.0);
+		assert_eq!(drain, StaminaDrain::default());
+
+		// representable steps still work, without accumulating rounding errors
+		let drain = StaminaDrain::calculate::<crate::Wife3>(hits, crate::J4, 0.5, 0.1);
+		let starts: Vec<f32> = drain.windows.iter().map(|w| w.start_second).collect();
+		assert_eq!(starts, vec![100.0, 100.0 + 6.0 * 0.1]);
+	}
+
+	#[test]
+	fn test_jumptrill_analysis() {
+		let mut note_data = crate::NoteData::new(4);
+		let rows: &[&[u8]] = &[
+			// Split jumptrill
+			&[0, 1],
+			&[2],
+			&[0, 1],
+			&[3],
+			&[0, 1],
+			// Breaks the jumptrill and starts a new one
+			&[0, 2],
+			&[1, 3],
+			&[0, 2],
+			// Continues the jumptrill, because it fits into its chords
+			&[3],
+			// Only a trill
+			&[3],
+			&[2],
+			&[3],
+			&[2],
+		];
+		for (i, columns) in rows.iter().enumerate() {
+			for &column in columns.iter() {
+				note_data.add_note(i as u32 * 12, column, crate::NoteType::Tap);
+			}
+		}
+		note_data.add_note(1000, 0, crate::NoteType::Mine);
+
+		let timing_info = crate::TimingInfo::from_constant_bpm(60.0);
+		let analysis = JumptrillAnalysis::calculate(&note_data, &timing_info, 3);
+		assert_eq!(
+			analysis.spans,
+			vec![
+				JumptrillSpan {
+					start_second: 0.0,
+					end_second: 1.0,
+					num_rows: 5,
+					num_notes: 8,
+				},
+				JumptrillSpan {
+					start_second: 1.25,
+					end_second: 2.0,
+					num_rows: 4,
+					num_notes: 7,
+				},
+			]
+		);
+		assert_eq!(analysis.jumptrillable_proportion, 15.0 / 19.0);
+
+		let empty = crate::NoteData::new(4);
+		assert_eq!(
+			JumptrillAnalysis::calculate(&empty, &timing_info, 3),
+			JumptrillAnalysis::default()
+		);
+	}
+
+	#[test]
+	fn test_find_anchors() {
+		let lanes = vec![
+			vec![0.0, 1.0, 2.0, 3.0],
+			vec![],
+			// Two anchors, separated by a gap
+			vec![0.5, 0.6, 0.7, 3.0, 3.1, 3.2, 3.3],
+		];
+
+		let anchors = find_anchors(&lanes, 0.25, 3);
+		assert_eq!(
+			anchors
+				.iter()
+				.map(|a| (a.column, a.start_second, a.num_notes))
+				.collect::<Vec<_>>(),
+			vec![(2, 0.5, 3), (2, 3.0, 4)],
+		);
+		// With a larger window, the anchors on the third lane are merged
+		assert_eq!(
+			find_anchors(&lanes, 3.0, 4)
+				.iter()
+				.map(|a| (a.column, a.start_second, a.num_notes))
+				.collect::<Vec<_>>(),
+			vec![(0, 0.0, 4), (2, 0.5, 7)],
+		);
+		assert!(find_anchors(&lanes, 0.05, 2).is_empty());
+	}
+
+	#[test]
+	fn test_anchor_without_duration() {
+		let anchors = find_anchors(&[vec![1.0, 1.0]], 0.5, 1);
+		assert_eq!(anchors.len(), 1);
+		assert_eq!(anchors[0].notes_per_second(), 0.0);
+	}
+}
@@ -0,0 +1,129 @@
+//! Compact binary serialization for caching parsed data, enabled by the `binary-cache` feature.
+
+use thiserror::Error;
+
+/// Identifies binary cache data produced by this crate
+const MAGIC: [u8; 4] = *b"ETTC";
+
+/// Bump this whenever the serialized layout of any [`BinaryCache`] type changes. Caches written with
+/// a different version are rejected with [`BinaryCacheError::VersionMismatch`]
+pub const BINARY_CACHE_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = MAGIC.len() + 2 + 1;
+
+#[derive(Debug, Error)]
+pub enum BinaryCacheError {
+	#[error("Data is not a binary cache")]
+	InvalidHeader,
+	#[error("Binary cache has version {found}, but version {expected} is required")]
+	VersionMismatch { found: u16, expected: u16 },
+	#[error("Binary cache contains a different type of data")]
+	WrongKind,
+	#[error("Binary cache is corrupted: {0}")]
+	Decode(#[from] postcard::Error),
+}
+
+/// Types that can be cached in a compact, versioned binary format, e.g. to skip re-parsing text
+/// replays on every run.
+///
+/// ```rust
+/// # use etterna::*;
+/// let replay = parse_replay_v2_fast(b"0 0.01 0\n48 -0.02 1\n").unwrap();
+/// let bytes = replay.to_cache_bytes();
+/// assert_eq!(ReplayV2Fast::from_cache_bytes(&bytes).unwrap(), replay);
+/// assert!(NoteData::from_cache_bytes(&bytes).is_err());
+/// ```
+pub trait BinaryCache: serde::Serialize + serde::de::DeserializeOwned {
+	/// Distinguishes the cached types from each other, so that data of one type can't be
+	/// accidentally decoded as another type
+	#[doc(hidden)]
+	const KIND: u8;
+
+	fn to_cache_bytes(&self) -> Vec<u8> {
+		let mut bytes = Vec::with_capacity(HEADER_LEN);
+		bytes.extend_from_slice(&MAGIC);
+		bytes.extend_from_slice(&BINARY_CACHE_VERSION.to_le_bytes());
+		bytes.push(Self::KIND);
+		postcard::to_extend(self, bytes).expect("serializing into a Vec can't fail")
+	}
+
+	fn from_cache_bytes(bytes: &[u8]) -> Result<Self, BinaryCacheError> {
+		if bytes.len() < HEADER_LEN || bytes[..MAGIC.len()] != MAGIC {
+			return Err(BinaryCacheError::InvalidHeader);
+		}
+
+		let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+		if version != BINARY_CACHE_VERSION {
+			return Err(BinaryCacheError::VersionMismatch {
+				found: version,
+				expected: BINARY_CACHE_VERSION,
+			});
+		}
+		if bytes[6] != Self::KIND {
+			return Err(BinaryCacheError::WrongKind);
+		}
+
+		Ok(postcard::from_bytes(&bytes[HEADER_LEN..])?)
+	}
+}
+
+impl BinaryCache for crate::ReplayV2Fast {
+	const KIND: u8 = 0;
+}
+
+impl BinaryCache for crate::NoteData {
+	const KIND: u8 = 1;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_note_data_roundtrip() {
+		let mut note_data = crate::NoteData::new(4);
+		note_data.add_note(0, 0, crate::NoteType::Tap);
+		note_data.add_note(48, 3, crate::NoteType::Mine);
+		let bytes = note_data.to_cache_bytes();
+		assert_eq!(
+			crate::NoteData::from_cache_bytes(&bytes).unwrap(),
+			note_data
+		);
+	}
+
+	#[test]
+	fn test_invalid_note_data() {
+		let mut note_data = crate::NoteData::new(4);
+		note_data.add_note(0, 3, crate::NoteType::Tap);
+		note_data.add_note(48, 0, crate::NoteType::Tap);
+		let bytes = note_data.to_cache_bytes();
+
+		// the keycount directly follows the header
+		let mut too_few_columns = bytes.clone();
+		too_few_columns[HEADER_LEN] = 2;
+		assert!(matches!(
+			crate::NoteData::from_cache_bytes(&too_few_columns),
+			Err(BinaryCacheError::Decode(_))
+		));
+		let mut too_many_columns = bytes;
+		too_many_columns[HEADER_LEN] = 33;
+		assert!(crate::NoteData::from_cache_bytes(&too_many_columns).is_err());
+	}
+
+	#[test]
+	fn test_invalid_cache() {
+		let replay = crate::ReplayV2Fast::default();
+		let mut bytes = replay.to_cache_bytes();
+
+		assert!(matches!(
+			crate::ReplayV2Fast::from_cache_bytes(&bytes[..3]),
+			Err(BinaryCacheError::InvalidHeader)
+		));
+
+		bytes[4] = bytes[4].wrapping_add(1);
+		assert!(matches!(
+			crate::ReplayV2Fast::from_cache_bytes(&bytes),
+			Err(BinaryCacheError::VersionMismatch { .. })
+		));
+	}
+}
@@ -0,0 +1,345 @@
+//! Parsing of BMS charts (.bms, .bme, .bml) into [`crate::NoteData`], enabled by the `bms` feature.
+
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// BPM used by BMS charts without a `#BPM` header, as defined by the format
+const DEFAULT_BPM: f64 = 130.0;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BmsParseError {
+	#[error("Chart doesn't contain a single note")]
+	NoNotes,
+}
+
+/// A chart parsed with [`parse_bms`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BmsChart {
+	pub title: Option<String>,
+	pub artist: Option<String>,
+	/// Columns are ordered left to right as displayed in-game. In single play, the scratch lane is
+	/// the leftmost column. In double play, player 2's scratch lane is the rightmost column.
+	pub note_data: crate::NoteData,
+	pub timing_info: crate::TimingInfo,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ObjectKind {
+	Normal,
+	LongNote,
+	Mine,
+}
+
+struct Object {
+	beat: f64,
+	/// Lane as (player, key), where key 0 is the scratch lane and 1-7 are the keys
+	lane: (u8, u8),
+	kind: ObjectKind,
+	value: u16,
+}
+
+/// Parses a base 36 object value like `0Z`
+fn parse_base36(digits: &[u8]) -> Option<u16> {
+	let mut value = 0;
+	for &digit in digits {
+		value = value * 36 + (digit as char).to_digit(36)? as u16;
+	}
+	Some(value)
+}
+
+/// Maps a BMS channel to its player and key. Returns None for channels that aren't played, like
+/// BGM, invisible notes and the free zone
+fn parse_note_channel(channel: &[u8]) -> Option<(ObjectKind, (u8, u8))> {
+	let (kind, player) = match channel[0] {
+		b'1' => (ObjectKind::Normal, 1),
+		b'2' => (ObjectKind::Normal, 2),
+		b'5' => (ObjectKind::LongNote, 1),
+		b'6' => (ObjectKind::LongNote, 2),
+		b'D' | b'd' => (ObjectKind::Mine, 1),
+		b'E' | b'e' => (ObjectKind::Mine, 2),
+		_ => return None,
+	};
+	let key = match channel[1] {
+		key @ b'1'..=b'5' => key - b'0',
+		b'6' => 0,
+		b'8' => 6,
+		b'9' => 7,
+		_ => return None,
+	};
+	Some((kind, (player, key)))
+}
+
+/// Parses a BMS chart, for analysis and rescoring. Keysounds, BGA, stops and scroll speed changes
+/// are ignored.
+///
+/// The keymode is detected from the used channels: 5 or 7 keys plus scratch, in single or double
+/// play. `#RANDOM` blocks always take the first branch, so the result is deterministic.
+///
+/// ```rust
+/// # use etterna::*;
+/// let chart = parse_bms(b"
+/// #TITLE Example
+/// #BPM 120
+/// #00111:01000100
+/// #00116:00000001
+/// #00203:F0
+/// #00211:0101
+/// ").unwrap();
+///
+/// assert_eq!(chart.title.as_deref(), Some("Example"));
+/// assert_eq!(chart.note_data.keycount(), 6);
+/// assert_eq!(chart.note_data.ticks(), vec![192, 288, 336, 384, 480]);
+/// // the BPM doubles from 120 to 240 (hex F0) at the start of the third measure
+/// let seconds = chart.note_data.row_seconds(&chart.timing_info);
+/// assert_eq!(seconds, vec![2.0, 3.0, 3.5, 4.0, 4.5]);
+/// ```
+pub fn parse_bms(bytes: &[u8]) -> Result<BmsChart, BmsParseError> {
+	let mut title = None;
+	let mut artist = None;
+	let mut bpm = DEFAULT_BPM;
+	let mut extended_bpms: HashMap<u16, f64> = HashMap::new();
+	let mut ln_object = None;
+	let mut measure_lengths: HashMap<u32, f64> = HashMap::new();
+	// (measure, channel, data)
+	let mut channel_lines: Vec<(u32, [u8; 2], &[u8])> = Vec::new();
+	// whether each enclosing #IF block is active
+	let mut if_stack: Vec<bool> = Vec::new();
+
+	for line in bytes.split(|&c| c == b'\n') {
+		let line = crate::util::trim_bstr(line);
+		let line = crate::some_or_continue!(line.strip_prefix(b"#"));
+		let upper = line.to_ascii_uppercase();
+
+		// Control flow. Every #RANDOM is assumed to roll 1
+		if let Some(condition) = upper.strip_prefix(b"IF") {
+			if_stack.push(crate::util::trim_bstr(condition) == b"1");
+			continue;
+		}
+		if upper.starts_with(b"ENDIF") {
+			if_stack.pop();
+			continue;
+		}
+		if if_stack.contains(&false) {
+			continue;
+		}
+
+		let header_value = |name: &[u8]| {
+			if upper.starts_with(name) {
+				Some(crate::util::trim_bstr(&line[name.len()..]))
+			} else {
+				None
+			}
+		};
+		if let Some(value) = header_value(b"TITLE ") {
+			title = Some(String::from_utf8_lossy(value).into_owned());
+		} else if let Some(value) = header_value(b"ARTIST ") {
+			artist = Some(String::from_utf8_lossy(value).into_owned());
+		} else if let Some(value) = header_value(b"BPM ") {
+			bpm = lexical_core::parse_lossy(value).unwrap_or(bpm);
+		} else if let Some(value) = header_value(b"LNOBJ ") {
+			ln_object = parse_base36(value);
+		} else if upper.starts_with(b"BPM") && line.len() > 6 {
+			let index = crate::some_or_continue!(parse_base36(&line[3..5]));
+			let value = crate::util::trim_bstr(&line[5..]);
+			if let Ok(value) = lexical_core::parse_lossy(value) {
+				extended_bpms.insert(index, value);
+			}
+		} else if line.len() > 6
+			&& line[5] == b':'
+			&& line[..5].iter().all(u8::is_ascii_alphanumeric)
+		{
+			let measure = crate::some_or_continue!(btoi::btou(&line[..3]).ok());
+			let channel = [upper[3], upper[4]];
+			let data = crate::util::trim_bstr(&line[6..]);
+			if &channel == b"02" {
+				if let Ok(length) = lexical_core::parse_lossy::<f64>(data) {
+					if length > 0.0 {
+						measure_lengths.insert(measure, length);
+					}
+				}
+			} else {
+				channel_lines.push((measure, channel, data));
+			}
+		}
+	}
+
+	let num_measures = channel_lines
+		.iter()
+		.map(|&(measure, _, _)| measure + 1)
+		.max();
+	let measure_start_beats: Vec<f64> = (0..=num_measures.unwrap_or(0))
+		.scan(0.0, |beat, measure| {
+			let start = *beat;
+			*beat += 4.0 * measure_lengths.get(&measure).copied().unwrap_or(1.0);
+			Some(start)
+		})
+		.collect();
+
+	let mut bpm_changes = Vec::new();
+	let mut objects = Vec::new();
+	for (measure, channel, data) in channel_lines {
+		let measure_start = measure_start_beats[measure as usize];
+		let measure_beats = measure_start_beats[measure as usize + 1] - measure_start;
+		let num_slots = data.len() / 2;
+		for (i, digits) in data.chunks_exact(2).enumerate() {
+			let beat = measure_start + measure_beats * i as f64 / num_slots as f64;
+			match &channel {
+				b"03" => {
+					if let Ok(bpm) = u8::from_str_radix(&String::from_utf8_lossy(digits), 16) {
+						if bpm != 0 {
+							bpm_changes.push((beat, bpm as f64));
+						}
+					}
+				}
+				b"08" => {
+					let index = crate::some_or_continue!(parse_base36(digits));
+					if let Some(&bpm) = extended_bpms.get(&index) {
+						bpm_changes.push((beat, bpm));
+					}
+				}
+				_ => {
+					let (kind, lane) = crate::some_or_continue!(parse_note_channel(&channel));
+					let value = crate::some_or_continue!(parse_base36(digits));
+					if value != 0 {
+						objects.push(Object {
+							beat,
+							lane,
+							kind,
+							value,
+						});
+					}
+				}
+			}
+		}
+	}
+
+	if !objects.iter().any(|object| object.kind != ObjectKind::Mine) {
+		return Err(BmsParseError::NoNotes);
+	}
+
+	let is_double_play = objects.iter().any(|object| object.lane.0 == 2);
+	let is_seven_key = objects.iter().any(|object| object.lane.1 >= 6);
+	let keys_per_side: u8 = if is_seven_key { 8 } else { 6 };
+	let column = |(player, key): (u8, u8)| match player {
+		1 => key,
+		// player 2's scratch is on the right side
+		_ if key == 0 => 2 * keys_per_side - 1,
+		_ => keys_per_side + key - 1,
+	};
+	let keycount = if is_double_play {
+		2 * keys_per_side
+	} else {
+		keys_per_side
+	};
+
+	objects.sort_by(|a, b| {
+		a.beat
+			.partial_cmp(&b.beat)
+			.unwrap_or(std::cmp::Ordering::Equal)
+	});
+	let mut note_data = crate::NoteData::new(keycount);
+	let mut long_note_open = [false; 16];
+	// tick of the last normal note per column, to turn it into a hold head when an #LNOBJ follows
+	let mut last_normal_note: [Option<u32>; 16] = [None; 16];
+	for object in objects {
+		let tick = (object.beat * 48.0).round() as u32;
+		let column = column(object.lane);
+		let note_type = match object.kind {
+			ObjectKind::Mine => crate::NoteType::Mine,
+			ObjectKind::LongNote => {
+				let open = &mut long_note_open[column as usize];
+				*open = !*open;
+				if *open {
+					crate::NoteType::HoldHead
+				} else {
+					crate::NoteType::HoldTail
+				}
+			}
+			ObjectKind::Normal if Some(object.value) == ln_object => {
+				match last_normal_note[column as usize].take() {
+					Some(head_tick) => {
+						note_data.add_note(head_tick, column, crate::NoteType::HoldHead);
+						crate::NoteType::HoldTail
+					}
+					None => continue,
+				}
+			}
+			ObjectKind::Normal => {
+				last_normal_note[column as usize] = Some(tick);
+				crate::NoteType::Tap
+			}
+		};
+		note_data.add_note(tick, column, note_type);
+	}
+
+	Ok(BmsChart {
+		title,
+		artist,
+		note_data,
+		timing_info: crate::TimingInfo::from_bpm_changes(bpm, bpm_changes),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_bms() {
+		let chart = parse_bms(
+			b"#BPM 60
+#BPM01 120
+#LNOBJ ZZ
+#00002:0.5
+#00019:01
+#00108:01
+#00151:0101
+#00156:00000101
+#001D2:01
+#00211:0202
+#00211:000000ZZ
+#00221:01
+#RANDOM 2
+#IF 2
+#00312:01
+#ENDIF
+#IF 1
+#00313:01
+#ENDIF
+",
+		)
+		.unwrap();
+
+		// 7 keys because of channel 19, double play because of channel 21
+		assert_eq!(chart.note_data.keycount(), 16);
+		// the first measure is half as long, and the BPM doubles at the start of the second
+		let seconds = chart.timing_info.ticks_to_seconds(&[96, 192]);
+		assert_eq!(seconds, vec![2.0, 3.0]);
+		let notes: Vec<_> = chart.note_data.iter_notes().collect();
+		assert_eq!(
+			notes,
+			vec![
+				(0, 7, crate::NoteType::Tap),
+				(96, 1, crate::NoteType::HoldHead),
+				(96, 2, crate::NoteType::Mine),
+				(192, 0, crate::NoteType::HoldHead),
+				(192, 1, crate::NoteType::HoldTail),
+				(240, 0, crate::NoteType::HoldTail),
+				(288, 1, crate::NoteType::Tap),
+				(288, 8, crate::NoteType::Tap),
+				(384, 1, crate::NoteType::HoldHead),
+				(432, 1, crate::NoteType::HoldTail),
+				(480, 3, crate::NoteType::Tap),
+			]
+		);
+	}
+
+	#[test]
+	fn test_parse_bms_no_notes() {
+		assert_eq!(
+			parse_bms(b"#BPM 120\n#0011D:01"),
+			Err(BmsParseError::NoNotes)
+		);
+		assert_eq!(parse_bms(b""), Err(BmsParseError::NoNotes));
+	}
+}
@@ -0,0 +1,378 @@
+/// A calendar day. Used as a group identifier for [`crate::SkillTimeline::calculate`], see
+/// [`CalendarGrouping::group_by_day`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Day {
+	pub year: i32,
+	/// 1-12
+	pub month: u32,
+	/// 1-31
+	pub day: u32,
+}
+
+/// An ISO 8601 week, which starts on Monday. Used as a group identifier for
+/// [`crate::SkillTimeline::calculate`], see [`CalendarGrouping::group_by_week`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Week {
+	/// The ISO week-numbering year. Can differ from the calendar year around New Year
+	pub year: i32,
+	/// 1-53
+	pub week: u32,
+}
+
+/// A calendar month. Used as a group identifier for [`crate::SkillTimeline::calculate`], see
+/// [`CalendarGrouping::group_by_month`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Month {
+	pub year: i32,
+	/// 1-12
+	pub month: u32,
+}
+
+/// Days since 1970-01-01 of the given date in the proleptic Gregorian calendar
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+	let year = if month <= 2 { year - 1 } else { year } as i64;
+	let era = year.div_euclid(400);
+	let year_of_era = year - era * 400;
+	let month = month as i64;
+	let day_of_year =
+		(153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of [`days_from_civil`]
+fn civil_from_days(days: i64) -> Day {
+	let days = days + 719468;
+	let era = days.div_euclid(146097);
+	let day_of_era = days - era * 146097;
+	let year_of_era =
+		(day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let mp = (5 * day_of_year + 2) / 153;
+	let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+	let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+	Day {
+		year: year as i32,
+		month,
+		day,
+	}
+}
+
+impl Day {
+	/// Days since 1970-01-01
+	fn days_since_epoch(&self) -> i64 {
+		days_from_civil(self.year, self.month, self.day)
+	}
+
+	/// The ISO week this day belongs to
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// // 2021-01-03 is a Sunday and still belongs to the last week of 2020
+	/// let day = Day { year: 2021, month: 1, day: 3 };
+	/// assert_eq!(day.week(), Week { year: 2020, week: 53 });
+	/// ```
+	pub fn week(&self) -> Week {
+		let days = self.days_since_epoch();
+		// 1970-01-01 was a Thursday
+		let days_since_monday = (days + 3).rem_euclid(7);
+		// The ISO year of a week is the year its Thursday falls into
+		let thursday = civil_from_days(days - days_since_monday + 3);
+		let day_of_year = thursday.days_since_epoch() - days_from_civil(thursday.year, 1, 1);
+		Week {
+			year: thursday.year,
+			week: (day_of_year / 7 + 1) as u32,
+		}
+	}
+
+	/// The month this day belongs to
+	pub fn month(&self) -> Month {
+		Month {
+			year: self.year,
+			month: self.month,
+		}
+	}
+}
+
+impl std::fmt::Display for Day {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+	}
+}
+
+impl std::fmt::Display for Week {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{:04}-W{:02}", self.year, self.week)
+	}
+}
+
+impl std::fmt::Display for Month {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{:04}-{:02}", self.year, self.month)
+	}
+}
+
+/// A timestamp that can be placed on the calendar.
+///
+/// Implemented for [`Day`] and [`std::time::SystemTime`] (in UTC). With the `chrono` or `time` feature enabled,
+/// it's also implemented for those crates' date and date-time types. Types carrying a time zone or
+/// offset use the local date in that zone.
+pub trait CalendarTimestamp {
+	/// The calendar day this timestamp falls into
+	fn day(&self) -> Day;
+}
+
+impl CalendarTimestamp for Day {
+	fn day(&self) -> Day {
+		*self
+	}
+}
+
+impl CalendarTimestamp for std::time::SystemTime {
+	fn day(&self) -> Day {
+		let seconds = match self.duration_since(std::time::UNIX_EPOCH) {
+			Ok(duration) => duration.as_secs() as i64,
+			Err(e) => -(e.duration().as_secs_f64().ceil() as i64),
+		};
+		civil_from_days(seconds.div_euclid(86400))
+	}
+}
+
+#[cfg(feature = "chrono")]
+impl CalendarTimestamp for chrono::NaiveDate {
+	fn day(&self) -> Day {
+		use chrono::Datelike;
+		Day {
+			year: self.year(),
+			month: self.month(),
+			day: Datelike::day(self),
+		}
+	}
+}
+
+#[cfg(feature = "chrono")]
+impl CalendarTimestamp for chrono::NaiveDateTime {
+	fn day(&self) -> Day {
+		self.date().day()
+	}
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> CalendarTimestamp for chrono::DateTime<Tz> {
+	fn day(&self) -> Day {
+		self.date_naive().day()
+	}
+}
+
+#[cfg(feature = "time")]
+impl CalendarTimestamp for time::Date {
+	fn day(&self) -> Day {
+		Day {
+			year: self.year(),
+			month: self.month() as u32,
+			day: time::Date::day(*self) as u32,
+		}
+	}
+}
+
+#[cfg(feature = "time")]
+impl CalendarTimestamp for time::PrimitiveDateTime {
+	fn day(&self) -> Day {
+		CalendarTimestamp::day(&self.date())
+	}
+}
+
+#[cfg(feature = "time")]
+impl CalendarTimestamp for time::OffsetDateTime {
+	fn day(&self) -> Day {
+		CalendarTimestamp::day(&self.date())
+	}
+}
+
+/// Iterator adapters that replace the timestamps in `(timestamp, value)` tuples with calendar
+/// group identifiers, for use with [`crate::SkillTimeline::calculate`]
+///
+/// ```rust
+/// # use etterna::*;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let ssr = Skillsets7 { stream: 20.0, ..Default::default() };
+/// let scores = vec![
+/// 	(UNIX_EPOCH + Duration::from_secs(1_600_000_000), ssr.clone()),
+/// 	(UNIX_EPOCH + Duration::from_secs(1_600_003_600), ssr.clone()),
+/// 	(UNIX_EPOCH + Duration::from_secs(1_600_100_000), ssr.clone()),
+/// ];
+///
+/// let timeline = SkillTimeline::calculate(scores.into_iter().group_by_day(), EtternaVersion::LATEST);
+/// assert_eq!(timeline.changes.len(), 2);
+/// assert_eq!(timeline.changes[0].0, Day { year: 2020, month: 9, day: 13 });
+/// ```
+pub trait CalendarGrouping<T: CalendarTimestamp, V>: Iterator<Item = (T, V)> + Sized {
+	/// Groups by calendar day
+	#[allow(clippy::type_complexity)]
+	fn group_by_day(self) -> std::iter::Map<Self, fn((T, V)) -> (Day, V)> {
+		let to_group: fn((T, V)) -> (Day, V) = |(timestamp, value)| (timestamp.day(), value);
+		self.map(to_group)
+	}
+
+	/// Groups by ISO week, which starts on Monday
+	#[allow(clippy::type_complexity)]
+	fn group_by_week(self) -> std::iter::Map<Self, fn((T, V)) -> (Week, V)> {
+		let to_group: fn((T, V)) -> (Week, V) =
+			|(timestamp, value)| (timestamp.day().week(), value);
+		self.map(to_group)
+	}
+
+	/// Groups by calendar month
+	#[allow(clippy::type_complexity)]
+	fn group_by_month(self) -> std::iter::Map<Self, fn((T, V)) -> (Month, V)> {
+		let to_group: fn((T, V)) -> (Month, V) =
+			|(timestamp, value)| (timestamp.day().month(), value);
+		self.map(to_group)
+	}
+}
+
+impl<I, T, V> CalendarGrouping<T, V> for I
+where
+	I: Iterator<Item = (T, V)>,
+	T: CalendarTimestamp,
+{
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_civil_days_roundtrip() {
+		for &(days, year, month, day) in &[
+			(0, 1970, 1, 1),
+			(-1, 1969, 12, 31),
+			(11016, 2000, 2, 29),
+			(18628, 2021, 1, 1),
+		] {
+			let date = Day { year, month, day };
+			assert_eq!(civil_from_days(days), date);
+			assert_eq!(date.days_since_epoch(), days);
+		}
+	}
+
+	#[test]
+	fn test_iso_week() {
+		let week = |year, month, day| Day { year, month, day }.week();
+		assert_eq!(
+			week(2021, 1, 4),
+			Week {
+				year: 2021,
+				week: 1
+			}
+		);
+		assert_eq!(
+			week(2019, 12, 30),
+			Week {
+				year: 2020,
+				week: 1
+			}
+		);
+		assert_eq!(
+			week(2020, 12, 31),
+			Week {
+				year: 2020,
+				week: 53
+			}
+		);
+		assert_eq!(
+			week(2021, 6, 15),
+			Week {
+				year: 2021,
+				week: 24
+			}
+		);
+		assert_eq!(week(2021, 6, 15).to_string(), "2021-W24");
+	}
+
+	#[test]
+	fn test_system_time_day() {
+		use std::time::{Duration, UNIX_EPOCH};
+
+		let day = |seconds| (UNIX_EPOCH + Duration::from_secs(seconds)).day();
+		assert_eq!(day(0).to_string(), "1970-01-01");
+		assert_eq!(day(86399).to_string(), "1970-01-01");
+		assert_eq!(day(1_600_000_000).to_string(), "2020-09-13");
+		assert_eq!(
+			(UNIX_EPOCH - Duration::from_secs(1)).day().to_string(),
+			"1969-12-31"
+		);
+	}
+
+	#[test]
+	fn test_group_by() {
+		let day = |year, month, day| Day { year, month, day };
+		let scores = vec![
+			(day(2021, 3, 28), 1),
+			(day(2021, 3, 29), 2),
+			(day(2021, 4, 1), 3),
+		];
+		let weeks: Vec<_> = scores.iter().cloned().group_by_week().collect();
+		assert_eq!(weeks[0].0.week, 12);
+		assert_eq!(weeks[1].0.week, 13);
+		assert_eq!(weeks[2].0.week, 13);
+		let months: Vec<_> = scores.into_iter().group_by_month().collect();
+		assert_eq!(
+			months[0],
+			(
+				Month {
+					year: 2021,
+					month: 3
+				},
+				1
+			)
+		);
+		assert_eq!(
+			months[2],
+			(
+				Month {
+					year: 2021,
+					month: 4
+				},
+				3
+			)
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "chrono")]
+	fn test_chrono_day() {
+		let date = chrono::NaiveDate::from_ymd_opt(2021, 3, 14).unwrap();
+		assert_eq!(
+			CalendarTimestamp::day(&date),
+			Day {
+				year: 2021,
+				month: 3,
+				day: 14
+			}
+		);
+		let date_time = date.and_hms_opt(23, 59, 0).unwrap();
+		assert_eq!(CalendarTimestamp::day(&date_time).to_string(), "2021-03-14");
+	}
+
+	#[test]
+	#[cfg(feature = "time")]
+	fn test_time_day() {
+		let date = time::Date::from_calendar_date(2021, time::Month::March, 14).unwrap();
+		assert_eq!(
+			CalendarTimestamp::day(&date),
+			Day {
+				year: 2021,
+				month: 3,
+				day: 14
+			}
+		);
+		let date_time = date.with_hms(23, 59, 0).unwrap().assume_utc();
+		assert_eq!(CalendarTimestamp::day(&date_time).to_string(), "2021-03-14");
+	}
+}
@@ -0,0 +1,577 @@
+/// A single row of a chart, i.e. all notes at one point in time
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChartRow {
+	/// Position of this row in ticks. There are 48 ticks per beat
+	pub tick: u32,
+	/// Tap notes and hold heads in this row
+	pub taps: crate::NoteRow,
+	/// Hold heads in this row. These are also contained in `taps`
+	pub holds: crate::NoteRow,
+	pub hold_tails: crate::NoteRow,
+	pub mines: crate::NoteRow,
+	pub lifts: crate::NoteRow,
+	pub fakes: crate::NoteRow,
+}
+
+// Every note type that can be stored in a ChartRow
+const NOTE_TYPES: [crate::NoteType; 6] = [
+	crate::NoteType::Tap,
+	crate::NoteType::HoldHead,
+	crate::NoteType::HoldTail,
+	crate::NoteType::Mine,
+	crate::NoteType::Lift,
+	crate::NoteType::Fake,
+];
+
+impl ChartRow {
+	/// Whether this row contains no notes at all
+	pub fn is_empty(&self) -> bool {
+		self.all_notes().bits() == 0
+	}
+
+	// Every column that contains any kind of note
+	fn all_notes(&self) -> crate::NoteRow {
+		crate::NoteRow::from_bits(
+			self.taps.bits()
+				| self.hold_tails.bits()
+				| self.mines.bits()
+				| self.lifts.bits()
+				| self.fakes.bits(),
+		)
+	}
+
+	fn add_note(&mut self, column: u32, note_type: crate::NoteType) {
+		let row = match note_type {
+			crate::NoteType::Tap => &mut self.taps,
+			crate::NoteType::HoldHead => {
+				self.holds = self.holds.with_tap_at(column);
+				&mut self.taps
+			}
+			crate::NoteType::HoldTail => &mut self.hold_tails,
+			crate::NoteType::Mine => &mut self.mines,
+			crate::NoteType::Lift => &mut self.lifts,
+			crate::NoteType::Fake => &mut self.fakes,
+			crate::NoteType::Keysound => return,
+		};
+		*row = row.with_tap_at(column);
+	}
+
+	fn iter_notes(&self) -> impl Iterator<Item = (u8, crate::NoteType)> + '_ {
+		(0..32).flat_map(move |column| {
+			NOTE_TYPES
+				.iter()
+				.copied()
+				.filter(move |&note_type| match note_type {
+					crate::NoteType::Tap => self.taps.tap_at(column) && !self.holds.tap_at(column),
+					crate::NoteType::HoldHead => self.holds.tap_at(column),
+					crate::NoteType::HoldTail => self.hold_tails.tap_at(column),
+					crate::NoteType::Mine => self.mines.tap_at(column),
+					crate::NoteType::Lift => self.lifts.tap_at(column),
+					crate::NoteType::Fake => self.fakes.tap_at(column),
+					crate::NoteType::Keysound => false,
+				})
+				.map(move |note_type| (column as u8, note_type))
+		})
+	}
+}
+
+/// A turn mod, which rearranges the columns of a chart. See [`NoteData::apply_turn`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Turn {
+	/// Flips the chart horizontally
+	Mirror,
+	/// On 4K, rotates the arrows 90 degrees counterclockwise like the game's Left turn: left becomes
+	/// down, down becomes right, up becomes left and right becomes up. Other keycounts move every
+	/// column one to the left, with the leftmost column wrapping around
+	Left,
+	/// The inverse of [`Self::Left`]: on 4K, rotates the arrows 90 degrees clockwise like the game's
+	/// Right turn. Other keycounts move every column one to the right, with the rightmost column
+	/// wrapping around
+	Right,
+	/// Randomly permutes the columns. The same seed always yields the same permutation
+	Shuffle { seed: u64 },
+}
+
+/// The notes of a chart, independent of the file format they came from. Chart parsers produce
+/// this, and analysis functions like [`ChartSummary::calculate`] consume it.
+///
+/// Rows are kept sorted by tick, with at most one row per tick and no empty rows.
+///
+/// ```rust
+/// # use etterna::*;
+/// let mut note_data = NoteData::new(4);
+/// note_data.add_note(48, 1, NoteType::Tap);
+/// note_data.add_note(0, 0, NoteType::HoldHead);
+/// note_data.add_note(48, 0, NoteType::HoldTail);
+///
+/// assert_eq!(note_data.ticks(), vec![0, 48]);
+/// assert_eq!(note_data.iter_notes().count(), 3);
+///
+/// let timing_info = TimingInfo::from_constant_bpm(120.0);
+/// assert_eq!(note_data.lane_note_seconds(&timing_info)[1], vec![0.5]);
+/// assert_eq!(note_data.summary(&timing_info).num_holds, 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedNoteData"))]
+pub struct NoteData {
+	keycount: u8,
+	rows: Vec<ChartRow>,
+}
+
+/// Returned when deserializing [`NoteData`] that [`NoteData::from_rows`] would reject
+#[cfg(feature = "serde")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Note data must have sorted rows with unique ticks, within a keycount of at most 32")]
+pub struct InvalidNoteDataError;
+
+/// Deserialized form of [`NoteData`], before the invariants are checked
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedNoteData {
+	keycount: u8,
+	rows: Vec<ChartRow>,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<UncheckedNoteData> for NoteData {
+	type Error = InvalidNoteDataError;
+
+	fn try_from(note_data: UncheckedNoteData) -> Result<Self, Self::Error> {
+		Self::from_rows(note_data.keycount, note_data.rows).ok_or(InvalidNoteDataError)
+	}
+}
+
+impl NoteData {
+	/// Highest supported number of columns, limited by the width of [`crate::NoteRow`]
+	pub const MAX_KEYCOUNT: u8 = 32;
+
+	/// Creates empty note data for a chart with the given number of columns
+	///
+	/// Panics if the keycount is above [`Self::MAX_KEYCOUNT`]
+	pub fn new(keycount: u8) -> Self {
+		assert!(
+			keycount <= Self::MAX_KEYCOUNT,
+			"keycount {} is above the maximum of {}",
+			keycount,
+			Self::MAX_KEYCOUNT
+		);
+		Self {
+			keycount,
+			rows: Vec::new(),
+		}
+	}
+
+	/// Creates note data from a list of rows. Empty rows are dropped.
+	///
+	/// Returns None if the rows aren't sorted by tick, if a tick appears twice, if a note lies
+	/// outside of the keycount, or if the keycount is above [`Self::MAX_KEYCOUNT`]
+	pub fn from_rows(keycount: u8, mut rows: Vec<ChartRow>) -> Option<Self> {
+		if keycount > Self::MAX_KEYCOUNT {
+			return None;
+		}
+		rows.retain(|row| !row.is_empty());
+		if rows.windows(2).any(|pair| pair[0].tick >= pair[1].tick) {
+			return None;
+		}
+		let max_width = rows
+			.iter()
+			.map(|row| row.all_notes().width())
+			.max()
+			.unwrap_or(0);
+		if max_width > keycount as u32 {
+			return None;
+		}
+
+		Some(Self { keycount, rows })
+	}
+
+	/// Number of columns
+	pub fn keycount(&self) -> u8 {
+		self.keycount
+	}
+
+	/// All non-empty rows, sorted by tick
+	pub fn rows(&self) -> &[ChartRow] {
+		&self.rows
+	}
+
+	pub fn into_rows(self) -> Vec<ChartRow> {
+		self.rows
+	}
+
+	/// Adds a note. Keysounds are ignored, since they don't have a column
+	///
+	/// Panics if the column is outside of the keycount
+	pub fn add_note(&mut self, tick: u32, column: u8, note_type: crate::NoteType) {
+		assert!(column < self.keycount, "Column outside of keycount");
+
+		let index = match self.rows.binary_search_by_key(&tick, |row| row.tick) {
+			Ok(index) => index,
+			Err(index) => {
+				if note_type == crate::NoteType::Keysound {
+					return;
+				}
+				self.rows.insert(
+					index,
+					ChartRow {
+						tick,
+						..Default::default()
+					},
+				);
+				index
+			}
+		};
+		self.rows[index].add_note(column as u32, note_type);
+	}
+
+	/// Iterates over every note as `(tick, column, note type)`, in chronological order
+	pub fn iter_notes(&self) -> impl Iterator<Item = (u32, u8, crate::NoteType)> + '_ {
+		self.rows.iter().flat_map(|row| {
+			row.iter_notes()
+				.map(move |(column, note_type)| (row.tick, column, note_type))
+		})
+	}
+
+	/// Tick of every row, sorted, as accepted by [`crate::TimingInfo::ticks_to_seconds`]
+	pub fn ticks(&self) -> Vec<u32> {
+		self.rows.iter().map(|row| row.tick).collect()
+	}
+
+	/// Position in seconds of every row, at 1.00x
+	pub fn row_seconds(&self, timing_info: &crate::TimingInfo) -> Vec<f32> {
+		timing_info.ticks_to_seconds(&self.ticks())
+	}
+
+	/// For every column, the sorted positions in seconds of its taps and hold heads, at 1.00x.
+	/// These are the notes that are judged in rescoring, see [`crate::NoteAndHitSeconds`]
+	pub fn lane_note_seconds(&self, timing_info: &crate::TimingInfo) -> Vec<Vec<f32>> {
+		let mut lanes = vec![Vec::new(); self.keycount as usize];
+		for (row, second) in self.rows.iter().zip(self.row_seconds(timing_info)) {
+			for (column, lane) in lanes.iter_mut().enumerate() {
+				if row.taps.tap_at(column as u32) {
+					lane.push(second);
+				}
+			}
+		}
+		lanes
+	}
+
+	/// See [`ChartSummary::calculate`]
+	pub fn summary(&self, timing_info: &crate::TimingInfo) -> ChartSummary {
+		ChartSummary::calculate(&self.rows, timing_info)
+	}
+
+	/// Moves every note from column `i` to column `mapping[i]`. Notes that land on the same spot
+	/// are merged
+	///
+	/// Panics if `mapping` doesn't have exactly one entry per column, or if an entry is outside of
+	/// the keycount
+	pub fn remap_columns(&mut self, mapping: &[u8]) {
+		assert_eq!(mapping.len(), self.keycount as usize);
+		assert!(mapping.iter().all(|&column| column < self.keycount));
+
+		let remap = |notes: crate::NoteRow| {
+			mapping
+				.iter()
+				.enumerate()
+				.filter(|&(column, _)| notes.tap_at(column as u32))
+				.fold(crate::NoteRow::default(), |row, (_, &new_column)| {
+					row.with_tap_at(new_column as u32)
+				})
+		};
+		for row in &mut self.rows {
+			row.taps = remap(row.taps);
+			row.holds = remap(row.holds);
+			row.hold_tails = remap(row.hold_tails);
+			row.mines = remap(row.mines);
+			row.lifts = remap(row.lifts);
+			row.fakes = remap(row.fakes);
+		}
+	}
+
+	/// Applies a turn mod. See [`Turn`] for which turns match the game
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let mut note_data = NoteData::new(4);
+	/// note_data.add_note(0, 0, NoteType::Tap);
+	/// note_data.add_note(48, 1, NoteType::Tap);
+	///
+	/// note_data.apply_turn(Turn::Mirror);
+	/// let columns: Vec<u8> = note_data.iter_notes().map(|(_, column, _)| column).collect();
+	/// assert_eq!(columns, vec![3, 2]);
+	/// ```
+	pub fn apply_turn(&mut self, turn: Turn) {
+		let keycount = self.keycount;
+		// in usize, so that `c + keycount` can't overflow
+		let rotate = |offset: usize| -> Vec<u8> {
+			let keycount = keycount as usize;
+			(0..keycount)
+				.map(|c| ((c + offset) % keycount) as u8)
+				.collect()
+		};
+		let mapping: Vec<u8> = match (turn, keycount) {
+			(Turn::Mirror, _) => (0..keycount).rev().collect(),
+			// StepMania's dance tables (NoteDataUtil::Turn), inverted from "new column takes from"
+			// to "old column moves to"
+			(Turn::Left, 4) => vec![1, 3, 0, 2],
+			(Turn::Right, 4) => vec![2, 0, 3, 1],
+			(Turn::Left, _) => rotate(keycount as usize - 1),
+			(Turn::Right, _) => rotate(1),
+			(Turn::Shuffle { seed }, _) => {
+				// Fisher-Yates with a xorshift generator, so that results are reproducible across
+				// platforms and versions
+				let mut state = seed | 1;
+				let mut mapping: Vec<u8> = (0..keycount).collect();
+				for i in (1..mapping.len()).rev() {
+					state ^= state << 13;
+					state ^= state >> 7;
+					state ^= state << 17;
+					mapping.swap(i, (state % (i as u64 + 1)) as usize);
+				}
+				mapping
+			}
+		};
+		self.remap_columns(&mapping);
+	}
+
+	/// Removes all mines, like the NoMines modifier
+	pub fn remove_mines(&mut self) {
+		for row in &mut self.rows {
+			row.mines = crate::NoteRow::default();
+		}
+		self.rows.retain(|row| !row.is_empty());
+	}
+}
+
+/// Overview of a chart's contents, like shown on the song wheel. See [`ChartSummary::calculate`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChartSummary {
+	/// Number of tap notes, including hold heads
+	pub num_taps: u32,
+	/// Number of rows with two or more taps
+	pub num_jumps: u32,
+	/// Number of rows with three or more taps
+	pub num_hands: u32,
+	/// Number of rows with four or more taps
+	pub num_quads: u32,
+	pub num_holds: u32,
+	pub num_mines: u32,
+	/// Time of the last tap note in seconds, at 1.0x
+	pub length_seconds: f32,
+	/// Tap notes per second between the first and the last tap note, at 1.0x
+	pub average_nps: f32,
+	/// Highest number of tap notes within any one second long window, at 1.0x
+	pub peak_nps: f32,
+}
+
+impl ChartSummary {
+	/// Calculates the summary of a chart from its rows, which must be sorted by tick
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let row = |tick, taps| ChartRow { tick, taps: NoteRow::from_bits(taps), ..Default::default() };
+	/// let rows = [row(0, 0b0001), row(48, 0b0011), row(96, 0b0111), row(144, 0b1111)];
+	///
+	/// let summary = ChartSummary::calculate(&rows, &TimingInfo::from_constant_bpm(60.0));
+	/// assert_eq!(summary.num_taps, 10);
+	/// assert_eq!((summary.num_jumps, summary.num_hands, summary.num_quads), (3, 2, 1));
+	/// assert_eq!(summary.length_seconds, 3.0);
+	/// ```
+	pub fn calculate(rows: &[ChartRow], timing_info: &crate::TimingInfo) -> Self {
+		let mut summary = Self::default();
+
+		let mut tap_rows = Vec::with_capacity(rows.len());
+		for row in rows {
+			let num_taps = row.taps.bits().count_ones();
+			summary.num_taps += num_taps;
+			summary.num_jumps += (num_taps >= 2) as u32;
+			summary.num_hands += (num_taps >= 3) as u32;
+			summary.num_quads += (num_taps >= 4) as u32;
+			summary.num_holds += row.holds.bits().count_ones();
+			summary.num_mines += row.mines.bits().count_ones();
+
+			if num_taps > 0 {
+				tap_rows.push((row.tick, num_taps));
+			}
+		}
+
+		let ticks: Vec<u32> = tap_rows.iter().map(|&(tick, _)| tick).collect();
+		let seconds = timing_info.ticks_to_seconds(&ticks);
+		let (first_second, last_second) = match (seconds.first(), seconds.last()) {
+			(Some(&first), Some(&last)) => (first, last),
+			_ => return summary,
+		};
+
+		summary.length_seconds = last_second;
+		if last_second > first_second {
+			summary.average_nps = summary.num_taps as f32 / (last_second - first_second);
+		}
+
+		// One entry per tap, so that chords count with all their notes
+		let tap_seconds: Vec<f32> = seconds
+			.iter()
+			.zip(&tap_rows)
+			.flat_map(|(&second, &(_, num_taps))| std::iter::repeat_n(second, num_taps as usize))
+			.collect();
+		summary.peak_nps = crate::sequences::max_count_in_window(&tap_seconds, 1.0) as f32;
+
+		summary
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_chart_summary() {
+		let row = |tick, taps, holds, mines| ChartRow {
+			tick,
+			taps: crate::NoteRow::from_bits(taps),
+			holds: crate::NoteRow::from_bits(holds),
+			mines: crate::NoteRow::from_bits(mines),
+			..Default::default()
+		};
+		// 120 BPM, so one beat (48 ticks) is half a second
+		let rows = [
+			row(0, 0b0001, 0b0001, 0),
+			row(12, 0b0010, 0, 0b0100),
+			row(24, 0b0100, 0, 0),
+			row(36, 0b1000, 0, 0),
+			row(48, 0, 0, 0b1000),
+			row(384, 0b0110, 0b0010, 0),
+		];
+
+		let summary = ChartSummary::calculate(&rows, &crate::TimingInfo::from_constant_bpm(120.0));
+		assert_eq!(
+			summary,
+			ChartSummary {
+				num_taps: 6,
+				num_jumps: 1,
+				num_hands: 0,
+				num_quads: 0,
+				num_holds: 2,
+				num_mines: 2,
+				length_seconds: 4.0,
+				average_nps: 1.5,
+				peak_nps: 4.0,
+			}
+		);
+
+		assert_eq!(
+			ChartSummary::calculate(&[], &crate::TimingInfo::from_constant_bpm(120.0)),
+			ChartSummary::default(),
+		);
+	}
+
+	#[test]
+	fn test_note_data() {
+		let mut note_data = NoteData::new(4);
+		note_data.add_note(96, 3, crate::NoteType::Mine);
+		note_data.add_note(0, 2, crate::NoteType::Tap);
+		note_data.add_note(0, 0, crate::NoteType::HoldHead);
+		note_data.add_note(48, 0, crate::NoteType::HoldTail);
+		note_data.add_note(48, 1, crate::NoteType::Lift);
+		note_data.add_note(72, 1, crate::NoteType::Keysound);
+
+		assert_eq!(
+			note_data.iter_notes().collect::<Vec<_>>(),
+			vec![
+				(0, 0, crate::NoteType::HoldHead),
+				(0, 2, crate::NoteType::Tap),
+				(48, 0, crate::NoteType::HoldTail),
+				(48, 1, crate::NoteType::Lift),
+				(96, 3, crate::NoteType::Mine),
+			]
+		);
+
+		// Round trip through rows
+		let rows = note_data.rows().to_vec();
+		assert_eq!(NoteData::from_rows(4, rows.clone()), Some(note_data));
+		assert_eq!(NoteData::from_rows(3, rows.clone()), None);
+		let mut unsorted_rows = rows;
+		unsorted_rows.swap(0, 1);
+		assert_eq!(NoteData::from_rows(4, unsorted_rows), None);
+	}
+
+	#[test]
+	fn test_turns() {
+		let mut note_data = NoteData::new(5);
+		note_data.add_note(0, 0, crate::NoteType::HoldHead);
+		note_data.add_note(24, 0, crate::NoteType::HoldTail);
+		note_data.add_note(24, 4, crate::NoteType::Tap);
+		note_data.add_note(48, 2, crate::NoteType::Mine);
+		let columns = |note_data: &NoteData| {
+			note_data
+				.iter_notes()
+				.map(|(_, column, _)| column)
+				.collect::<Vec<_>>()
+		};
+
+		let mut left = note_data.clone();
+		left.apply_turn(Turn::Left);
+		assert_eq!(columns(&left), vec![4, 3, 4, 1]);
+		left.apply_turn(Turn::Right);
+		assert_eq!(left, note_data);
+
+		let mut shuffled_a = note_data.clone();
+		let mut shuffled_b = note_data.clone();
+		shuffled_a.apply_turn(Turn::Shuffle { seed: 42 });
+		shuffled_b.apply_turn(Turn::Shuffle { seed: 42 });
+		assert_eq!(shuffled_a, shuffled_b);
+		assert_eq!(shuffled_a.iter_notes().count(), 4);
+		assert_eq!(shuffled_a.rows()[0].holds, shuffled_a.rows()[0].taps);
+
+		let mut no_mines = note_data;
+		no_mines.remove_mines();
+		assert_eq!(no_mines.ticks(), vec![0, 24]);
+	}
+
+	#[test]
+	fn test_4k_turns() {
+		// one note per column, on consecutive rows: left, down, up, right
+		let mut note_data = NoteData::new(4);
+		for column in 0..4 {
+			note_data.add_note(column as u32 * 48, column, crate::NoteType::Tap);
+		}
+		let columns = |note_data: &NoteData| {
+			note_data
+				.iter_notes()
+				.map(|(_, column, _)| column)
+				.collect::<Vec<_>>()
+		};
+
+		let mut left = note_data.clone();
+		left.apply_turn(Turn::Left);
+		assert_eq!(columns(&left), vec![1, 3, 0, 2]);
+		let mut right = note_data.clone();
+		right.apply_turn(Turn::Right);
+		assert_eq!(columns(&right), vec![2, 0, 3, 1]);
+		right.apply_turn(Turn::Left);
+		assert_eq!(right, note_data);
+	}
+
+	#[test]
+	fn test_turn_max_keycount() {
+		let mut note_data = NoteData::new(NoteData::MAX_KEYCOUNT);
+		note_data.add_note(0, 0, crate::NoteType::Tap);
+		note_data.add_note(0, 31, crate::NoteType::Tap);
+		note_data.apply_turn(Turn::Left);
+		note_data.apply_turn(Turn::Mirror);
+		note_data.apply_turn(Turn::Shuffle { seed: 1 });
+		assert_eq!(note_data.iter_notes().count(), 2);
+
+		assert!(NoteData::from_rows(33, vec![]).is_none());
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_keycount_above_max() {
+		NoteData::new(200);
+	}
+}
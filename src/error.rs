@@ -0,0 +1,31 @@
+use thiserror::Error;
+
+/// Crate-wide error type, returned by the non-panicking `try_` functions like [`crate::try_rescore`].
+/// The more specific error types of individual functions convert into it, so it can be used with
+/// `?` across the crate's API.
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("Input doesn't contain any notes")]
+	NoNotes,
+	#[error("{0} are not sorted chronologically")]
+	Unsorted(&'static str),
+	#[error(transparent)]
+	Rate(#[from] crate::RateParseError),
+	#[error(transparent)]
+	ReplayParse(#[from] crate::ReplayParseError),
+	#[error(transparent)]
+	ReplayFile(#[from] crate::ReplayFileError),
+	#[error(transparent)]
+	Pattern(#[from] crate::PatternError),
+	#[error(transparent)]
+	Simfile(#[from] crate::SimfileParseError),
+	#[error(transparent)]
+	Pack(#[from] crate::PackReadError),
+	#[error(transparent)]
+	SongCache(#[from] crate::SongCacheError),
+	#[error(transparent)]
+	ScoreValidation(#[from] crate::ScoreValidationError),
+	#[cfg(feature = "binary-cache")]
+	#[error(transparent)]
+	BinaryCache(#[from] crate::BinaryCacheError),
+}
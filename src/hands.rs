@@ -0,0 +1,118 @@
+/// One of the player's hands
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hand {
+	Left,
+	Right,
+}
+
+impl Hand {
+	/// Both hands, left first. Per-hand results in this crate are in this order
+	pub fn list() -> &'static [Self] {
+		&[Self::Left, Self::Right]
+	}
+
+	/// Index into per-hand arrays like `[T; 2]`
+	pub fn index(self) -> usize {
+		match self {
+			Self::Left => 0,
+			Self::Right => 1,
+		}
+	}
+}
+
+/// Decides which hand plays which lane. Implement this for keymodes that aren't split down the
+/// middle, e.g. with a scratch lane; closures `Fn(u8) -> Option<Hand>` implement it too.
+pub trait HandMapping {
+	/// The hand that plays the given lane, or None if the lane isn't attributed to either hand
+	fn hand(&self, lane: u8) -> Option<Hand>;
+}
+
+impl<F: Fn(u8) -> Option<Hand>> HandMapping for F {
+	fn hand(&self, lane: u8) -> Option<Hand> {
+		self(lane)
+	}
+}
+
+/// The usual hand mapping: the lower half of the lanes is played by the left hand and the upper
+/// half by the right hand. For odd keycounts, the middle lane is not attributed to either hand.
+/// For 4k, that's lanes 0 and 1 versus 2 and 3.
+///
+/// ```rust
+/// # use etterna::*;
+/// assert_eq!(SplitHands { keycount: 4 }.hand(1), Some(Hand::Left));
+/// assert_eq!(SplitHands { keycount: 4 }.hand(2), Some(Hand::Right));
+/// assert_eq!(SplitHands { keycount: 5 }.hand(2), None);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SplitHands {
+	pub keycount: u8,
+}
+
+impl HandMapping for SplitHands {
+	fn hand(&self, lane: u8) -> Option<Hand> {
+		if lane < self.keycount / 2 {
+			Some(Hand::Left)
+		} else if lane >= self.keycount.div_ceil(2) {
+			Some(Hand::Right)
+		} else {
+			None
+		}
+	}
+}
+
+/// Merges the lanes played by each hand into a single lane per hand, left hand first. Lanes that
+/// aren't attributed to either hand are skipped
+pub fn split_lanes_by_hand(
+	lanes: &[crate::NoteAndHitSeconds],
+	mapping: &impl HandMapping,
+) -> [crate::NoteAndHitSeconds; 2] {
+	let mut hands: [crate::NoteAndHitSeconds; 2] = Default::default();
+	for (lane_index, lane) in lanes.iter().enumerate() {
+		if let Some(hand) = mapping.hand(lane_index as u8) {
+			let hand = &mut hands[hand.index()];
+			hand.note_seconds.extend_from_slice(&lane.note_seconds);
+			hand.hit_seconds.extend_from_slice(&lane.hit_seconds);
+		}
+	}
+
+	// UNWRAP: seconds are never NaN
+	for hand in &mut hands {
+		hand.note_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+		hand.hit_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	}
+	hands
+}
+
+/// Finds the fastest burst of hits played by each hand, like [`crate::find_fastest_note_subset`]
+/// on the merged hits of each hand. Left hand first
+///
+/// ```rust
+/// # use etterna::*;
+/// let lane = |hit_seconds: Vec<f32>| NoteAndHitSeconds {
+/// 	note_seconds: hit_seconds.clone(),
+/// 	hit_seconds,
+/// };
+/// let lanes = [
+/// 	lane(vec![0.0, 0.2, 0.4]),
+/// 	lane(vec![0.1, 0.3, 0.5]),
+/// 	lane(vec![0.0, 0.5, 1.0]),
+/// 	lane(vec![]),
+/// ];
+///
+/// let [left, right] = fastest_hand_bursts(&lanes, &SplitHands { keycount: 4 }, 2, 4);
+/// assert!(left.speed > 9.9);
+/// assert_eq!(right.speed, 2.0);
+/// ```
+pub fn fastest_hand_bursts(
+	lanes: &[crate::NoteAndHitSeconds],
+	mapping: &impl HandMapping,
+	min_num_notes: u32,
+	max_num_notes: u32,
+) -> [crate::FastestComboInfo; 2] {
+	let [left, right] = split_lanes_by_hand(lanes, mapping);
+	[
+		crate::find_fastest_note_subset(&left.hit_seconds, min_num_notes, max_num_notes),
+		crate::find_fastest_note_subset(&right.hit_seconds, min_num_notes, max_num_notes),
+	]
+}
@@ -3,6 +3,10 @@
 /// For detailed information, see
 /// [this spreadsheet](https://docs.google.com/spreadsheets/d/1syi5aN6sTiDA2Bs_lzZjsLQ1yCEhxl5EnAd6EsD6cF4)
 /// from Foxfire and poco0317
+///
+/// With the `serde` feature, the built-in judges are (de)serialized as their name, e.g. `"J4"`.
+/// Custom judges are (de)serialized as a map of their windows.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Judge {
 	pub name: &'static str,
 	pub marvelous_window: f32,
@@ -17,10 +21,85 @@ pub struct Judge {
 	///
 	/// Before universal mine timing the mine window was equal to the current judge's great window.
 	pub mine_window: f32,
+	/// See [`Self::timing_scale`]
 	pub(crate) timing_scale: f32,
 }
 
+/// The game never lets the bad window of a judge go below this value (J4's bad window), so every
+/// judge from J4 upwards shares the same bad window. See [`Judge::with_timing_scale`]
+pub const LOCKED_BAD_WINDOW: f32 = 0.18;
+
 impl Judge {
+	/// Get a list of all built-in judges, from J1 to J9
+	pub fn list() -> &'static [&'static Judge] {
+		&[J1, J2, J3, J4, J5, J6, J7, J8, J9]
+	}
+
+	/// Looks up a built-in judge by its name, case-insensitively.
+	///
+	/// ```rust
+	/// # use etterna::Judge;
+	/// assert_eq!(Judge::from_name("j4"), Some(etterna::J4));
+	/// assert_eq!(Judge::from_name("J10"), None);
+	/// ```
+	pub fn from_name(name: &str) -> Option<&'static Judge> {
+		Self::list()
+			.iter()
+			.copied()
+			.find(|judge| judge.name.eq_ignore_ascii_case(name))
+	}
+
+	/// Creates a custom judge whose windows are J4's windows multiplied by `scale`, with the same
+	/// locks the game applies: the bad window never goes below [`LOCKED_BAD_WINDOW`], and the roll
+	/// window never goes below J7's. The mine window is independent of the judge.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let judge = Judge::with_timing_scale(0.5);
+	/// assert_eq!(judge.great_window, J7.great_window);
+	/// assert_eq!(judge.bad_window, J7.bad_window);
+	/// assert_eq!(judge.timing_scale(), 0.5);
+	/// ```
+	pub fn with_timing_scale(scale: f32) -> Self {
+		Self {
+			bad_window: f32::max(J4.bad_window * scale, LOCKED_BAD_WINDOW),
+			..Self::with_timing_scale_unlocked(scale)
+		}
+	}
+
+	/// Like [`Self::with_timing_scale`], but the bad window is scaled like the other windows
+	/// instead of being locked to [`LOCKED_BAD_WINDOW`]. This replicates old game versions, which
+	/// didn't lock the bad window yet. Stricter judges then count more CBs.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let judge = Judge::with_timing_scale_unlocked(0.5);
+	/// assert_eq!(judge.bad_window, 0.09);
+	/// assert_eq!(Judge::with_timing_scale_unlocked(1.5).bad_window, J1.bad_window);
+	/// ```
+	pub fn with_timing_scale_unlocked(scale: f32) -> Self {
+		Self {
+			name: "Custom",
+			marvelous_window: J4.marvelous_window * scale,
+			perfect_window: J4.perfect_window * scale,
+			great_window: J4.great_window * scale,
+			good_window: J4.good_window * scale,
+			bad_window: J4.bad_window * scale,
+			hold_window: J4.hold_window * scale,
+			roll_window: f32::max(J4.roll_window * scale, J7.roll_window),
+			mine_window: J4.mine_window,
+			timing_scale: scale,
+		}
+	}
+
+	/// Factor by which this judge's timing windows are scaled relative to J4, e.g. 0.5 for J7.
+	///
+	/// Smaller values mean stricter judging. Besides the windows, Wife formulas use this value to
+	/// decide how harshly a deviation is penalized.
+	pub fn timing_scale(&self) -> f32 {
+		self.timing_scale
+	}
+
 	/// Classifies a tap deviation in seconds to a judgement. The parameter can be negative.
 	pub fn classify(&self, deviation: f32) -> crate::TapJudgement {
 		let deviation = deviation.abs();
@@ -40,9 +119,29 @@ impl Judge {
 		}
 	}
 
-	/// Whether the given deviation is a combo breaker (CB)
+	/// Whether the given deviation is a combo breaker (CB), i.e. lies outside of the great window.
+	/// The parameter can be negative.
+	///
+	/// ```rust
+	/// # use etterna::J4;
+	/// assert!(!J4.is_cb(-0.05));
+	/// assert!(J4.is_cb(-0.12));
+	/// assert!(J4.is_cb(0.12));
+	/// ```
 	pub fn is_cb(&self, deviation: f32) -> bool {
-		deviation <= self.great_window
+		deviation.abs() > self.great_window
+	}
+
+	/// Converts a deviation hit on this judge to the equivalent deviation on another judge, i.e.
+	/// one that lies at the same relative position within the timing windows.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// assert_eq!(J7.scale_deviation_to(J4, 0.01), 0.02);
+	/// assert_eq!(J7.classify(0.01), J4.classify(J7.scale_deviation_to(J4, 0.01)));
+	/// ```
+	pub fn scale_deviation_to(&self, other: &Judge, deviation: f32) -> f32 {
+		deviation * other.timing_scale / self.timing_scale
 	}
 
 	/// Whether the given deviation is considered marvelous
@@ -76,6 +175,88 @@ impl Judge {
 	}
 }
 
+/// Converts a deviation hit on the given judge to the equivalent deviation on J4, so that statistics
+/// of players on different judges can be compared on a common scale. See
+/// [`Judge::scale_deviation_to`]
+///
+/// ```rust
+/// # use etterna::*;
+/// assert_eq!(normalize_to_j4(0.0189, J5), 0.0225);
+/// ```
+pub fn normalize_to_j4(deviation: f32, judge: &Judge) -> f32 {
+	judge.scale_deviation_to(J4, deviation)
+}
+
+// Custom judges are serialized with all of their windows. They can't be given a name because the
+// name is a &'static str
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CustomJudge {
+	marvelous_window: f32,
+	perfect_window: f32,
+	great_window: f32,
+	good_window: f32,
+	bad_window: f32,
+	hold_window: f32,
+	roll_window: f32,
+	mine_window: f32,
+	timing_scale: f32,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Judge {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		if let Some(preset) = Self::list().iter().find(|&&preset| preset == self) {
+			return serializer.serialize_str(preset.name);
+		}
+
+		CustomJudge {
+			marvelous_window: self.marvelous_window,
+			perfect_window: self.perfect_window,
+			great_window: self.great_window,
+			good_window: self.good_window,
+			bad_window: self.bad_window,
+			hold_window: self.hold_window,
+			roll_window: self.roll_window,
+			mine_window: self.mine_window,
+			timing_scale: self.timing_scale,
+		}
+		.serialize(serializer)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Judge {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		#[derive(serde::Deserialize)]
+		#[serde(untagged)]
+		enum JudgeRepr {
+			Preset(String),
+			Custom(CustomJudge),
+		}
+
+		Ok(match JudgeRepr::deserialize(deserializer)? {
+			JudgeRepr::Preset(name) => Self::from_name(&name)
+				.ok_or_else(|| {
+					serde::de::Error::custom(format!("unknown judge preset \"{}\"", name))
+				})?
+				.clone(),
+			JudgeRepr::Custom(judge) => Self {
+				name: "Custom",
+				marvelous_window: judge.marvelous_window,
+				perfect_window: judge.perfect_window,
+				great_window: judge.great_window,
+				good_window: judge.good_window,
+				bad_window: judge.bad_window,
+				hold_window: judge.hold_window,
+				roll_window: judge.roll_window,
+				mine_window: judge.mine_window,
+				timing_scale: judge.timing_scale,
+			},
+		})
+	}
+}
+
 /// Removed from Etterna in 0.69.0
 pub const J1: &Judge = &Judge {
 	name: "J1",
@@ -139,7 +320,7 @@ pub const J5: &Judge = &Judge {
 	perfect_window: 0.0378,
 	great_window: 0.0756,
 	good_window: 0.1134,
-	/// Before J4 boo window lock: 151.2ms
+	// Before J4 boo window lock: 151.2ms
 	bad_window: 0.18,
 	hold_window: 0.21,
 	roll_window: 0.42,
@@ -154,7 +335,7 @@ pub const J6: &Judge = &Judge {
 	perfect_window: 0.0297,
 	great_window: 0.0594,
 	good_window: 0.0891,
-	/// Before J4 boo window lock: 118.8ms
+	// Before J4 boo window lock: 118.8ms
 	bad_window: 0.18,
 	hold_window: 0.165,
 	roll_window: 0.33,
@@ -169,7 +350,7 @@ pub const J7: &Judge = &Judge {
 	perfect_window: 0.0225,
 	great_window: 0.045,
 	good_window: 0.0675,
-	/// Before J4 boo window lock: 90ms
+	// Before J4 boo window lock: 90ms
 	bad_window: 0.18,
 	hold_window: 0.125,
 	roll_window: 0.25,
@@ -184,10 +365,10 @@ pub const J8: &Judge = &Judge {
 	perfect_window: 0.01485,
 	great_window: 0.0297,
 	good_window: 0.04455,
-	/// Before J4 boo window lock: 59.4ms
+	// Before J4 boo window lock: 59.4ms
 	bad_window: 0.18,
 	hold_window: 0.0825,
-	/// Before J7 Roll Lock: 165ms
+	// Before J7 Roll Lock: 165ms
 	timing_scale: 0.33,
 	roll_window: 0.25,
 	mine_window: 0.075,
@@ -200,11 +381,60 @@ pub const J9: &Judge = &Judge {
 	perfect_window: 0.009,
 	great_window: 0.018,
 	good_window: 0.027,
-	/// Before J4 boo window lock: 36ms
+	// Before J4 boo window lock: 36ms
 	bad_window: 0.18,
 	hold_window: 0.05,
-	/// Before J7 Roll lock: 100ms
+	// Before J7 Roll lock: 100ms
 	timing_scale: 0.20,
 	roll_window: 0.25,
 	mine_window: 0.075,
 };
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_with_timing_scale() {
+		for &judge in Judge::list() {
+			let custom = Judge::with_timing_scale(judge.timing_scale());
+			let windows = |judge: &Judge| {
+				[
+					judge.marvelous_window,
+					judge.perfect_window,
+					judge.great_window,
+					judge.good_window,
+					judge.bad_window,
+					judge.hold_window,
+					judge.roll_window,
+					judge.mine_window,
+				]
+			};
+			for (a, b) in windows(&custom).iter().zip(&windows(judge)) {
+				assert!((a - b).abs() < 0.0001, "{}: {} != {}", judge.name, a, b);
+			}
+			assert!(judge.bad_window >= LOCKED_BAD_WINDOW);
+		}
+
+		let unlocked = Judge::with_timing_scale_unlocked(J9.timing_scale());
+		assert!(unlocked.bad_window < J9.bad_window);
+		assert!((unlocked.great_window - J9.great_window).abs() < 0.0001);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn test_judge_serde() {
+		assert_eq!(serde_json::to_string(J4).unwrap(), "\"J4\"");
+		assert_eq!(serde_json::from_str::<Judge>("\"j7\"").unwrap(), *J7);
+		assert!(serde_json::from_str::<Judge>("\"J10\"").is_err());
+
+		let custom = Judge {
+			name: "Custom",
+			marvelous_window: 0.01,
+			..J4.clone()
+		};
+		let json = serde_json::to_string(&custom).unwrap();
+		assert!(json.starts_with("{\"marvelous_window\":0.01,"));
+		assert_eq!(serde_json::from_str::<Judge>(&json).unwrap(), custom);
+	}
+}
@@ -0,0 +1,168 @@
+use smallvec::SmallVec;
+
+/// One value per lane (column), for any keymode. Up to four lanes are stored inline, so 4k data
+/// doesn't need an extra allocation.
+///
+/// Derefs to a slice, so it can be passed to functions like [`crate::rescore`] that take lanes as
+/// a slice.
+///
+/// ```rust
+/// # use etterna::*;
+/// let mut lanes: Lanes<u32> = Lanes::new(7);
+/// lanes[3] = 5;
+/// assert_eq!(lanes.keycount(), 7);
+/// assert_eq!(lanes.get(3), Some(&5));
+/// assert_eq!(lanes.get(7), None);
+///
+/// let doubled = lanes.map(|&x| x * 2);
+/// assert_eq!(doubled.iter_lanes().sum::<u32>(), 10);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Lanes<T> {
+	lanes: SmallVec<[T; 4]>,
+}
+
+impl<T: Default> Lanes<T> {
+	/// Creates `keycount` lanes with default values
+	pub fn new(keycount: usize) -> Self {
+		Self {
+			lanes: (0..keycount).map(|_| T::default()).collect(),
+		}
+	}
+}
+
+impl<T> Lanes<T> {
+	/// Number of lanes
+	pub fn keycount(&self) -> usize {
+		self.lanes.len()
+	}
+
+	pub fn get(&self, lane: usize) -> Option<&T> {
+		self.lanes.get(lane)
+	}
+
+	pub fn get_mut(&mut self, lane: usize) -> Option<&mut T> {
+		self.lanes.get_mut(lane)
+	}
+
+	/// Iterates over the lanes from left to right
+	pub fn iter_lanes(&self) -> std::slice::Iter<'_, T> {
+		self.lanes.iter()
+	}
+
+	pub fn iter_lanes_mut(&mut self) -> std::slice::IterMut<'_, T> {
+		self.lanes.iter_mut()
+	}
+
+	/// Applies a function to every lane, keeping the keymode
+	pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Lanes<U> {
+		Lanes {
+			lanes: self.lanes.iter().map(f).collect(),
+		}
+	}
+}
+
+/// Four default lanes, for 4k
+impl<T: Default> Default for Lanes<T> {
+	fn default() -> Self {
+		Self::new(4)
+	}
+}
+
+impl<T> std::ops::Deref for Lanes<T> {
+	type Target = [T];
+
+	fn deref(&self) -> &[T] {
+		&self.lanes
+	}
+}
+
+impl<T> std::ops::DerefMut for Lanes<T> {
+	fn deref_mut(&mut self) -> &mut [T] {
+		&mut self.lanes
+	}
+}
+
+impl<T> From<Vec<T>> for Lanes<T> {
+	fn from(lanes: Vec<T>) -> Self {
+		Self {
+			lanes: SmallVec::from_vec(lanes),
+		}
+	}
+}
+
+impl<T> From<[T; 4]> for Lanes<T> {
+	fn from(lanes: [T; 4]) -> Self {
+		Self {
+			lanes: SmallVec::from_buf(lanes),
+		}
+	}
+}
+
+impl<T> std::iter::FromIterator<T> for Lanes<T> {
+	fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+		Self {
+			lanes: iter.into_iter().collect(),
+		}
+	}
+}
+
+impl<T> IntoIterator for Lanes<T> {
+	type Item = T;
+	type IntoIter = smallvec::IntoIter<[T; 4]>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.lanes.into_iter()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a Lanes<T> {
+	type Item = &'a T;
+	type IntoIter = std::slice::Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.lanes.iter()
+	}
+}
+
+// (De)serialized as a plain sequence of lanes
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Lanes<T> {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_seq(self.iter_lanes())
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Lanes<T> {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Vec::<T>::deserialize(deserializer).map(Self::from)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_lanes() {
+		let lanes = Lanes::from([1, 2, 3, 4]);
+		assert_eq!(lanes, Lanes::from(vec![1, 2, 3, 4]));
+		assert_eq!(lanes.iter().rev().collect::<Lanes<_>>().keycount(), 4);
+		assert_eq!(lanes.into_iter().max(), Some(4));
+
+		let mut lanes: Lanes<Vec<u32>> = Lanes::new(10);
+		lanes[9].push(1);
+		assert_eq!(lanes.map(Vec::len).iter_lanes().sum::<usize>(), 1);
+		assert_eq!(Lanes::<u8>::default().keycount(), 4);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn test_serde() {
+		let lanes = Lanes::from(vec![1, 2, 3, 4, 5, 6]);
+		let json = serde_json::to_string(&lanes).unwrap();
+		assert_eq!(json, "[1,2,3,4,5,6]");
+		assert_eq!(serde_json::from_str::<Lanes<u32>>(&json).unwrap(), lanes);
+	}
+}
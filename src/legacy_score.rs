@@ -0,0 +1,148 @@
+/// A pre-Wife scoring system, where each judgement is worth a fixed number of points. See
+/// [`LegacyScore`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LegacyScoringSystem {
+	/// StepMania's dance points (DP), as used by Etterna before Wife. Marvelous and perfect are
+	/// worth the same
+	DancePoints,
+	/// MIGS, which rewards marvelouses over perfects and ignores mines
+	Migs,
+}
+
+impl LegacyScoringSystem {
+	/// Points awarded for a tap judgement
+	pub fn tap_weight(self, judgement: crate::TapJudgement) -> i64 {
+		use crate::TapJudgement::*;
+
+		match (self, judgement) {
+			(Self::DancePoints, Marvelous) => 2,
+			(Self::Migs, Marvelous) => 3,
+			(_, Perfect) => 2,
+			(_, Great) => 1,
+			(_, Good) => 0,
+			(_, Bad) => -4,
+			(_, Miss) => -8,
+		}
+	}
+
+	/// Points awarded for a hold judgement
+	pub fn hold_weight(self, judgement: crate::HoldJudgement) -> i64 {
+		match judgement {
+			crate::HoldJudgement::Held => 6,
+			crate::HoldJudgement::LetGo | crate::HoldJudgement::Missed => 0,
+		}
+	}
+
+	/// Points awarded for a mine hit
+	pub fn mine_hit_weight(self) -> i64 {
+		match self {
+			Self::DancePoints => -8,
+			Self::Migs => 0,
+		}
+	}
+}
+
+/// A score in a legacy scoring system, calculated from judgement counts. Used to display old
+/// scores, or DP/MIGS percentages next to the wifescore, e.g. in migration tools from older themes.
+///
+/// ```rust
+/// # use etterna::*;
+/// let judgements = FullJudgements {
+/// 	marvelouses: 90,
+/// 	perfects: 8,
+/// 	greats: 2,
+/// 	held_holds: 5,
+/// 	..Default::default()
+/// };
+///
+/// let dp = LegacyScore::calculate(LegacyScoringSystem::DancePoints, &judgements);
+/// assert_eq!((dp.points, dp.max_points), (228, 230));
+/// assert_eq!(dp.to_string(), "228/230");
+///
+/// let migs = LegacyScore::calculate(LegacyScoringSystem::Migs, &judgements);
+/// assert_eq!((migs.points, migs.max_points), (318, 330));
+/// assert!(migs.proportion().unwrap() < dp.proportion().unwrap());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LegacyScore {
+	pub system: LegacyScoringSystem,
+	/// Achieved points. Can be negative
+	pub points: i64,
+	/// Points for hitting every tap marvelous and holding every hold
+	pub max_points: u64,
+}
+
+impl LegacyScore {
+	pub fn calculate(system: LegacyScoringSystem, judgements: &crate::FullJudgements) -> Self {
+		let mut points = system.mine_hit_weight() * judgements.hit_mines as i64;
+		let mut max_points = 0;
+		for &judgement in crate::TapJudgement::list() {
+			let count = judgements[judgement] as i64;
+			points += system.tap_weight(judgement) * count;
+			max_points += system.tap_weight(crate::TapJudgement::Marvelous) * count;
+		}
+		for &judgement in crate::HoldJudgement::list() {
+			let count = judgements[judgement] as i64;
+			points += system.hold_weight(judgement) * count;
+			max_points += system.hold_weight(crate::HoldJudgement::Held) * count;
+		}
+
+		Self {
+			system,
+			points,
+			max_points: max_points as u64,
+		}
+	}
+
+	/// Achieved proportion of the maximum points, where 1.0 is a perfect score. Can be negative.
+	///
+	/// Returns None if there are no judgements
+	pub fn proportion(&self) -> Option<f32> {
+		match self.max_points {
+			0 => None,
+			max_points => Some(self.points as f32 / max_points as f32),
+		}
+	}
+
+	/// Like [`Self::proportion`], as a [`crate::Wifescore`] for display and comparison with the
+	/// wifescore. Like in the game, the percentage is floored at -100%
+	pub fn to_percentage(&self) -> Option<crate::Wifescore> {
+		crate::Wifescore::from_proportion(self.proportion()?.max(-1.0))
+	}
+}
+
+impl std::fmt::Display for LegacyScore {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}/{}", self.points, self.max_points)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_legacy_score() {
+		let judgements = crate::FullJudgements {
+			marvelouses: 10,
+			misses: 10,
+			hit_mines: 1,
+			let_go_holds: 1,
+			..Default::default()
+		};
+
+		let dp = LegacyScore::calculate(LegacyScoringSystem::DancePoints, &judgements);
+		assert_eq!(dp.points, 20 - 80 - 8);
+		assert_eq!(dp.max_points, 40 + 6);
+		assert_eq!(dp.to_percentage(), crate::Wifescore::from_proportion(-1.0));
+
+		let migs = LegacyScore::calculate(LegacyScoringSystem::Migs, &judgements);
+		assert_eq!(migs.points, 30 - 80);
+		assert_eq!(migs.max_points, 60 + 6);
+
+		let empty = LegacyScore::calculate(LegacyScoringSystem::Migs, &Default::default());
+		assert_eq!(empty.proportion(), None);
+	}
+}
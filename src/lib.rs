@@ -2,24 +2,46 @@
 	clippy::len_zero,
 	clippy::tabs_in_doc_comments,
 	clippy::collapsible_if,
-	clippy::needless_bool
+	clippy::needless_bool,
+	clippy::manual_is_multiple_of
 )]
 
+mod error;
+pub use error::*;
+
 mod wife;
 pub use wife::*;
 
 mod rescore;
 pub use rescore::*;
 
+mod version;
+pub use version::*;
+
 mod rating_calc;
 pub use rating_calc::*;
 
+mod rating_percentiles;
+pub use rating_percentiles::*;
+
 mod note_subsets;
 pub use note_subsets::*;
 
+mod hands;
+pub use hands::*;
+
+mod aliases;
+pub use aliases::*;
+
 mod structs;
 pub use structs::*;
 
+mod lanes;
+pub use lanes::*;
+
+mod legacy_score;
+pub use legacy_score::*;
+
 mod skillsets;
 pub use skillsets::*;
 
@@ -32,12 +54,87 @@ pub use timing_info::*;
 mod judge;
 pub use judge::*;
 
+mod replay;
+pub use replay::*;
+
+mod replay_index;
+pub use replay_index::*;
+
+#[cfg(feature = "bms")]
+mod bms;
+#[cfg(feature = "bms")]
+pub use bms::*;
+
+#[cfg(feature = "binary-cache")]
+mod binary_cache;
+#[cfg(feature = "binary-cache")]
+pub use binary_cache::*;
+
+mod replay_comparison;
+pub use replay_comparison::*;
+
+mod render;
+pub use render::*;
+
+mod chart;
+pub use chart::*;
+
+mod simfile;
+pub use simfile::*;
+
+mod pack;
+pub use pack::*;
+
+mod song_cache;
+pub use song_cache::*;
+
+mod pattern;
+pub use pattern::*;
+
+mod sessions;
+pub use sessions::*;
+
+mod calendar;
+pub use calendar::*;
+
+mod top_scores;
+pub use top_scores::*;
+
+mod wifescore_distribution;
+pub use wifescore_distribution::*;
+
+mod pb_tracker;
+pub use pb_tracker::*;
+
+mod score;
+pub use score::*;
+
+mod analysis;
+pub use analysis::*;
+
+mod validity;
+pub use validity::*;
+
+mod ssr_provider;
+pub use ssr_provider::*;
+
+mod ssr_eligibility;
+pub use ssr_eligibility::*;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "minacalc")]
+pub mod minacalc;
+
 pub mod prelude {
 	pub use crate::structs::*;
-	pub use crate::{Rate, Skillset7, Skillset8, Skillsets7, Skillsets8, Wifescore};
+	pub use crate::{GetSkillset, Rate, Skillset7, Skillset8, Skillsets7, Skillsets8, Wifescore};
 }
 
-mod util;
+pub mod util;
+
+pub mod sequences;
 
 #[cfg(all(feature = "rayon", not(feature = "parallel")))]
 compile_error!("Use the `parallel` feature flag instead of `rayon`");
@@ -45,47 +142,6 @@ compile_error!("Use the `parallel` feature flag instead of `rayon`");
 #[cfg(feature = "parallel")]
 use rayon::iter::ParallelIterator;
 
-// this needs to be here for some reason, and it also needs to be publically accessible because MACROS
-#[doc(hidden)]
-#[macro_export]
-macro_rules! impl_get_skillset {
-	($return_type:ty, $self_:ident, $overall_getter:expr) => {
-		/// Get a specific skillset value
-		pub fn get(&self, skillset: impl Into<crate::Skillset8>) -> $return_type {
-			let $self_ = self;
-			match skillset.into() {
-				crate::Skillset8::Overall => $overall_getter,
-				crate::Skillset8::Stream => self.stream,
-				crate::Skillset8::Jumpstream => self.jumpstream,
-				crate::Skillset8::Handstream => self.handstream,
-				crate::Skillset8::Stamina => self.stamina,
-				crate::Skillset8::Jackspeed => self.jackspeed,
-				crate::Skillset8::Chordjack => self.chordjack,
-				crate::Skillset8::Technical => self.technical,
-			}
-		}
-	};
-	($return_type:ty, $self_:ident, $overall_getter:expr, $overall_getter_pre_070:expr) => {
-		crate::impl_get_skillset!($return_type, $self_, $overall_getter);
-
-		/// Get a specific skillset value. If Overall was requested, use the pre-0.70 algorithm
-		/// for calculation.
-		pub fn get_pre_070(&self, skillset: impl Into<crate::Skillset8>) -> $return_type {
-			let $self_ = self;
-			match skillset.into() {
-				crate::Skillset8::Overall => $overall_getter_pre_070,
-				crate::Skillset8::Stream => self.stream,
-				crate::Skillset8::Jumpstream => self.jumpstream,
-				crate::Skillset8::Handstream => self.handstream,
-				crate::Skillset8::Stamina => self.stamina,
-				crate::Skillset8::Jackspeed => self.jackspeed,
-				crate::Skillset8::Chordjack => self.chordjack,
-				crate::Skillset8::Technical => self.technical,
-			}
-		}
-	};
-}
-
 // do we even need this?
 // #[doc(hidden)]
 // #[macro_export]
@@ -116,6 +172,7 @@ where
 
 /// Representation of a player's ratings over time. See [`skill_timeline`]
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SkillTimeline<T> {
 	pub changes: Vec<(T, Skillsets8)>,
 }
@@ -131,34 +188,64 @@ impl<T: PartialEq + Send> SkillTimeline<T> {
 	/// You can use almost any type you want as a group identifier as long as it can be compared
 	/// (has a PartialEq impl).
 	///
-	/// You can either use the current, 0.70+ algorithm, or the old algorithm from older game versions.
-	/// For that, use the `pre_070` parameter.
+	/// The rating algorithm depends on the game version: versions before 0.70 used a different
+	/// algorithm, see [`EtternaVersion::is_pre_070`].
 	///
 	/// ```rust,ignore
 	/// scores = &[
-	/// 	("2020-08-05", ChartSkillsets { ... }),
-	/// 	("2020-08-05", ChartSkillsets { ... }),
-	/// 	("2020-08-05", ChartSkillsets { ... }),
-	/// 	("2020-08-06", ChartSkillsets { ... }),
-	/// 	("2020-08-06", ChartSkillsets { ... }),
+	/// 	("2020-08-05", Skillsets7 { ... }),
+	/// 	("2020-08-05", Skillsets7 { ... }),
+	/// 	("2020-08-05", Skillsets7 { ... }),
+	/// 	("2020-08-06", Skillsets7 { ... }),
+	/// 	("2020-08-06", Skillsets7 { ... }),
 	/// ];
 	///
-	/// let timeline = skill_timeline(scores, false);
+	/// let timeline = SkillTimeline::calculate(scores, EtternaVersion::LATEST);
 	/// assert_eq!(timeline.changes.len(), 2);
 	/// ```
-	pub fn calculate<I>(iterator: I, pre_070: bool) -> SkillTimeline<T>
+	pub fn calculate<I>(iterator: I, version: EtternaVersion) -> SkillTimeline<T>
 	where
 		I: IntoIterator<Item = (T, Skillsets7)>,
 	{
-		let skillset_calc_function = if pre_070 {
-			rating_calc::calculate_player_skillset_rating_pre_070
-		} else {
-			rating_calc::calculate_player_skillset_rating
-		};
-		let overall_calc_function = if pre_070 {
-			Skillsets7::calc_player_overall_pre_070
+		Self::calculate_with_progress(iterator, version, &SkillTimelineProgress::new())
+			.expect("the calculation can't be cancelled without access to the progress handle")
+	}
+
+	/// Like [`Self::calculate`], but reports the progress to `progress` after each group, and
+	/// stops early if [`SkillTimelineProgress::cancel`] is called.
+	///
+	/// Share the progress handle with another thread (e.g. a UI thread) to monitor or cancel the
+	/// calculation while it's running. Returns None if the calculation was cancelled.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let ssr = Skillsets7 { stream: 20.0, ..Default::default() };
+	/// let scores = vec![(1, ssr.clone()), (1, ssr.clone()), (2, ssr.clone())];
+	///
+	/// let progress = SkillTimelineProgress::new();
+	/// let timeline = SkillTimeline::calculate_with_progress(scores.clone(), EtternaVersion::LATEST, &progress);
+	/// assert_eq!(timeline.unwrap().changes.len(), 2);
+	/// assert_eq!(progress.num_groups_done(), 2);
+	/// assert_eq!(progress.proportion_done(), Some(1.0));
+	///
+	/// let progress = SkillTimelineProgress::new();
+	/// progress.cancel();
+	/// assert!(SkillTimeline::calculate_with_progress(scores, EtternaVersion::LATEST, &progress).is_none());
+	/// ```
+	pub fn calculate_with_progress<I>(
+		iterator: I,
+		version: EtternaVersion,
+		progress: &SkillTimelineProgress,
+	) -> Option<SkillTimeline<T>>
+	where
+		I: IntoIterator<Item = (T, Skillsets7)>,
+	{
+		use std::sync::atomic::Ordering;
+
+		let player_rating_calc_function = if version.is_pre_070() {
+			Skillsets7::calc_player_rating_pre_070
 		} else {
-			Skillsets7::calc_player_overall
+			Skillsets7::calc_player_rating
 		};
 
 		let iterator = iterator.into_iter();
@@ -177,6 +264,14 @@ impl<T: PartialEq + Send> SkillTimeline<T> {
 		let mut day_indices: Vec<(T, usize)> = vec![];
 		let mut prev_day_id = None;
 		for (day_id, ssr) in iterator {
+			// must happen before pushing this score, or the score would count into the previous day
+			if let Some(prev_day_id) = prev_day_id.take() {
+				if prev_day_id != day_id {
+					day_indices.push((prev_day_id, rating_vectors[0].len()));
+				}
+			}
+			prev_day_id = Some(day_id);
+
 			rating_vectors[0].push(ssr.stream);
 			rating_vectors[1].push(ssr.jumpstream);
 			rating_vectors[2].push(ssr.handstream);
@@ -184,36 +279,83 @@ impl<T: PartialEq + Send> SkillTimeline<T> {
 			rating_vectors[4].push(ssr.jackspeed);
 			rating_vectors[5].push(ssr.chordjack);
 			rating_vectors[6].push(ssr.technical);
-
-			if let Some(prev_day_id) = prev_day_id.take() {
-				if prev_day_id != day_id {
-					day_indices.push((prev_day_id, rating_vectors[0].len()));
-				}
-			}
-			prev_day_id = Some(day_id);
 		}
 		if let Some(prev_day_id) = prev_day_id {
 			day_indices.push((prev_day_id, rating_vectors[0].len()));
 		}
 
+		progress
+			.num_groups_total
+			.store(day_indices.len(), Ordering::Relaxed);
+
 		let changes = par_iter_maybe(day_indices)
 			.map(|(day_id, i)| {
-				(
-					day_id,
-					overall_calc_function(&Skillsets7 {
-						stream: (skillset_calc_function)(&rating_vectors[0][..i]),
-						jumpstream: (skillset_calc_function)(&rating_vectors[1][..i]),
-						handstream: (skillset_calc_function)(&rating_vectors[2][..i]),
-						stamina: (skillset_calc_function)(&rating_vectors[3][..i]),
-						jackspeed: (skillset_calc_function)(&rating_vectors[4][..i]),
-						chordjack: (skillset_calc_function)(&rating_vectors[5][..i]),
-						technical: (skillset_calc_function)(&rating_vectors[6][..i]),
-					}),
-				)
+				if progress.is_cancelled() {
+					return None;
+				}
+
+				let rating = player_rating_calc_function(&[
+					&rating_vectors[0][..i],
+					&rating_vectors[1][..i],
+					&rating_vectors[2][..i],
+					&rating_vectors[3][..i],
+					&rating_vectors[4][..i],
+					&rating_vectors[5][..i],
+					&rating_vectors[6][..i],
+				]);
+				progress.num_groups_done.fetch_add(1, Ordering::Relaxed);
+				Some((day_id, rating))
 			})
-			.collect();
+			.collect::<Option<_>>()?;
 
-		Self { changes }
+		Some(Self { changes })
+	}
+}
+
+/// Progress and cancellation handle for [`SkillTimeline::calculate_with_progress`]. Can be shared
+/// across threads
+#[derive(Debug, Default)]
+pub struct SkillTimelineProgress {
+	num_groups_done: std::sync::atomic::AtomicUsize,
+	num_groups_total: std::sync::atomic::AtomicUsize,
+	is_cancelled: std::sync::atomic::AtomicBool,
+}
+
+impl SkillTimelineProgress {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Number of groups whose rating has been calculated so far
+	pub fn num_groups_done(&self) -> usize {
+		self.num_groups_done
+			.load(std::sync::atomic::Ordering::Relaxed)
+	}
+
+	/// Total number of groups. Zero until all scores have been read and grouped
+	pub fn num_groups_total(&self) -> usize {
+		self.num_groups_total
+			.load(std::sync::atomic::Ordering::Relaxed)
+	}
+
+	/// Proportion of groups done, from 0.0 to 1.0. Returns None if the number of groups isn't known
+	/// yet
+	pub fn proportion_done(&self) -> Option<f32> {
+		match self.num_groups_total() {
+			0 => None,
+			total => Some(self.num_groups_done() as f32 / total as f32),
+		}
+	}
+
+	/// Requests the calculation to stop. Groups that are already being calculated are finished,
+	/// but no new groups are started
+	pub fn cancel(&self) {
+		self.is_cancelled
+			.store(true, std::sync::atomic::Ordering::Relaxed);
+	}
+
+	pub fn is_cancelled(&self) -> bool {
+		self.is_cancelled.load(std::sync::atomic::Ordering::Relaxed)
 	}
 }
 
@@ -223,5 +365,67 @@ where
 	I: IntoIterator<Item = (T, Skillsets7)>,
 	T: PartialEq + Send,
 {
-	SkillTimeline::calculate(iterator, pre_070)
+	SkillTimeline::calculate(iterator, EtternaVersion::from_pre_070(pre_070))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_skill_timeline_day_grouping() {
+		let weak = Skillsets7 {
+			stream: 10.0,
+			..Default::default()
+		};
+		let strong = Skillsets7 {
+			stream: 30.0,
+			..Default::default()
+		};
+		let scores = vec![(1, weak.clone()), (1, weak.clone()), (2, strong)];
+
+		let timeline = SkillTimeline::calculate(scores, EtternaVersion::LATEST);
+		let days: Vec<i32> = timeline.changes.iter().map(|&(day, _)| day).collect();
+		assert_eq!(days, vec![1, 2]);
+
+		// the first day's rating must only include that day's scores, not the first score of the
+		// next day
+		let first_day_only =
+			SkillTimeline::calculate(vec![(1, weak.clone()), (1, weak)], EtternaVersion::LATEST);
+		assert_eq!(timeline.changes[0], first_day_only.changes[0]);
+		assert!(timeline.changes[1].1.stream > timeline.changes[0].1.stream);
+	}
+
+	#[test]
+	#[cfg(feature = "serde")]
+	fn test_skill_timeline_serde() {
+		let ssr = Skillsets7 {
+			stream: 20.0,
+			..Default::default()
+		};
+		let scores = vec![
+			(
+				Day {
+					year: 2021,
+					month: 3,
+					day: 14,
+				},
+				ssr.clone(),
+			),
+			(
+				Day {
+					year: 2021,
+					month: 3,
+					day: 15,
+				},
+				ssr,
+			),
+		];
+		let timeline = SkillTimeline::calculate(scores, EtternaVersion::LATEST);
+		let json = serde_json::to_string(&timeline).unwrap();
+		assert_eq!(
+			serde_json::from_str::<SkillTimeline<Day>>(&json).unwrap(),
+			timeline
+		);
+	}
 }
@@ -0,0 +1,164 @@
+//! FFI bindings to the official MinaCalc difficulty calculator, enabled by the `minacalc` feature.
+//!
+//! This module doesn't ship MinaCalc itself. Build MinaCalc as a static library called `minacalc`
+//! (e.g. `libminacalc.a`) using its C API header, and point the `MINACALC_LIB_DIR` environment
+//! variable to the directory containing it when building this crate.
+//!
+//! ```rust,ignore
+//! use etterna::minacalc::Calc;
+//!
+//! let mut calc = Calc::new().unwrap();
+//! let msd = calc.calc_msd(&note_data, &timing_info, etterna::Rate::from_f32(1.0).unwrap());
+//! println!("Stream MSD: {}", msd.stream);
+//! ```
+
+use std::os::raw::{c_float, c_int, c_uint};
+
+mod ffi {
+	use super::*;
+
+	#[repr(C)]
+	pub struct CalcHandle {
+		_private: [u8; 0],
+	}
+
+	#[repr(C)]
+	#[derive(Debug, Copy, Clone)]
+	pub struct Ssr {
+		pub overall: c_float,
+		pub stream: c_float,
+		pub jumpstream: c_float,
+		pub handstream: c_float,
+		pub stamina: c_float,
+		pub jackspeed: c_float,
+		pub chordjack: c_float,
+		pub technical: c_float,
+	}
+
+	#[repr(C)]
+	#[derive(Debug, Copy, Clone)]
+	pub struct NoteInfo {
+		/// Bitset of the taps in this row, least significant bit is the leftmost column
+		pub notes: c_uint,
+		/// Position of this row in seconds, at 1.00x
+		pub row_time: c_float,
+	}
+
+	extern "C" {
+		pub fn calc_version() -> c_int;
+		pub fn create_calc() -> *mut CalcHandle;
+		pub fn destroy_calc(calc: *mut CalcHandle);
+		pub fn calc_ssr(
+			calc: *mut CalcHandle,
+			rows: *mut NoteInfo,
+			num_rows: usize,
+			music_rate: c_float,
+			score_goal: c_float,
+		) -> Ssr;
+	}
+}
+
+/// Wifescore at which a score's SSR equals the chart's MSD
+const MSD_WIFESCORE: f32 = 0.93;
+
+/// Version of the linked MinaCalc library, e.g. to invalidate stored MSD values after updates
+pub fn calc_version() -> i32 {
+	// SAFETY: the function has no preconditions
+	unsafe { ffi::calc_version() }
+}
+
+/// An instance of the MinaCalc difficulty calculator. Creating an instance is somewhat expensive,
+/// so reuse it across charts
+pub struct Calc {
+	handle: *mut ffi::CalcHandle,
+}
+
+impl Calc {
+	/// Returns None if MinaCalc failed to create a calculator instance
+	pub fn new() -> Option<Self> {
+		// SAFETY: the function has no preconditions
+		let handle = unsafe { ffi::create_calc() };
+		if handle.is_null() {
+			None
+		} else {
+			Some(Self { handle })
+		}
+	}
+
+	/// Calculates the MSD (difficulty) of a chart at the given rate, where `timing_info` is the
+	/// chart's timing info.
+	///
+	/// Returns None if the chart contains no taps
+	pub fn calc_msd(
+		&mut self,
+		note_data: &crate::NoteData,
+		timing_info: &crate::TimingInfo,
+		rate: crate::Rate,
+	) -> Option<crate::Skillsets7> {
+		let wifescore = crate::Wifescore::from_proportion(MSD_WIFESCORE)?;
+		Some(
+			self.calc_ssr(note_data, timing_info, rate, wifescore)?
+				.to_skillsets7(),
+		)
+	}
+
+	/// Calculates the SSR (score-specific rating) of a score with the given wifescore on a chart at
+	/// the given rate. See [`Self::calc_msd`] for the meaning of `note_data` and `timing_info`.
+	///
+	/// Returns None if the chart contains no taps
+	pub fn calc_ssr(
+		&mut self,
+		note_data: &crate::NoteData,
+		timing_info: &crate::TimingInfo,
+		rate: crate::Rate,
+		wifescore: crate::Wifescore,
+	) -> Option<crate::Skillsets8> {
+		let tap_rows: Vec<&crate::ChartRow> = note_data
+			.rows()
+			.iter()
+			.filter(|row| row.taps.bits() != 0)
+			.collect();
+		if tap_rows.is_empty() {
+			return None;
+		}
+
+		let ticks: Vec<u32> = tap_rows.iter().map(|row| row.tick).collect();
+		let mut note_infos: Vec<ffi::NoteInfo> = tap_rows
+			.iter()
+			.zip(timing_info.ticks_to_seconds(&ticks))
+			.map(|(row, second)| ffi::NoteInfo {
+				notes: row.taps.bits(),
+				row_time: second,
+			})
+			.collect();
+
+		// SAFETY: the handle is valid for the lifetime of self, and the pointer and length describe
+		// a valid, initialized buffer
+		let ssr = unsafe {
+			ffi::calc_ssr(
+				self.handle,
+				note_infos.as_mut_ptr(),
+				note_infos.len(),
+				rate.as_f32(),
+				wifescore.as_proportion(),
+			)
+		};
+		Some(crate::Skillsets8 {
+			overall: ssr.overall,
+			stream: ssr.stream,
+			jumpstream: ssr.jumpstream,
+			handstream: ssr.handstream,
+			stamina: ssr.stamina,
+			jackspeed: ssr.jackspeed,
+			chordjack: ssr.chordjack,
+			technical: ssr.technical,
+		})
+	}
+}
+
+impl Drop for Calc {
+	fn drop(&mut self) {
+		// SAFETY: the handle was created by create_calc and is destroyed only once
+		unsafe { ffi::destroy_calc(self.handle) }
+	}
+}
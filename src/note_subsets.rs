@@ -1,5 +1,6 @@
 /// Information about a combo found by [`find_fastest_note_subset`]
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FastestComboInfo {
 	pub start_second: f32,
 	pub end_second: f32,
@@ -209,9 +210,9 @@ mod tests {
 			expected_length: u32,
 			expected_speed: f32,
 		) {
-			let fastest_subset = find_fastest_note_subset(&seconds, min_num_notes, max_num_notes);
+			let fastest_subset = find_fastest_note_subset(seconds, min_num_notes, max_num_notes);
 			let fastest_wife_pts_subset = find_fastest_note_subset_wife_pts(
-				&seconds,
+				seconds,
 				min_num_notes,
 				max_num_notes,
 				&vec![1.0; seconds.len()],
@@ -243,4 +244,12 @@ mod tests {
 										   // than the size-6 window!
 		test_the_functions(seconds, 5, 5, 5, 1.25);
 	}
+
+	#[test]
+	#[cfg(feature = "serde")]
+	fn test_fastest_combo_info_serde() {
+		let info = find_fastest_note_subset(&[0.0, 1.0, 1.5, 2.0, 4.0], 3, 3);
+		let json = serde_json::to_string(&info).unwrap();
+		assert_eq!(serde_json::from_str::<FastestComboInfo>(&json).unwrap(), info);
+	}
 }
@@ -0,0 +1,337 @@
+//! Reading of song packs, i.e. folders of song folders, for pack-wide statistics. See
+//! [`read_pack`].
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PackReadError {
+	#[error("Could not read {}: {source}", path.display())]
+	Io {
+		path: PathBuf,
+		source: std::io::Error,
+	},
+	#[error("Song folder {} doesn't contain a .sm or .ssc file", path.display())]
+	NoSimfile { path: PathBuf },
+	#[error("Could not parse simfile {}: {source}", path.display())]
+	Simfile {
+		path: PathBuf,
+		source: crate::SimfileParseError,
+	},
+}
+
+/// Contents of a pack's `pack.ini`. Section headers are ignored, and keys are case insensitive.
+///
+/// ```rust
+/// # use etterna::*;
+/// let ini = PackIni::parse("
+/// [Group]
+/// Version=1
+/// DisplayTitle=Example Pack
+/// SyncOffset=NULL
+/// ");
+/// assert_eq!(ini.display_title(), Some("Example Pack"));
+/// assert_eq!(ini.get("version"), Some("1"));
+/// assert_eq!(ini.get("Banner"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackIni {
+	/// `(key, value)` pairs in file order
+	pub entries: Vec<(String, String)>,
+}
+
+impl PackIni {
+	/// Parses the contents of a `pack.ini`. Malformed lines and comments are skipped
+	pub fn parse(text: &str) -> Self {
+		let entries = text
+			.lines()
+			.map(str::trim)
+			.filter(|line| !line.starts_with(';') && !line.starts_with('#'))
+			.filter_map(|line| {
+				let equals_index = line.find('=')?;
+				Some((
+					line[..equals_index].trim().to_owned(),
+					line[equals_index + 1..].trim().to_owned(),
+				))
+			})
+			.collect();
+		Self { entries }
+	}
+
+	/// Value of the first entry with the given key
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.entries
+			.iter()
+			.find(|(entry_key, _)| entry_key.eq_ignore_ascii_case(key))
+			.map(|(_, value)| value.as_str())
+	}
+
+	pub fn display_title(&self) -> Option<&str> {
+		self.get("DisplayTitle")
+	}
+
+	/// Either `NULL` or `ITG`, depending on which sync the pack's files are offset to
+	pub fn sync_offset(&self) -> Option<&str> {
+		self.get("SyncOffset")
+	}
+}
+
+/// A song of a [`Pack`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackSong {
+	/// Path of the song folder
+	pub path: PathBuf,
+	pub simfile: crate::Simfile,
+}
+
+/// A song pack read with [`read_pack`]
+#[derive(Debug)]
+pub struct Pack {
+	/// Name of the pack folder
+	pub name: String,
+	/// None if the pack doesn't have a `pack.ini`
+	pub ini: Option<PackIni>,
+	/// Sorted by folder name
+	pub songs: Vec<PackSong>,
+	/// Song folders that couldn't be read, with the reason
+	pub skipped_songs: Vec<PackReadError>,
+}
+
+impl Pack {
+	/// The title from the `pack.ini`, or the folder name if there is none
+	pub fn title(&self) -> &str {
+		self.ini
+			.as_ref()
+			.and_then(PackIni::display_title)
+			.unwrap_or(&self.name)
+	}
+
+	/// Shorthand for [`PackStatistics::calculate`]
+	pub fn statistics(&self) -> PackStatistics {
+		PackStatistics::calculate(self.songs.iter().map(|song| &song.simfile))
+	}
+}
+
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> PackReadError + '_ {
+	move |source| PackReadError::Io {
+		path: path.to_owned(),
+		source,
+	}
+}
+
+/// Reads the simfile of a song folder. Like in the game, .ssc files take precedence over .sm files
+fn read_song(path: &Path) -> Result<PackSong, PackReadError> {
+	let mut simfile_paths = Vec::new();
+	for entry in std::fs::read_dir(path).map_err(io_error(path))? {
+		let file_path = entry.map_err(io_error(path))?.path();
+		let extension = file_path
+			.extension()
+			.and_then(|extension| extension.to_str())
+			.map(str::to_ascii_lowercase);
+		match extension.as_deref() {
+			Some("ssc") => simfile_paths.push((0, file_path)),
+			Some("sm") => simfile_paths.push((1, file_path)),
+			_ => {}
+		}
+	}
+	simfile_paths.sort();
+
+	let (_, simfile_path) =
+		simfile_paths
+			.into_iter()
+			.next()
+			.ok_or_else(|| PackReadError::NoSimfile {
+				path: path.to_owned(),
+			})?;
+	let bytes = std::fs::read(&simfile_path).map_err(io_error(&simfile_path))?;
+	let simfile = crate::parse_simfile(&bytes).map_err(|source| PackReadError::Simfile {
+		path: simfile_path,
+		source,
+	})?;
+
+	Ok(PackSong {
+		path: path.to_owned(),
+		simfile,
+	})
+}
+
+/// Reads a pack folder: the `pack.ini`, if any, and the simfile of every song folder inside.
+///
+/// Fails only if the pack folder or its `pack.ini` can't be read. Song folders that can't be read
+/// or parsed are collected in [`Pack::skipped_songs`].
+pub fn read_pack(path: impl AsRef<Path>) -> Result<Pack, PackReadError> {
+	let path = path.as_ref();
+
+	let ini_path = path.join("pack.ini");
+	let ini = match std::fs::read(&ini_path) {
+		Ok(bytes) => Some(PackIni::parse(&String::from_utf8_lossy(&bytes))),
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+		Err(e) => return Err(io_error(&ini_path)(e)),
+	};
+
+	let mut song_paths = Vec::new();
+	for entry in std::fs::read_dir(path).map_err(io_error(path))? {
+		let entry = entry.map_err(io_error(path))?;
+		if entry.file_type().map_err(io_error(path))?.is_dir() {
+			song_paths.push(entry.path());
+		}
+	}
+	song_paths.sort();
+
+	let mut songs = Vec::with_capacity(song_paths.len());
+	let mut skipped_songs = Vec::new();
+	for song_path in song_paths {
+		match read_song(&song_path) {
+			Ok(song) => songs.push(song),
+			Err(e) => skipped_songs.push(e),
+		}
+	}
+
+	Ok(Pack {
+		name: path
+			.file_name()
+			.map(|name| name.to_string_lossy().into_owned())
+			.unwrap_or_default(),
+		ini,
+		songs,
+		skipped_songs,
+	})
+}
+
+/// Aggregated statistics over the charts of a pack. See [`PackStatistics::calculate`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackStatistics {
+	pub num_songs: u32,
+	pub num_charts: u32,
+	/// Number of charts per keycount
+	pub keymodes: BTreeMap<u8, u32>,
+	/// Number of charts per difficulty slot. Charts with an unrecognized difficulty aren't counted
+	pub difficulties: BTreeMap<crate::Difficulty, u32>,
+	/// Lowest and highest meter, or None if no chart has a meter
+	pub meter_range: Option<(u32, u32)>,
+	/// Mean of the charts' average NPS, at 1.0x
+	pub average_nps: f32,
+	/// Highest peak NPS of any chart, at 1.0x
+	pub peak_nps: f32,
+	/// Sum of the song lengths in seconds, at 1.0x. A song is as long as its longest chart
+	pub total_length_seconds: f32,
+}
+
+impl PackStatistics {
+	/// Aggregates the charts of the given songs
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let simfile = parse_simfile("
+	/// #BPMS:0.000=60.000;
+	/// #NOTES:dance-single::Hard:10::1000 0100 0010 0001;
+	/// #NOTES:dance-single::Challenge:14::1100 0011 1100 0011;
+	/// #NOTES:dance-solo::Edit:20::110000 001100 000011 111111;
+	/// ".replace(' ', "\n").as_bytes()).unwrap();
+	///
+	/// let statistics = PackStatistics::calculate(&[simfile]);
+	/// assert_eq!((statistics.num_songs, statistics.num_charts), (1, 3));
+	/// assert_eq!(statistics.keymodes.get(&4), Some(&2));
+	/// assert_eq!(statistics.keymodes.get(&6), Some(&1));
+	/// assert_eq!(statistics.difficulties.get(&Difficulty::Edit), Some(&1));
+	/// assert_eq!(statistics.meter_range, Some((10, 20)));
+	/// assert_eq!(statistics.total_length_seconds, 3.0);
+	/// ```
+	pub fn calculate<'a>(songs: impl IntoIterator<Item = &'a crate::Simfile>) -> Self {
+		let mut statistics = Self::default();
+		let mut nps_sum = 0.0;
+		for song in songs {
+			statistics.num_songs += 1;
+
+			let mut song_length: f32 = 0.0;
+			for chart in &song.charts {
+				let summary = chart.summary();
+				statistics.num_charts += 1;
+				*statistics
+					.keymodes
+					.entry(chart.note_data.keycount())
+					.or_insert(0) += 1;
+				if let Some(difficulty) = chart.difficulty {
+					*statistics.difficulties.entry(difficulty).or_insert(0) += 1;
+				}
+				if let Some(meter) = chart.meter {
+					statistics.meter_range = Some(match statistics.meter_range {
+						Some((min, max)) => (min.min(meter), max.max(meter)),
+						None => (meter, meter),
+					});
+				}
+				nps_sum += summary.average_nps;
+				statistics.peak_nps = statistics.peak_nps.max(summary.peak_nps);
+				song_length = song_length.max(summary.length_seconds);
+			}
+			statistics.total_length_seconds += song_length;
+		}
+
+		if statistics.num_charts > 0 {
+			statistics.average_nps = nps_sum / statistics.num_charts as f32;
+		}
+		statistics
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_read_pack() {
+		let pack_path =
+			std::env::temp_dir().join(format!("etterna-test-pack-{}", std::process::id()));
+		let song = |name: &str, file_name: &str, contents: &str| {
+			let song_path = pack_path.join(name);
+			std::fs::create_dir_all(&song_path).unwrap();
+			std::fs::write(song_path.join(file_name), contents).unwrap();
+		};
+		song(
+			"B Song",
+			"song.sm",
+			"#TITLE:B;#BPMS:0=120;#NOTES:dance-single::Easy:3::1000\n0100;",
+		);
+		song(
+			"A Song",
+			"song.ssc",
+			"#TITLE:A;#BPMS:0=120;#NOTEDATA:;#DIFFICULTY:Hard;#NOTES:1000\n0100;",
+		);
+		// the .ssc file takes precedence, so this broken file is never parsed
+		song("A Song", "song.sm", "#NOTES:garbage;");
+		song("Broken", "song.sm", "#TITLE:Broken;");
+		song("Empty", "readme.txt", "");
+		std::fs::write(
+			pack_path.join("pack.ini"),
+			"[Group]\nDisplayTitle=Test Pack\n",
+		)
+		.unwrap();
+
+		let pack = read_pack(&pack_path);
+		std::fs::remove_dir_all(&pack_path).unwrap();
+		let pack = pack.unwrap();
+
+		assert_eq!(pack.title(), "Test Pack");
+		let titles: Vec<_> = pack
+			.songs
+			.iter()
+			.map(|song| song.simfile.title.as_deref().unwrap())
+			.collect();
+		assert_eq!(titles, vec!["A", "B"]);
+		assert!(matches!(
+			pack.skipped_songs[..],
+			[
+				PackReadError::Simfile { .. },
+				PackReadError::NoSimfile { .. }
+			]
+		));
+
+		let statistics = pack.statistics();
+		assert_eq!(statistics.num_charts, 2);
+		assert_eq!(statistics.keymodes.get(&4), Some(&2));
+		assert_eq!(statistics.average_nps, 2.0);
+	}
+}
@@ -0,0 +1,210 @@
+use thiserror::Error;
+
+/// A common kind of pattern, for [`Pattern::generate`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrillKind {
+	/// Alternating between the two middle lanes
+	Trill,
+	/// Alternating between a chord on the left half and a chord on the right half
+	Jumptrill,
+	/// Going through all lanes from left to right, over and over
+	Roll,
+	/// Hitting the leftmost lane over and over
+	Jack,
+	/// Hitting all lanes at once, over and over
+	Quads,
+}
+
+/// Reason why a [`Pattern`] is invalid for a keymode. See [`Pattern::validate`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PatternError {
+	#[error("Lane {lane} in row {row} is out of range for {keymode}k")]
+	LaneOutOfRange { row: usize, lane: u32, keymode: u32 },
+	#[error("Lane {lane} appears multiple times in row {row}")]
+	DuplicateLane { row: usize, lane: u32 },
+}
+
+/// A sequence of rows, each consisting of the lanes that have a note in that row. Patterns have no
+/// timing information; every row is one step.
+///
+/// Lanes are zero-indexed, starting from the left.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pattern {
+	pub rows: Vec<Vec<u32>>,
+}
+
+impl Pattern {
+	/// Generates a drill pattern with `length` rows for the given keymode, e.g. for practice
+	/// snippets in trainer tools.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let pattern = Pattern::generate(DrillKind::Jumptrill, 4, 3);
+	/// assert_eq!(pattern.rows, vec![vec![0, 1], vec![2, 3], vec![0, 1]]);
+	///
+	/// let pattern = Pattern::generate(DrillKind::Roll, 6, 7);
+	/// assert_eq!(pattern.rows[5..], [vec![5], vec![0]]);
+	/// ```
+	pub fn generate(kind: DrillKind, keymode: u32, length: usize) -> Self {
+		if keymode == 0 {
+			return Self::default();
+		}
+
+		// For odd keymodes, the middle lane is left out of jumptrills
+		let half = (keymode / 2).max(1);
+		let left_half: Vec<u32> = (0..half).collect();
+		let right_half: Vec<u32> = ((keymode - half)..keymode).collect();
+		let middle_left = (keymode - 1) / 2;
+		let middle_right = (middle_left + 1).min(keymode - 1);
+
+		let rows = (0..length)
+			.map(|i| match kind {
+				DrillKind::Trill if i.is_multiple_of(2) => vec![middle_left],
+				DrillKind::Trill => vec![middle_right],
+				DrillKind::Jumptrill if i.is_multiple_of(2) => left_half.clone(),
+				DrillKind::Jumptrill => right_half.clone(),
+				DrillKind::Roll => vec![(i % keymode as usize) as u32],
+				DrillKind::Jack => vec![0],
+				DrillKind::Quads => (0..keymode).collect(),
+			})
+			.collect();
+
+		Self { rows }
+	}
+
+	/// Checks that every lane is within the keymode, and that no lane appears twice in a row.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let pattern = Pattern { rows: vec![vec![0, 1], vec![4]] };
+	/// assert!(pattern.validate(5).is_ok());
+	/// assert_eq!(
+	/// 	pattern.validate(4),
+	/// 	Err(PatternError::LaneOutOfRange { row: 1, lane: 4, keymode: 4 }),
+	/// );
+	/// ```
+	pub fn validate(&self, keymode: u32) -> Result<(), PatternError> {
+		for (row_index, row) in self.rows.iter().enumerate() {
+			for (i, &lane) in row.iter().enumerate() {
+				if lane >= keymode {
+					return Err(PatternError::LaneOutOfRange {
+						row: row_index,
+						lane,
+						keymode,
+					});
+				}
+				if row[..i].contains(&lane) {
+					return Err(PatternError::DuplicateLane {
+						row: row_index,
+						lane,
+					});
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Moves every note from lane `i` to lane `mapping[i]`. Afterwards, the lanes within each row
+	/// are sorted and notes that landed on the same lane are merged.
+	///
+	/// Panics if a lane has no entry in `mapping`
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let mut pattern = Pattern { rows: vec![vec![0, 1], vec![2]] };
+	/// pattern.remap_lanes(&[3, 0, 0]);
+	/// assert_eq!(pattern.rows, vec![vec![0, 3], vec![0]]);
+	/// ```
+	pub fn remap_lanes(&mut self, mapping: &[u32]) {
+		for row in &mut self.rows {
+			for lane in row.iter_mut() {
+				*lane = mapping[*lane as usize];
+			}
+			row.sort_unstable();
+			row.dedup();
+		}
+	}
+
+	/// Flips the pattern horizontally.
+	///
+	/// Panics if a lane is out of range for the keymode; use [`Self::validate`] first
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let mut pattern = Pattern { rows: vec![vec![0, 1], vec![3]] };
+	/// pattern.mirror(4);
+	/// assert_eq!(pattern.rows, vec![vec![2, 3], vec![0]]);
+	/// ```
+	pub fn mirror(&mut self, keymode: u32) {
+		let mapping: Vec<u32> = (0..keymode).rev().collect();
+		self.remap_lanes(&mapping);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_generate() {
+		let generate = |kind, keymode| Pattern::generate(kind, keymode, 4).rows;
+
+		assert_eq!(
+			generate(DrillKind::Trill, 4),
+			vec![vec![1], vec![2], vec![1], vec![2]]
+		);
+		assert_eq!(
+			generate(DrillKind::Trill, 7),
+			vec![vec![3], vec![4], vec![3], vec![4]]
+		);
+		assert_eq!(
+			generate(DrillKind::Trill, 1),
+			vec![vec![0], vec![0], vec![0], vec![0]]
+		);
+		assert_eq!(
+			generate(DrillKind::Jumptrill, 5)[..2],
+			[vec![0, 1], vec![3, 4]]
+		);
+		assert_eq!(generate(DrillKind::Jumptrill, 1)[..2], [vec![0], vec![0]]);
+		assert_eq!(
+			generate(DrillKind::Jack, 6),
+			vec![vec![0], vec![0], vec![0], vec![0]]
+		);
+		assert_eq!(generate(DrillKind::Quads, 4)[3], vec![0, 1, 2, 3]);
+		assert_eq!(generate(DrillKind::Roll, 3)[3], vec![0]);
+		assert!(generate(DrillKind::Quads, 0).is_empty());
+		assert!(Pattern::generate(DrillKind::Roll, 4, 0).rows.is_empty());
+	}
+
+	#[test]
+	fn test_validate_and_normalize() {
+		let mut pattern = Pattern {
+			rows: vec![vec![2, 0], vec![], vec![1, 1]],
+		};
+		assert_eq!(
+			pattern.validate(4),
+			Err(PatternError::DuplicateLane { row: 2, lane: 1 })
+		);
+		assert_eq!(
+			pattern.validate(2),
+			Err(PatternError::LaneOutOfRange {
+				row: 0,
+				lane: 2,
+				keymode: 2
+			})
+		);
+
+		pattern.mirror(3);
+		assert_eq!(pattern.rows, vec![vec![0, 2], vec![], vec![1]]);
+		assert!(pattern.validate(3).is_ok());
+
+		for kind in [DrillKind::Trill, DrillKind::Jumptrill, DrillKind::Roll] {
+			for keymode in 1..10 {
+				let pattern = Pattern::generate(kind, keymode, 10);
+				assert!(pattern.validate(keymode).is_ok());
+			}
+		}
+	}
+}
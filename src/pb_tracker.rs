@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+/// A player's best results on a single chart and rate. See [`PbTracker`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PersonalBests {
+	pub wifescore: crate::Wifescore,
+	/// Highest overall SSR
+	pub ssr: f32,
+}
+
+/// What kind of personal best was broken
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PbKind {
+	Wifescore,
+	Ssr,
+}
+
+/// Emitted by [`PbTracker::insert`] when a personal best was broken
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PbEvent {
+	pub chartkey: crate::Chartkey,
+	pub rate: crate::Rate,
+	pub kind: PbKind,
+	/// The personal bests before the new score, or None if this was the first score on this chart
+	/// and rate
+	pub previous: Option<PersonalBests>,
+	/// The personal bests after the new score
+	pub new: PersonalBests,
+}
+
+/// Keeps track of a player's personal bests (PBs) per chart and rate, by wifescore and by overall
+/// SSR.
+///
+/// Feed the player's scores in chronological order using [`Self::insert`], which reports when a
+/// PB was broken.
+///
+/// ```rust
+/// # use etterna::*;
+/// let chartkey = Chartkey::new("X0123456789abcdef0123456789abcdef01234567".into()).unwrap();
+/// let rate = Rate::from_f32(1.0).unwrap();
+/// let wifescore = |percent| Wifescore::from_percent(percent).unwrap();
+///
+/// let mut tracker = PbTracker::new();
+/// assert_eq!(tracker.insert(chartkey.clone(), rate, wifescore(93.0), 25.0).len(), 2);
+/// assert_eq!(tracker.insert(chartkey.clone(), rate, wifescore(92.0), 24.5).len(), 0);
+///
+/// let events = tracker.insert(chartkey.clone(), rate, wifescore(95.0), 24.9);
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].kind, PbKind::Wifescore);
+/// assert_eq!(tracker.get(&chartkey, rate).unwrap().ssr, 25.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PbTracker {
+	pbs: HashMap<(crate::Chartkey, crate::Rate), PersonalBests>,
+}
+
+impl PbTracker {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Registers a new score and returns an event for each personal best that it broke. The first
+	/// score on a chart and rate always breaks both PBs.
+	pub fn insert(
+		&mut self,
+		chartkey: crate::Chartkey,
+		rate: crate::Rate,
+		wifescore: crate::Wifescore,
+		ssr: f32,
+	) -> Vec<PbEvent> {
+		let previous = self.pbs.get(&(chartkey.clone(), rate)).cloned();
+
+		let mut broken_pbs = Vec::new();
+		let mut new = previous.clone().unwrap_or_default();
+		match &previous {
+			Some(previous) => {
+				if wifescore.as_proportion() > previous.wifescore.as_proportion() {
+					new.wifescore = wifescore;
+					broken_pbs.push(PbKind::Wifescore);
+				}
+				if ssr > previous.ssr {
+					new.ssr = ssr;
+					broken_pbs.push(PbKind::Ssr);
+				}
+			}
+			None => {
+				new = PersonalBests { wifescore, ssr };
+				broken_pbs.push(PbKind::Wifescore);
+				broken_pbs.push(PbKind::Ssr);
+			}
+		}
+
+		if !broken_pbs.is_empty() {
+			self.pbs.insert((chartkey.clone(), rate), new.clone());
+		}
+
+		broken_pbs
+			.into_iter()
+			.map(|kind| PbEvent {
+				chartkey: chartkey.clone(),
+				rate,
+				kind,
+				previous: previous.clone(),
+				new: new.clone(),
+			})
+			.collect()
+	}
+
+	/// Like [`Self::insert`], taking the data from a [`crate::Score`]. Scores without an SSR
+	/// count as zero SSR
+	pub fn insert_score<T>(&mut self, score: &crate::Score<T>) -> Vec<PbEvent> {
+		self.insert(
+			score.chartkey.clone(),
+			score.rate,
+			score.wifescore,
+			score.overall_ssr().unwrap_or(0.0),
+		)
+	}
+
+	/// Returns the current personal bests on the given chart and rate, if the player has played it
+	pub fn get(&self, chartkey: &crate::Chartkey, rate: crate::Rate) -> Option<&PersonalBests> {
+		self.pbs.get(&(chartkey.clone(), rate))
+	}
+
+	/// Iterates over the personal bests of all played charts and rates, in no particular order
+	pub fn iter(
+		&self,
+	) -> impl Iterator<Item = (&crate::Chartkey, crate::Rate, &PersonalBests)> + '_ {
+		self.pbs
+			.iter()
+			.map(|((chartkey, rate), pbs)| (chartkey, *rate, pbs))
+	}
+
+	/// Number of distinct chart and rate combinations that the player has played
+	pub fn len(&self) -> usize {
+		self.pbs.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.pbs.is_empty()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_pb_tracker() {
+		let chartkey =
+			crate::Chartkey::new("X0123456789abcdef0123456789abcdef01234567".into()).unwrap();
+		let rate = |r| crate::Rate::from_f32(r).unwrap();
+		let wifescore = |percent| crate::Wifescore::from_percent(percent).unwrap();
+
+		let mut tracker = PbTracker::new();
+		tracker.insert(chartkey.clone(), rate(1.0), wifescore(90.0), 20.0);
+
+		// Different rates are tracked separately
+		let events = tracker.insert(chartkey.clone(), rate(1.1), wifescore(85.0), 21.0);
+		assert_eq!(events.len(), 2);
+		assert_eq!(events[0].previous, None);
+		assert_eq!(tracker.len(), 2);
+
+		let events = tracker.insert(chartkey.clone(), rate(1.0), wifescore(89.0), 20.5);
+		assert_eq!(
+			events,
+			vec![PbEvent {
+				chartkey: chartkey.clone(),
+				rate: rate(1.0),
+				kind: PbKind::Ssr,
+				previous: Some(PersonalBests {
+					wifescore: wifescore(90.0),
+					ssr: 20.0,
+				}),
+				new: PersonalBests {
+					wifescore: wifescore(90.0),
+					ssr: 20.5,
+				},
+			}],
+		);
+
+		// Equal results don't count as a new PB
+		assert!(tracker
+			.insert(chartkey.clone(), rate(1.0), wifescore(90.0), 20.5)
+			.is_empty());
+	}
+}
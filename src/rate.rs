@@ -13,25 +13,72 @@ pub struct Rate {
 }
 
 impl Rate {
-	/// Rounds to the nearest valid rate.
+	/// How far a value passed to [`Self::try_from_f32_exact`] may be from a multiple of 0.05, to
+	/// account for floating point inaccuracy
+	pub const EXACT_TOLERANCE: f32 = 0.0001;
+
+	/// Rounds to the nearest valid rate. Same as [`Self::round_to_nearest`]
 	///
-	/// Returns None if the given value is negative or too large
+	/// Returns None if the given value is negative, NaN or too large
 	pub fn from_f32(r: f32) -> Option<Self> {
-		// Some(Self { x20: (r * 20.0).round().try_into().ok()? })
-		if r < 0.0 || r > u32::MAX as f32 {
+		Self::round_to_nearest(r)
+	}
+
+	/// Rounds to the nearest multiple of 0.05, e.g. 1.07 becomes 1.05 and 1.08 becomes 1.10. Values
+	/// exactly in between round away from zero.
+	///
+	/// The rounding is silent, which can hide data entry errors. When the input is supposed to be
+	/// a valid rate already, e.g. when importing scores, prefer [`Self::try_from_f32_exact`].
+	///
+	/// Returns None if the given value is negative, NaN or too large
+	///
+	/// ```rust
+	/// # use etterna::Rate;
+	/// assert_eq!(Rate::round_to_nearest(1.07), Rate::from_string("1.05"));
+	/// assert_eq!(Rate::round_to_nearest(1.08), Rate::from_string("1.10"));
+	/// assert_eq!(Rate::round_to_nearest(-1.0), None);
+	/// ```
+	pub fn round_to_nearest(r: f32) -> Option<Self> {
+		let x20 = (r * 20.0).round();
+		// negated comparison to catch NaN
+		if !(x20 >= 0.0 && x20 <= u32::MAX as f32) {
 			None
 		} else {
-			Some(Self {
-				x20: (r * 20.0).round() as u32,
-			})
+			Some(Self { x20: x20 as u32 })
 		}
 	}
 
-	/// Parses a string into a rate. The string needs to be in the format `\d+\.\d+[05]?`
+	/// Like [`Self::round_to_nearest`], but returns None instead of rounding if the value is not
+	/// within [`Self::EXACT_TOLERANCE`] of a multiple of 0.05
+	///
+	/// ```rust
+	/// # use etterna::Rate;
+	/// assert_eq!(Rate::try_from_f32_exact(1.15), Rate::from_string("1.15"));
+	/// assert_eq!(Rate::try_from_f32_exact(1.1500001), Rate::from_string("1.15"));
+	/// assert_eq!(Rate::try_from_f32_exact(1.17), None);
+	/// ```
+	pub fn try_from_f32_exact(r: f32) -> Option<Self> {
+		let rate = Self::round_to_nearest(r)?;
+		if (rate.as_f32() - r).abs() > Self::EXACT_TOLERANCE {
+			return None;
+		}
+		Some(rate)
+	}
+
+	/// Parses a string into a rate. The string needs to be in the format `\d+\.\d+[05]?`,
+	/// optionally followed by an `x` like in the [`Display`](std::fmt::Display) output
 	///
 	/// Returns None if parsing failed
+	///
+	/// ```rust
+	/// # use etterna::Rate;
+	/// assert_eq!(Rate::from_string("1.45x"), Rate::from_string("1.45"));
+	/// ```
 	// TODO: Rework this to not rely on float parsing but parse the digits directly
 	pub fn from_string(string: &str) -> Option<Self> {
+		let string = string
+			.strip_suffix(|c| c == 'x' || c == 'X')
+			.unwrap_or(string);
 		// not the most efficient but /shrug
 		Self::from_f32(string.parse().ok()?)
 	}
@@ -64,11 +111,41 @@ impl Rate {
 	pub fn as_x20(self) -> u32 {
 		self.x20
 	}
+
+	/// Converts a duration or position in chart time (as if played at 1.00x) into real time at
+	/// this rate. Faster rates make things shorter.
+	///
+	/// ```rust
+	/// # use etterna::Rate;
+	/// assert_eq!(Rate::from_f32(2.0).unwrap().apply(10.0), 5.0);
+	/// ```
+	pub fn apply(self, chart_seconds: f32) -> f32 {
+		chart_seconds / self.as_f32()
+	}
+
+	/// Inverse of [`Self::apply`]: converts real time at this rate back into chart time
+	///
+	/// ```rust
+	/// # use etterna::Rate;
+	/// assert_eq!(Rate::from_f32(2.0).unwrap().unapply(5.0), 10.0);
+	/// ```
+	pub fn unapply(self, real_seconds: f32) -> f32 {
+		real_seconds * self.as_f32()
+	}
+}
+
+/// Converts a list of chart seconds to real seconds in-place. See [`Rate::apply`]
+pub fn apply_rate(seconds: &mut [f32], rate: Rate) {
+	for second in seconds {
+		*second = rate.apply(*second);
+	}
 }
 
 impl std::fmt::Display for Rate {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "{}.{:02}x", (self.x20 * 5) / 100, (self.x20 * 5) % 100)
+		// widen to avoid overflow on huge rates
+		let x100 = self.x20 as u64 * 5;
+		write!(f, "{}.{:02}x", x100 / 100, x100 % 100)
 	}
 }
 
@@ -84,12 +161,6 @@ impl Default for Rate {
 	}
 }
 
-impl From<f32> for Rate {
-	fn from(value: f32) -> Self {
-		Self::from_f32(value).expect("Invalid rate string")
-	}
-}
-
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
 pub struct RateParseError;
 impl std::fmt::Display for RateParseError {
@@ -107,6 +178,32 @@ impl std::str::FromStr for Rate {
 	}
 }
 
+/// Rounds to the nearest valid rate, like [`Rate::from_f32`]. Fails if the value is negative, NaN
+/// or too large
+///
+/// ```rust
+/// # use etterna::Rate;
+/// # use std::convert::TryFrom;
+/// assert_eq!(Rate::try_from(1.07), Ok(Rate::from_x20(21)));
+/// assert!(Rate::try_from(-1.0).is_err());
+/// assert!(Rate::try_from(f32::NAN).is_err());
+/// ```
+impl std::convert::TryFrom<f32> for Rate {
+	type Error = RateParseError;
+
+	fn try_from(value: f32) -> Result<Self, Self::Error> {
+		Self::from_f32(value).ok_or(RateParseError)
+	}
+}
+
+impl std::convert::TryFrom<&str> for Rate {
+	type Error = RateParseError;
+
+	fn try_from(s: &str) -> Result<Self, Self::Error> {
+		s.parse()
+	}
+}
+
 impl std::ops::Add for Rate {
 	type Output = Self;
 
@@ -134,3 +231,44 @@ impl std::ops::SubAssign for Rate {
 		self.x20 -= other.x20;
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
+
+	#[test]
+	fn test_invalid_rates() {
+		assert_eq!(Rate::from_f32(f32::NAN), None);
+		assert_eq!(Rate::from_f32(-0.05), None);
+		assert_eq!(Rate::from_f32(f32::INFINITY), None);
+		assert_eq!(Rate::from_string("NaN"), None);
+		assert_eq!(Rate::from_string(""), None);
+		assert_eq!(Rate::try_from_f32_exact(f32::NAN), None);
+		assert_eq!(Rate::try_from_f32_exact(-0.05), None);
+		assert_eq!(Rate::try_from_f32_exact(1.00005), Some(Rate::from_x20(20)));
+		assert_eq!(Rate::try_from_f32_exact(1.01), None);
+		assert_eq!(Rate::try_from_f32_exact(0.96), None);
+		assert_eq!(Rate::from_x20(u32::MAX).to_string(), "214748364.75x");
+	}
+
+	proptest! {
+		#[test]
+		fn test_rate_string_roundtrip(x20 in 0u32..2_000_000) {
+			let rate = Rate::from_x20(x20);
+			prop_assert_eq!(Rate::from_string(&rate.to_string()), Some(rate));
+		}
+
+		#[test]
+		fn test_rate_from_string_doesnt_panic(string in "\\PC*") {
+			let _ = Rate::from_string(&string);
+		}
+
+		#[test]
+		fn test_rate_from_f32_doesnt_panic(value in any::<f32>()) {
+			if let Some(rate) = Rate::from_f32(value) {
+				let _ = rate.to_string();
+			}
+		}
+	}
+}
@@ -1,6 +1,9 @@
 //! https://discord.com/channels/339597420239519755/389194939881488385/735175202006237344
 //! The following implementations are bit-accurate to the Etterna game code as of 2020-07-21
 
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator;
+
 fn is_rating_okay(rating: f32, ssrs: &[f32], delta_multiplier: f32) -> bool {
 	// Notice the somewhat peculiar usage of f32 and f64 in here. That's to mirror the C++
 	// implementation as closely as possible - we thrive for bit-accuracy after all
@@ -84,6 +87,111 @@ pub fn calculate_player_overall(skillsets: &[f32; 7]) -> f32 {
 	calc_rating(skillsets, 1.125, 0.1)
 }
 
+/// Calculate what a player's rating would be if they had additional scores with the given SSRs.
+///
+/// `calc` is the rating function to use, for example [`calculate_player_skillset_rating`] or
+/// [`calculate_player_skillset_rating_pre_070`].
+///
+/// ```rust
+/// # use etterna::*;
+/// let ssrs = [20.0, 21.0, 22.0];
+/// let rating = calculate_player_skillset_rating(&ssrs);
+/// let new_rating = simulate_rating_with(&ssrs, &[30.0], calculate_player_skillset_rating);
+/// assert!(new_rating > rating);
+/// ```
+pub fn simulate_rating_with(ssrs: &[f32], extra: &[f32], calc: impl Fn(&[f32]) -> f32) -> f32 {
+	let mut ssrs = ssrs.to_vec();
+	ssrs.extend_from_slice(extra);
+	calc(&ssrs)
+}
+
+/// Calculate what a player's rating would be without the scores at the given indices in `ssrs`.
+/// Useful to quantify how much a suspected invalid score inflates a player's rating.
+///
+/// Out-of-range indices are ignored. See [`simulate_rating_with`] for the meaning of `calc`.
+///
+/// ```rust
+/// # use etterna::*;
+/// let ssrs = [20.0, 21.0, 35.0];
+/// let rating = calculate_player_skillset_rating(&ssrs);
+/// let new_rating = simulate_rating_without(&ssrs, &[2], calculate_player_skillset_rating);
+/// assert!(new_rating < rating);
+/// // same as never having played the 35 score
+/// assert_eq!(new_rating, calculate_player_skillset_rating(&[20.0, 21.0]));
+/// ```
+pub fn simulate_rating_without(
+	ssrs: &[f32],
+	removed_indices: &[usize],
+	calc: impl Fn(&[f32]) -> f32,
+) -> f32 {
+	let mut is_removed = vec![false; ssrs.len()];
+	for &i in removed_indices {
+		if let Some(is_removed) = is_removed.get_mut(i) {
+			*is_removed = true;
+		}
+	}
+
+	let ssrs: Vec<f32> = ssrs
+		.iter()
+		.zip(is_removed)
+		.filter(|&(_, is_removed)| !is_removed)
+		.map(|(&ssr, _)| ssr)
+		.collect();
+	calc(&ssrs)
+}
+
+/// Calculates the ratings of many players at once, from each player's list of score SSRs. Uses
+/// the 0.70+ algorithm, like [`crate::Skillsets7::calc_player_rating`].
+///
+/// The SSR buffers are reused across players, and players are processed in parallel if the
+/// `parallel` feature is enabled. Prefer this over calling the single-player functions in a loop
+/// when computing leaderboards or similar.
+///
+/// ```rust
+/// # use etterna::*;
+/// let scores = vec![Skillsets7::generate(|_| 20.0), Skillsets7::generate(|_| 25.0)];
+/// let ratings = calculate_many_ratings(&[&scores[..1], &scores[..]]);
+/// assert!(ratings[1].overall > ratings[0].overall);
+/// ```
+pub fn calculate_many_ratings<P>(players: &[P]) -> Vec<crate::Skillsets8>
+where
+	P: AsRef<[crate::Skillsets7]> + Sync,
+{
+	let calculate = |ssrs_per_skillset: &mut [Vec<f32>; 7], scores: &P| {
+		for (ss, ssrs) in crate::Skillset7::list()
+			.iter()
+			.zip(ssrs_per_skillset.iter_mut())
+		{
+			ssrs.clear();
+			ssrs.extend(scores.as_ref().iter().map(|score| score.get(*ss)));
+		}
+		let ssrs_per_skillset: [&[f32]; 7] = [
+			&ssrs_per_skillset[0],
+			&ssrs_per_skillset[1],
+			&ssrs_per_skillset[2],
+			&ssrs_per_skillset[3],
+			&ssrs_per_skillset[4],
+			&ssrs_per_skillset[5],
+			&ssrs_per_skillset[6],
+		];
+		crate::Skillsets7::calc_player_rating(&ssrs_per_skillset)
+	};
+
+	#[cfg(feature = "parallel")]
+	let ratings = crate::par_iter_maybe(players)
+		.map_init(<[Vec<f32>; 7]>::default, calculate)
+		.collect();
+	#[cfg(not(feature = "parallel"))]
+	let ratings = {
+		let mut ssrs_per_skillset = Default::default();
+		players
+			.iter()
+			.map(|scores| calculate(&mut ssrs_per_skillset, scores))
+			.collect()
+	};
+	ratings
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -141,4 +249,23 @@ mod tests {
 			assert_eq!(calculate_player_overall(&numbers), p_overall);
 		}
 	}
+
+	#[test]
+	#[allow(clippy::float_cmp)]
+	fn test_simulate_rating() {
+		let ssrs = [21.0, 24.0, 23.0, 14.0, 17.0, 25.0, 24.0];
+
+		assert_eq!(
+			simulate_rating_with(&ssrs[..5], &ssrs[5..], calculate_player_skillset_rating),
+			calculate_player_skillset_rating(&ssrs),
+		);
+		assert_eq!(
+			simulate_rating_without(
+				&ssrs,
+				&[1, 5, 100],
+				calculate_player_skillset_rating_pre_070
+			),
+			calculate_player_skillset_rating_pre_070(&[21.0, 23.0, 14.0, 17.0, 24.0]),
+		);
+	}
 }
@@ -0,0 +1,168 @@
+use thiserror::Error;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Error)]
+#[error("Need at least two sorted ratings without NaN")]
+pub struct InvalidRatingTableError;
+
+/// Distribution of player ratings, for mapping a rating to "top X%" of the player base.
+///
+/// The table stores the rating at evenly spaced quantiles and interpolates linearly between them,
+/// so it stays small regardless of the number of players. Build it from a dataset of ratings with
+/// [`Self::from_ratings`]; with the `serde` feature, the table can be serialized to ship a snapshot
+/// of the population without the underlying data.
+///
+/// ```rust
+/// # use etterna::*;
+/// // Ratings of 1001 players, evenly spread from 10 to 30
+/// let ratings = (0..=1000).map(|i| 10.0 + i as f32 / 50.0);
+/// let table = RatingPercentileTable::from_ratings(ratings, 100).unwrap();
+///
+/// assert!((table.percentile_of(25.0) - 75.0).abs() < 0.01);
+/// assert!((table.top_percent(25.0) - 25.0).abs() < 0.01);
+/// assert_eq!(table.percentile_of(5.0), 0.0);
+/// assert_eq!(table.top_percent(40.0), 0.0);
+/// assert!((table.rating_at_percentile(50.0) - 20.0).abs() < 0.01);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<f32>", into = "Vec<f32>"))]
+pub struct RatingPercentileTable {
+	/// Ratings at evenly spaced quantiles from the lowest to the highest rating, sorted
+	ratings: Vec<f32>,
+}
+
+impl RatingPercentileTable {
+	/// Builds a table with `resolution + 1` quantile points from the given ratings. NaN ratings are
+	/// skipped.
+	///
+	/// Returns None if there are no ratings or the resolution is zero
+	pub fn from_ratings(ratings: impl IntoIterator<Item = f32>, resolution: usize) -> Option<Self> {
+		let mut sorted: Vec<f32> = ratings.into_iter().filter(|r| !r.is_nan()).collect();
+		if sorted.is_empty() || resolution == 0 {
+			return None;
+		}
+		// UNWRAP: we filtered out NaNs
+		sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+		let max_index = (sorted.len() - 1) as f64;
+		let ratings = (0..=resolution)
+			.map(|i| {
+				let position = i as f64 / resolution as f64 * max_index;
+				let lower = position.floor() as usize;
+				let upper = position.ceil() as usize;
+				let t = (position - lower as f64) as f32;
+				sorted[lower] + (sorted[upper] - sorted[lower]) * t
+			})
+			.collect();
+		Some(Self { ratings })
+	}
+
+	/// Creates a table from precomputed ratings at evenly spaced quantiles, from the lowest to the
+	/// highest rating.
+	///
+	/// Fails if there are fewer than two ratings, or if they aren't sorted or contain NaN
+	pub fn from_quantile_ratings(ratings: Vec<f32>) -> Result<Self, InvalidRatingTableError> {
+		if ratings.len() < 2 || ratings.iter().any(|r| r.is_nan()) {
+			return Err(InvalidRatingTableError);
+		}
+		if !crate::util::is_sorted(&ratings) {
+			return Err(InvalidRatingTableError);
+		}
+		Ok(Self { ratings })
+	}
+
+	/// The ratings at the table's quantile points, sorted
+	pub fn quantile_ratings(&self) -> &[f32] {
+		&self.ratings
+	}
+
+	/// Percentage of players whose rating is lower than or equal to the given rating, from 0 to 100
+	pub fn percentile_of(&self, rating: f32) -> f32 {
+		let (first, last) = (self.ratings[0], self.ratings[self.ratings.len() - 1]);
+		if rating.is_nan() || rating < first {
+			return 0.0;
+		}
+		if rating >= last {
+			return 100.0;
+		}
+
+		// first rating above the given one. Can't be zero or out of bounds due to the checks above
+		let upper = self.ratings.partition_point(|&r| r <= rating);
+		let (lower_rating, upper_rating) = (self.ratings[upper - 1], self.ratings[upper]);
+		let t = (rating - lower_rating) / (upper_rating - lower_rating);
+		((upper - 1) as f32 + t) / (self.ratings.len() - 1) as f32 * 100.0
+	}
+
+	/// Which top percentage of players the given rating is in, e.g. 5 for "top 5%"
+	pub fn top_percent(&self, rating: f32) -> f32 {
+		100.0 - self.percentile_of(rating)
+	}
+
+	/// The inverse of [`Self::percentile_of`]: the rating needed to reach the given percentile. The
+	/// percentile is clamped to 0..=100
+	pub fn rating_at_percentile(&self, percentile: f32) -> f32 {
+		let max_index = self.ratings.len() - 1;
+		let position = percentile.clamp(0.0, 100.0) / 100.0 * max_index as f32;
+		let lower = (position.floor() as usize).min(max_index);
+		let upper = (position.ceil() as usize).min(max_index);
+		let t = position - lower as f32;
+		self.ratings[lower] + (self.ratings[upper] - self.ratings[lower]) * t
+	}
+}
+
+/// Serialized as the plain list of [`RatingPercentileTable::quantile_ratings`]
+impl std::convert::TryFrom<Vec<f32>> for RatingPercentileTable {
+	type Error = InvalidRatingTableError;
+
+	fn try_from(ratings: Vec<f32>) -> Result<Self, Self::Error> {
+		Self::from_quantile_ratings(ratings)
+	}
+}
+
+impl From<RatingPercentileTable> for Vec<f32> {
+	fn from(table: RatingPercentileTable) -> Self {
+		table.ratings
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_rating_percentile_table() {
+		assert_eq!(RatingPercentileTable::from_ratings(vec![], 10), None);
+		assert_eq!(RatingPercentileTable::from_ratings(vec![1.0], 0), None);
+		assert_eq!(
+			RatingPercentileTable::from_quantile_ratings(vec![2.0, 1.0]),
+			Err(InvalidRatingTableError)
+		);
+
+		let table =
+			RatingPercentileTable::from_ratings(vec![30.0, 10.0, f32::NAN, 20.0], 4).unwrap();
+		assert_eq!(table.quantile_ratings(), &[10.0, 15.0, 20.0, 25.0, 30.0]);
+		assert_eq!(table.percentile_of(10.0), 0.0);
+		assert_eq!(table.percentile_of(17.5), 37.5);
+		assert_eq!(table.rating_at_percentile(37.5), 17.5);
+		assert_eq!(table.rating_at_percentile(200.0), 30.0);
+
+		// for a plateau of equal ratings, players with that rating are counted as below
+		let table =
+			RatingPercentileTable::from_quantile_ratings(vec![10.0, 20.0, 20.0, 30.0]).unwrap();
+		assert!((table.percentile_of(20.0) - 200.0 / 3.0).abs() < 0.001);
+		assert!((table.percentile_of(19.0) - 30.0).abs() < 0.001);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn test_serde() {
+		let table = RatingPercentileTable::from_quantile_ratings(vec![10.0, 20.0, 30.0]).unwrap();
+		let json = serde_json::to_string(&table).unwrap();
+		assert_eq!(json, "[10.0,20.0,30.0]");
+		assert!(serde_json::from_str::<RatingPercentileTable>("[]").is_err());
+		assert_eq!(
+			serde_json::from_str::<RatingPercentileTable>(&json).unwrap(),
+			table
+		);
+	}
+}
@@ -0,0 +1,270 @@
+//! Text and SVG lane diagrams of a replay, for quick visual snippets without a GUI toolkit. See
+//! [`render_ascii`] and [`render_svg`].
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Options for [`render_ascii`] and [`render_svg`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+	/// Section of the chart to render, in ticks (48 per beat)
+	pub ticks: std::ops::Range<u32>,
+	/// Whether [`render_ascii`] colors the judgements with 24-bit ANSI escape codes
+	pub ansi_colors: bool,
+	/// Width of a lane in [`render_svg`], in pixels
+	pub lane_width: u32,
+	/// Vertical distance between two beats in [`render_svg`], in pixels
+	pub pixels_per_beat: u32,
+}
+
+impl Default for RenderOptions {
+	fn default() -> Self {
+		Self {
+			ticks: 0..u32::MAX,
+			ansi_colors: false,
+			lane_width: 32,
+			pixels_per_beat: 64,
+		}
+	}
+}
+
+/// Judgement of every tap in the replay within the given ticks, by `(tick, column)`
+fn judgements(
+	replay: &crate::ReplayV2Fast,
+	judge: &crate::Judge,
+	ticks: &std::ops::Range<u32>,
+) -> HashMap<(u32, u8), crate::TapJudgement> {
+	let mut judgements = HashMap::new();
+	for ((&tick, &deviation), &column) in replay
+		.ticks
+		.iter()
+		.zip(&replay.deviations)
+		.zip(&replay.columns)
+	{
+		if ticks.contains(&tick) {
+			let judgement = if deviation >= crate::replay::MISS_DEVIATION {
+				crate::TapJudgement::Miss
+			} else {
+				judge.classify(deviation)
+			};
+			judgements.insert((tick, column), judgement);
+		}
+	}
+	judgements
+}
+
+/// Character of a judged tap in [`render_ascii`]: `1` to `5` for W1 to W5, and `x` for a miss
+fn judgement_char(judgement: crate::TapJudgement) -> char {
+	match judgement {
+		crate::TapJudgement::Marvelous => '1',
+		crate::TapJudgement::Perfect => '2',
+		crate::TapJudgement::Great => '3',
+		crate::TapJudgement::Good => '4',
+		crate::TapJudgement::Bad => '5',
+		crate::TapJudgement::Miss => 'x',
+	}
+}
+
+/// Renders a section of a chart as text, one line per row from top to bottom, with each tap
+/// replaced by the judgement it got in the replay: `1` to `5` for W1 to W5 and `x` for misses.
+/// Taps that the replay doesn't contain are `o`, mines are `^`, and hold tails are `-`.
+///
+/// Each line starts with the tick of the row.
+///
+/// ```rust
+/// # use etterna::*;
+/// let mut note_data = NoteData::new(4);
+/// note_data.add_note(0, 0, NoteType::Tap);
+/// note_data.add_note(12, 1, NoteType::Tap);
+/// note_data.add_note(12, 3, NoteType::Mine);
+/// note_data.add_note(24, 2, NoteType::Tap);
+///
+/// let replay = ReplayV2Fast {
+/// 	ticks: vec![0, 12],
+/// 	deviations: vec![0.01, 1.0],
+/// 	columns: vec![0, 1],
+/// 	..Default::default()
+/// };
+///
+/// let text = render_ascii(&note_data, &replay, J4, &RenderOptions::default());
+/// assert_eq!(text, "     0 |1...|\n    12 |.x.^|\n    24 |..o.|\n");
+/// ```
+pub fn render_ascii(
+	note_data: &crate::NoteData,
+	replay: &crate::ReplayV2Fast,
+	judge: &crate::Judge,
+	options: &RenderOptions,
+) -> String {
+	let judgements = judgements(replay, judge, &options.ticks);
+
+	let mut text = String::new();
+	for row in note_data.rows() {
+		if !options.ticks.contains(&row.tick) {
+			continue;
+		}
+
+		// UNWRAP: writing to a String can't fail
+		write!(text, "{:>6} |", row.tick).unwrap();
+		for column in 0..note_data.keycount() {
+			let lane = column as u32;
+			if row.taps.tap_at(lane) {
+				match judgements.get(&(row.tick, column)) {
+					Some(&judgement) if options.ansi_colors => {
+						let (r, g, b) = judgement.color();
+						let c = judgement_char(judgement);
+						write!(text, "\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, c).unwrap();
+					}
+					Some(&judgement) => text.push(judgement_char(judgement)),
+					None => text.push('o'),
+				}
+			} else if row.mines.tap_at(lane) {
+				text.push('^');
+			} else if row.hold_tails.tap_at(lane) {
+				text.push('-');
+			} else {
+				text.push('.');
+			}
+		}
+		text.push_str("|\n");
+	}
+	text
+}
+
+/// Renders a section of a chart as an SVG lane diagram, scrolling downwards. Taps are colored by
+/// the judgement they got in the replay ([`crate::TapJudgement::color`]); taps that the replay
+/// doesn't contain are white, and mines are small gray squares. Hold bodies aren't drawn.
+///
+/// ```rust
+/// # use etterna::*;
+/// let mut note_data = NoteData::new(4);
+/// note_data.add_note(0, 2, NoteType::Tap);
+///
+/// let replay = ReplayV2Fast {
+/// 	ticks: vec![0],
+/// 	deviations: vec![0.01],
+/// 	columns: vec![2],
+/// 	..Default::default()
+/// };
+///
+/// let svg = render_svg(&note_data, &replay, J4, &RenderOptions::default());
+/// assert!(svg.starts_with("<svg"));
+/// assert!(svg.contains("fill=\"#99CCFF\""));
+/// ```
+pub fn render_svg(
+	note_data: &crate::NoteData,
+	replay: &crate::ReplayV2Fast,
+	judge: &crate::Judge,
+	options: &RenderOptions,
+) -> String {
+	let judgements = judgements(replay, judge, &options.ticks);
+	let rows: Vec<&crate::ChartRow> = note_data
+		.rows()
+		.iter()
+		.filter(|row| options.ticks.contains(&row.tick))
+		.collect();
+
+	let lane_width = options.lane_width as f32;
+	let first_tick = rows.first().map_or(0, |row| row.tick);
+	let y_of = |tick: u32| {
+		(tick - first_tick) as f32 / 48.0 * options.pixels_per_beat as f32 + lane_width / 2.0
+	};
+	let width = lane_width * note_data.keycount() as f32;
+	let height = rows.last().map_or(0.0, |row| y_of(row.tick)) + lane_width / 2.0;
+
+	let mut svg = String::new();
+	// UNWRAP: writing to a String can't fail
+	writeln!(
+		svg,
+		r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+		width, height, width, height
+	)
+	.unwrap();
+	writeln!(
+		svg,
+		r##"<rect width="{}" height="{}" fill="#202020"/>"##,
+		width, height
+	)
+	.unwrap();
+
+	let note_size = lane_width * 0.8;
+	let mine_size = lane_width * 0.4;
+	for row in rows {
+		let y = y_of(row.tick);
+		for column in 0..note_data.keycount() {
+			let lane = column as u32;
+			let x = (column as f32 + 0.5) * lane_width;
+			let (size, (r, g, b)) = if row.taps.tap_at(lane) {
+				let color = judgements
+					.get(&(row.tick, column))
+					.map_or((0xFF, 0xFF, 0xFF), |judgement| judgement.color());
+				(note_size, color)
+			} else if row.mines.tap_at(lane) {
+				(mine_size, (0x80, 0x80, 0x80))
+			} else {
+				continue;
+			};
+			writeln!(
+				svg,
+				r##"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="#{:02X}{:02X}{:02X}"/>"##,
+				x - size / 2.0,
+				y - size / 2.0,
+				size,
+				size,
+				size / 4.0,
+				r,
+				g,
+				b,
+			)
+			.unwrap();
+		}
+	}
+	svg.push_str("</svg>\n");
+	svg
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_render_section() {
+		let mut note_data = crate::NoteData::new(4);
+		for (tick, column) in [(0, 0), (48, 1), (96, 2), (144, 3)].iter().copied() {
+			note_data.add_note(tick, column, crate::NoteType::Tap);
+		}
+		note_data.add_note(96, 0, crate::NoteType::HoldHead);
+		note_data.add_note(144, 0, crate::NoteType::HoldTail);
+		let replay = crate::ReplayV2Fast {
+			ticks: vec![0, 48, 96, 96, 144],
+			deviations: vec![0.2, -0.06, 0.0, 0.1, 0.0],
+			columns: vec![0, 1, 2, 0, 3],
+			..Default::default()
+		};
+		let options = RenderOptions {
+			ticks: 48..144,
+			..Default::default()
+		};
+
+		assert_eq!(
+			render_ascii(&note_data, &replay, crate::J4, &options),
+			"    48 |.3..|\n    96 |4.1.|\n"
+		);
+
+		let colored = render_ascii(
+			&note_data,
+			&replay,
+			crate::J4,
+			&RenderOptions {
+				ansi_colors: true,
+				..options.clone()
+			},
+		);
+		assert!(colored.contains("\x1b[38;2;20;204;143m3\x1b[0m"));
+
+		let svg = render_svg(&note_data, &replay, crate::J4, &options);
+		// one background and three notes
+		assert_eq!(svg.matches("<rect").count(), 4);
+		assert!(svg.contains(r#"height="96""#));
+		assert!(svg.trim_end().ends_with("</svg>"));
+	}
+}
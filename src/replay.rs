@@ -0,0 +1,1214 @@
+use thiserror::Error;
+
+/// Deviations at or above this value represent a missed note in the ReplayV2 format
+pub(crate) const MISS_DEVIATION: f32 = 1.0;
+
+/// A dropped hold in a replay, parsed from the `H` lines in the ReplayV2 format
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayHoldDrop {
+	/// Position of the hold head in ticks
+	pub tick: u32,
+	pub column: u8,
+}
+
+/// Replay data parsed from Etterna's ReplayV2 format. Only includes the information necessary for
+/// rescoring: note hits, hold drops, plus the number of mine hits.
+///
+/// `ticks`, `deviations` and `columns` are parallel vectors, in the order they appear in the replay
+/// file. There are 48 ticks per beat.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayV2Fast {
+	pub ticks: Vec<u32>,
+	/// Hit deviations in seconds. Missed notes have a deviation of 1.0 or above
+	pub deviations: Vec<f32>,
+	pub columns: Vec<u8>,
+	pub num_mine_hits: u32,
+	pub hold_drops: Vec<ReplayHoldDrop>,
+}
+
+/// A replay entry that is neither a tap nor a hold head, e.g. a mine hit
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayNoteEvent {
+	pub tick: u32,
+	pub column: u8,
+	/// Deviation in seconds, as recorded in the replay
+	pub deviation: f32,
+}
+
+/// Like [`ReplayV2Fast`], but additionally retains the positions of mine hits, lifts and fakes.
+/// Parse with [`parse_replay_v2_full`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayV2Full {
+	/// Taps, hold heads, hold drops and the mine hit count, as parsed by [`parse_replay_v2_fast`]
+	pub replay: ReplayV2Fast,
+	pub mine_hits: Vec<ReplayNoteEvent>,
+	pub lifts: Vec<ReplayNoteEvent>,
+	pub fakes: Vec<ReplayNoteEvent>,
+}
+
+/// Reason why a single line of a ReplayV2 file couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ReplayLineErrorKind {
+	#[error("Missing {0} field")]
+	MissingField(&'static str),
+	#[error("Could not parse the tick into an integer")]
+	InvalidTick,
+	#[error("Could not parse the deviation into a float")]
+	InvalidDeviation,
+	#[error("Could not parse the column into an integer")]
+	InvalidColumn,
+	#[error("Could not parse the note type into an integer")]
+	InvalidNoteType,
+}
+
+/// A malformed line in a ReplayV2 file
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Line {line_number}: {kind}")]
+pub struct ReplayLineError {
+	/// 1-based line number
+	pub line_number: usize,
+	pub kind: ReplayLineErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ReplayParseError {
+	#[error("{0}")]
+	InvalidLine(ReplayLineError),
+	#[error("Replay doesn't contain a single note")]
+	NoNotes,
+}
+
+/// Parses the contents of a ReplayV2 file. Malformed lines are skipped.
+///
+/// Returns None if the replay doesn't contain a single note.
+pub fn parse_replay_v2_fast(bytes: &[u8]) -> Option<ReplayV2Fast> {
+	let mut replay = ReplayV2Fast::with_capacity_for(bytes);
+	// the shortest possible line is a hold line like `H 0 0`
+	for line in crate::util::split_newlines(bytes, 5) {
+		if let Ok(line) = parse_line(line) {
+			replay.add_line(line, &mut |_, _| {});
+		}
+	}
+
+	if replay.ticks.len() == 0 {
+		return None;
+	}
+	Some(replay)
+}
+
+/// Parses the contents of a ReplayV2 file, like [`parse_replay_v2_fast`], but additionally keeps
+/// track of where mines were hit and where lifts and fakes were.
+///
+/// Returns None if the replay doesn't contain a single note.
+pub fn parse_replay_v2_full(bytes: &[u8]) -> Option<ReplayV2Full> {
+	let mut replay = ReplayV2Fast::with_capacity_for(bytes);
+	let mut mine_hits = Vec::new();
+	let mut lifts = Vec::new();
+	let mut fakes = Vec::new();
+	for line in crate::util::split_newlines(bytes, 5) {
+		if let Ok(line) = parse_line(line) {
+			replay.add_line(line, &mut |note_type, event| match note_type {
+				4 => mine_hits.push(event),
+				5 => lifts.push(event),
+				8 => fakes.push(event),
+				_ => {}
+			});
+		}
+	}
+
+	if replay.ticks.len() == 0 {
+		return None;
+	}
+	Some(ReplayV2Full {
+		replay,
+		mine_hits,
+		lifts,
+		fakes,
+	})
+}
+
+/// Parses the contents of a ReplayV2 file. Unlike [`parse_replay_v2_fast`], this function fails on
+/// the first malformed line, and reports which line it was and what was wrong with it.
+pub fn parse_replay_v2(bytes: &[u8]) -> Result<ReplayV2Fast, ReplayParseError> {
+	let mut replay = ReplayV2Fast::with_capacity_for(bytes);
+	for (i, line) in bytes.split(|&c| c == b'\n').enumerate() {
+		let line = parse_line(line).map_err(|kind| {
+			ReplayParseError::InvalidLine(ReplayLineError {
+				line_number: i + 1,
+				kind,
+			})
+		})?;
+		replay.add_line(line, &mut |_, _| {});
+	}
+
+	if replay.ticks.len() == 0 {
+		return Err(ReplayParseError::NoNotes);
+	}
+	Ok(replay)
+}
+
+/// Parses the contents of a ReplayV2 file. Like [`parse_replay_v2_fast`], malformed lines are
+/// skipped, but each skipped line is reported in the returned list of warnings.
+pub fn parse_replay_v2_lossy(
+	bytes: &[u8],
+) -> Result<(ReplayV2Fast, Vec<ReplayLineError>), ReplayParseError> {
+	let mut replay = ReplayV2Fast::with_capacity_for(bytes);
+	let mut warnings = Vec::new();
+	for (i, line) in bytes.split(|&c| c == b'\n').enumerate() {
+		match parse_line(line) {
+			Ok(line) => replay.add_line(line, &mut |_, _| {}),
+			Err(kind) => warnings.push(ReplayLineError {
+				line_number: i + 1,
+				kind,
+			}),
+		}
+	}
+
+	if replay.ticks.len() == 0 {
+		return Err(ReplayParseError::NoNotes);
+	}
+	Ok((replay, warnings))
+}
+
+/// Error returned by [`parse_replay_file`]
+#[derive(Debug, Error)]
+pub enum ReplayFileError {
+	#[error("Could not read replay file {}: {source}", path.display())]
+	Io {
+		path: std::path::PathBuf,
+		source: std::io::Error,
+	},
+	#[error("Could not parse replay file {}: {source}", path.display())]
+	Parse {
+		path: std::path::PathBuf,
+		source: ReplayParseError,
+	},
+}
+
+/// Reads and parses a ReplayV2 file, like [`parse_replay_v2_fast`]. Malformed lines are skipped.
+///
+/// Fails if the file couldn't be read or doesn't contain a single note. The error includes the
+/// file path.
+pub fn parse_replay_file(
+	path: impl AsRef<std::path::Path>,
+) -> Result<ReplayV2Fast, ReplayFileError> {
+	let path = path.as_ref();
+	let bytes = std::fs::read(path).map_err(|source| ReplayFileError::Io {
+		path: path.to_owned(),
+		source,
+	})?;
+	parse_replay_bytes_from(path, &bytes)
+}
+
+/// Like [`parse_replay_file`], but memory-maps the file instead of reading it into a buffer. This
+/// avoids a copy, which adds up when ingesting large replay folders.
+///
+/// The file must not be modified while it is being parsed. Etterna writes every replay file only
+/// once, so this is fine for replay folders of the game.
+#[cfg(feature = "mmap")]
+pub fn parse_replay_file_mmap(
+	path: impl AsRef<std::path::Path>,
+) -> Result<ReplayV2Fast, ReplayFileError> {
+	let path = path.as_ref();
+	let io_error = |source| ReplayFileError::Io {
+		path: path.to_owned(),
+		source,
+	};
+
+	let file = std::fs::File::open(path).map_err(io_error)?;
+	// SAFETY: the caller guarantees that the file isn't modified while it's mapped. Apart from
+	// that, the mapping is only read through the byte slice and dropped at the end of this function
+	let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+	parse_replay_bytes_from(path, &mmap)
+}
+
+fn parse_replay_bytes_from(
+	path: &std::path::Path,
+	bytes: &[u8],
+) -> Result<ReplayV2Fast, ReplayFileError> {
+	parse_replay_v2_fast(bytes).ok_or_else(|| ReplayFileError::Parse {
+		path: path.to_owned(),
+		source: ReplayParseError::NoNotes,
+	})
+}
+
+/// Rewrites a ReplayV2 file into a canonical, byte-stable form, e.g. for building public replay
+/// datasets from user submissions.
+///
+/// Only the data that Etterna itself reads is kept. Malformed lines, unknown trailing fields and
+/// blank lines are dropped, so nothing besides the judged inputs can leak through. Lines are ordered
+/// by tick, then column, then note type, with hold drops at the end. Deviations are written with six
+/// decimals like the game does, and lines end with `\n`. Normalizing the output again yields the
+/// same bytes.
+///
+/// Returns None if the replay doesn't contain a single note.
+///
+/// ```rust
+/// # use etterna::*;
+/// let replay = b"48 -0.0200001 1 1 extra\r\nH 96 2\r\n\r\n0 0.01 0\r\ngarbage\r\n";
+/// let normalized = normalize_replay_v2(replay).unwrap();
+/// assert_eq!(normalized, b"0 0.010000 0\n48 -0.020000 1\nH 96 2\n");
+/// assert_eq!(normalize_replay_v2(&normalized).unwrap(), normalized);
+/// ```
+pub fn normalize_replay_v2(bytes: &[u8]) -> Option<Vec<u8>> {
+	use std::io::Write as _;
+
+	let mut notes = Vec::new();
+	let mut hold_drops = Vec::new();
+	for line in bytes.split(|&c| c == b'\n') {
+		match parse_line(line) {
+			Ok(ReplayLine::Note { note_type, event }) => notes.push((note_type, event)),
+			Ok(ReplayLine::HoldDrop(hold_drop)) => hold_drops.push(hold_drop),
+			Ok(ReplayLine::Empty) | Err(_) => {}
+		}
+	}
+	if !notes
+		.iter()
+		.any(|&(note_type, _)| note_type == 1 || note_type == 2)
+	{
+		return None;
+	}
+
+	notes.sort_by_key(|&(note_type, event)| (event.tick, event.column, note_type));
+	hold_drops.sort_by_key(|hold_drop| (hold_drop.tick, hold_drop.column));
+
+	let mut output = Vec::with_capacity(bytes.len());
+	for (note_type, event) in notes {
+		// avoid writing negative zero
+		let deviation = if event.deviation == 0.0 {
+			0.0
+		} else {
+			event.deviation
+		};
+		// writing into a Vec can't fail
+		let _ = write!(output, "{} {:.6} {}", event.tick, deviation, event.column);
+		if note_type != 1 {
+			let _ = write!(output, " {}", note_type);
+		}
+		output.push(b'\n');
+	}
+	for hold_drop in hold_drops {
+		let _ = writeln!(output, "H {} {}", hold_drop.tick, hold_drop.column);
+	}
+	Some(output)
+}
+
+// A single line in a ReplayV2 file
+enum ReplayLine {
+	Empty,
+	HoldDrop(ReplayHoldDrop),
+	Note {
+		note_type: u8,
+		event: ReplayNoteEvent,
+	},
+}
+
+fn parse_line(line: &[u8]) -> Result<ReplayLine, ReplayLineErrorKind> {
+	let line = crate::util::trim_bstr(line);
+	if line.len() == 0 {
+		return Ok(ReplayLine::Empty);
+	}
+
+	let mut tokens = line.split(|&c| c == b' ');
+	let mut next_field = |name| tokens.next().ok_or(ReplayLineErrorKind::MissingField(name));
+
+	if line[0] == b'H' {
+		next_field("H")?;
+		let tick = btoi::btou(next_field("tick")?).map_err(|_| ReplayLineErrorKind::InvalidTick)?;
+		let column =
+			btoi::btou(next_field("column")?).map_err(|_| ReplayLineErrorKind::InvalidColumn)?;
+		return Ok(ReplayLine::HoldDrop(ReplayHoldDrop { tick, column }));
+	}
+
+	let tick = btoi::btou(next_field("tick")?).map_err(|_| ReplayLineErrorKind::InvalidTick)?;
+	let deviation = lexical_core::parse_lossy(next_field("deviation")?)
+		.map_err(|_| ReplayLineErrorKind::InvalidDeviation)?;
+	let column =
+		btoi::btou(next_field("column")?).map_err(|_| ReplayLineErrorKind::InvalidColumn)?;
+	// the note type is only written if the note is not a plain tap
+	let note_type = match tokens.next() {
+		Some(token) => btoi::btou(token).map_err(|_| ReplayLineErrorKind::InvalidNoteType)?,
+		None => 1,
+	};
+
+	Ok(ReplayLine::Note {
+		note_type,
+		event: ReplayNoteEvent {
+			tick,
+			column,
+			deviation,
+		},
+	})
+}
+
+impl ReplayV2Fast {
+	fn with_capacity_for(bytes: &[u8]) -> Self {
+		// rough capacity approximation; a typical line looks like `1344 -0.012345 2`
+		let approx_num_notes = bytes.len() / 16;
+		Self {
+			ticks: Vec::with_capacity(approx_num_notes),
+			deviations: Vec::with_capacity(approx_num_notes),
+			columns: Vec::with_capacity(approx_num_notes),
+			num_mine_hits: 0,
+			hold_drops: Vec::new(),
+		}
+	}
+
+	// `on_other_note` is called for every note which is neither a tap nor a hold head, with the
+	// note type as found in the replay file
+	fn add_line(&mut self, line: ReplayLine, on_other_note: &mut impl FnMut(u8, ReplayNoteEvent)) {
+		match line {
+			ReplayLine::Empty => {}
+			ReplayLine::HoldDrop(hold_drop) => self.hold_drops.push(hold_drop),
+			// See TapNoteType in Etterna's NoteTypes.h
+			// tap, hold head
+			ReplayLine::Note {
+				note_type: 1 | 2,
+				event,
+			} => {
+				self.ticks.push(event.tick);
+				self.deviations.push(event.deviation);
+				self.columns.push(event.column);
+			}
+			ReplayLine::Note { note_type, event } => {
+				// mine
+				if note_type == 4 {
+					self.num_mine_hits += 1;
+				}
+				on_other_note(note_type, event);
+			}
+		}
+	}
+}
+
+impl ReplayV2Fast {
+	/// Number of dropped holds in this replay
+	pub fn num_hold_drops(&self) -> u32 {
+		self.hold_drops.len() as u32
+	}
+
+	/// Ticks of the first and the last note in this replay, or None if the replay is empty
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"96 0.01 0\n48 0.02 1\n144 1.0 3\n").unwrap();
+	/// assert_eq!(replay.tick_range(), Some(48..=144));
+	/// assert_eq!(ReplayV2Fast::default().tick_range(), None);
+	/// ```
+	pub fn tick_range(&self) -> Option<std::ops::RangeInclusive<u32>> {
+		let first = *self.ticks.iter().min()?;
+		let last = *self.ticks.iter().max()?;
+		Some(first..=last)
+	}
+
+	/// Length of the chart in real time seconds on the given rate, measured like the game does: from
+	/// the start of the song to the last note. Returns None if the replay is empty
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"48 0.01 0\n240 0.02 1\n").unwrap();
+	/// let timing_info = TimingInfo::from_constant_bpm(120.0);
+	///
+	/// assert_eq!(replay.length_seconds(&timing_info, Rate::from_f32(1.0).unwrap()), Some(2.5));
+	/// assert_eq!(replay.length_seconds(&timing_info, Rate::from_f32(2.0).unwrap()), Some(1.25));
+	/// ```
+	pub fn length_seconds(
+		&self,
+		timing_info: &crate::TimingInfo,
+		rate: crate::Rate,
+	) -> Option<f32> {
+		let last_tick = *self.tick_range()?.end();
+		let last_second = timing_info.ticks_to_seconds(&[last_tick])[0];
+		Some(rate.apply(last_second))
+	}
+
+	/// Number of notes in each column, indexed by column. The length is one more than the highest
+	/// column in the replay, so columns without notes in between are counted as zero
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"0 0.01 0\n48 0.02 3\n96 1.0 0\n").unwrap();
+	/// assert_eq!(replay.notes_per_column(), vec![2, 0, 0, 1]);
+	/// ```
+	pub fn notes_per_column(&self) -> Vec<u32> {
+		let mut counts = Vec::new();
+		for &column in &self.columns {
+			let column = column as usize;
+			if column >= counts.len() {
+				counts.resize(column + 1, 0);
+			}
+			counts[column] += 1;
+		}
+		counts
+	}
+
+	/// Returns a copy of this replay that only contains the notes and hold drops in columns for
+	/// which `keep_column` returns true. The result implements [`crate::SimpleReplay`] like any
+	/// other replay, which makes it easy to analyze a subset of columns, e.g. a single hand.
+	///
+	/// Mine hits don't have a column in this format, so `num_mine_hits` is carried over unchanged
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"0 0.01 0\n48 0.02 1\n96 0.03 3\n").unwrap();
+	/// let left_hand = replay.filter_columns(|column| column < 2);
+	/// assert_eq!(left_hand.iter_deviations().collect::<Vec<_>>(), vec![0.01, 0.02]);
+	/// ```
+	pub fn filter_columns(&self, mut keep_column: impl FnMut(u8) -> bool) -> Self {
+		let mut filtered = Self {
+			num_mine_hits: self.num_mine_hits,
+			hold_drops: self
+				.hold_drops
+				.iter()
+				.filter(|hold_drop| keep_column(hold_drop.column))
+				.copied()
+				.collect(),
+			..Default::default()
+		};
+		for ((&tick, &deviation), &column) in
+			self.ticks.iter().zip(&self.deviations).zip(&self.columns)
+		{
+			if keep_column(column) {
+				filtered.ticks.push(tick);
+				filtered.deviations.push(deviation);
+				filtered.columns.push(column);
+			}
+		}
+		filtered
+	}
+
+	/// Approximates what this replay, played on rate `from`, would look like on rate `to`. Useful
+	/// for "can I pass this on 1.1x" analysis, by rescoring the result.
+	///
+	/// This assumes that the player hits each note at the same position relative to the music,
+	/// so each deviation is converted to chart time and back to real time on the new rate: going
+	/// from 1.0x to 1.1x shrinks every deviation by a factor of 1.1. In reality, players tend to
+	/// get less accurate on higher rates, so the result is an optimistic estimate.
+	///
+	/// Ticks are positions in the chart and don't depend on the rate, so they're left as is; pass
+	/// `to` wherever a rate is needed for the result, e.g. [`Self::hit_seconds`]. Misses stay
+	/// misses, and mine hits and hold drops are carried over unchanged
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"0 0.022 0\n48 -0.011 1\n96 1.0 2\n").unwrap();
+	/// let rate = |rate| Rate::from_f32(rate).unwrap();
+	///
+	/// let converted = replay.convert_rate(rate(1.0), rate(1.1));
+	/// assert_eq!(converted.ticks, replay.ticks);
+	/// assert_eq!(converted.deviations, vec![0.02, -0.01, 1.0]);
+	/// ```
+	pub fn convert_rate(&self, from: crate::Rate, to: crate::Rate) -> Self {
+		let deviations = self
+			.deviations
+			.iter()
+			.map(|&deviation| {
+				if deviation < MISS_DEVIATION {
+					to.apply(from.unapply(deviation))
+				} else {
+					deviation
+				}
+			})
+			.collect();
+
+		Self {
+			deviations,
+			..self.clone()
+		}
+	}
+
+	/// Calculates the chart position in seconds of each note, in the same order as `self.ticks`.
+	/// The returned seconds are at 1.00x; they are not adjusted for rate.
+	pub fn note_seconds(&self, timing_info: &crate::TimingInfo) -> Vec<f32> {
+		// TimingInfo::ticks_to_seconds requires sorted input, so we sort, convert, and sort back
+		let order = match self.tick_order() {
+			Some(order) => order,
+			None => return timing_info.ticks_to_seconds(&self.ticks),
+		};
+		let sorted_ticks: Vec<u32> = order.iter().map(|&i| self.ticks[i as usize]).collect();
+		let sorted_note_seconds = timing_info.ticks_to_seconds(&sorted_ticks);
+
+		let mut note_seconds = vec![0.0; self.ticks.len()];
+		for (&i, &note_second) in order.iter().zip(&sorted_note_seconds) {
+			note_seconds[i as usize] = note_second;
+		}
+		note_seconds
+	}
+
+	/// Returns the note indices sorted by tick, or None if the notes are already sorted by tick,
+	/// which is the case for most replays
+	fn tick_order(&self) -> Option<Vec<u32>> {
+		if crate::util::is_sorted(&self.ticks) {
+			return None;
+		}
+
+		let mut order: Vec<u32> = (0..self.ticks.len() as u32).collect();
+		order.sort_unstable_by_key(|&i| self.ticks[i as usize]);
+		Some(order)
+	}
+
+	/// Calculates the real-time second of every hit in this replay, in chronological order. Note
+	/// positions are scaled by the given rate; deviations are already recorded in real time. Missed
+	/// notes are skipped.
+	///
+	/// Useful to sync a replay to a recording or to analyze play time across a session
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"96 0.02 1\n0 0.01 0\n48 1.0 2\n").unwrap();
+	/// let timing_info = TimingInfo::from_constant_bpm(60.0);
+	///
+	/// let hit_seconds = replay.hit_seconds(&timing_info, Rate::from_f32(2.0).unwrap());
+	/// assert_eq!(hit_seconds, vec![0.01, 1.02]);
+	/// ```
+	pub fn hit_seconds(&self, timing_info: &crate::TimingInfo, rate: crate::Rate) -> Vec<f32> {
+		let mut hit_seconds: Vec<f32> = self
+			.note_seconds(timing_info)
+			.into_iter()
+			.zip(&self.deviations)
+			.filter(|&(_, &deviation)| deviation < MISS_DEVIATION)
+			.map(|(note_second, &deviation)| rate.apply(note_second) + deviation)
+			.collect();
+
+		// UNWRAP: seconds are derived from finite ticks and deviations, so they're never NaN
+		hit_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+		hit_seconds
+	}
+
+	/// Splits the replay into the four 4k lanes, calculating note and hit seconds using the given
+	/// timing info. See [`Self::split_into_lanes_with_keycount`] for other keymodes.
+	///
+	/// Returns None if a note's column is outside the 4k range
+	pub fn split_into_lanes(
+		&self,
+		timing_info: &crate::TimingInfo,
+	) -> Option<crate::Lanes<crate::NoteAndHitSeconds>> {
+		self.split_into_lanes_with_keycount(timing_info, 4)
+	}
+
+	/// Like [`Self::split_into_lanes`], but for a chart with the given number of keys.
+	///
+	/// Returns None if a note's column is outside the range of the keymode
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"0 0.01 0\n48 0.02 6\n").unwrap();
+	/// let timing_info = TimingInfo::from_constant_bpm(120.0);
+	///
+	/// let lanes = replay.split_into_lanes_with_keycount(&timing_info, 7).unwrap();
+	/// assert_eq!(lanes.keycount(), 7);
+	/// assert_eq!(lanes[6].hit_seconds, vec![0.52]);
+	/// assert_eq!(replay.split_into_lanes(&timing_info), None);
+	/// ```
+	pub fn split_into_lanes_with_keycount(
+		&self,
+		timing_info: &crate::TimingInfo,
+		keycount: usize,
+	) -> Option<crate::Lanes<crate::NoteAndHitSeconds>> {
+		let mut lanes = crate::Lanes::new(keycount);
+		self.split_into_lanes_preallocated(timing_info, &mut lanes)?;
+		Some(lanes)
+	}
+
+	/// Like [`Self::split_into_lanes_with_keycount`], but writes into the given lanes instead of
+	/// allocating new ones. The keymode is that of the given lanes. The lanes are cleared first, so
+	/// their allocations can be reused when processing many replays in a row.
+	///
+	/// Returns None if a note's column is outside the range of the keymode. The lanes' contents
+	/// are unspecified in that case
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let timing_info = TimingInfo::from_constant_bpm(120.0);
+	/// let mut lanes = Default::default();
+	/// for replay in [b"0 0.01 0\n48 0.02 1\n", b"48 0.00 3\n0 0.03 3\n"] {
+	/// 	let replay = parse_replay_v2_fast(replay).unwrap();
+	/// 	replay.split_into_lanes_preallocated(&timing_info, &mut lanes).unwrap();
+	/// }
+	/// assert!(lanes[0].note_seconds.is_empty());
+	/// assert_eq!(lanes[3].note_seconds, vec![0.0, 0.5]);
+	/// assert_eq!(lanes[3].hit_seconds, vec![0.03, 0.5]);
+	/// ```
+	pub fn split_into_lanes_preallocated(
+		&self,
+		timing_info: &crate::TimingInfo,
+		lanes: &mut crate::Lanes<crate::NoteAndHitSeconds>,
+	) -> Option<()> {
+		for lane in lanes.iter_lanes_mut() {
+			lane.note_seconds.clear();
+			lane.hit_seconds.clear();
+		}
+
+		// Notes are processed in chronological order, so that note seconds are already sorted
+		let mut push_note = |i: usize, note_second: f32| -> Option<()> {
+			let lane = lanes.get_mut(self.columns[i] as usize)?;
+			lane.note_seconds.push(note_second);
+			let deviation = self.deviations[i];
+			if deviation < MISS_DEVIATION {
+				lane.hit_seconds.push(note_second + deviation);
+			}
+			Some(())
+		};
+		match self.tick_order() {
+			None => {
+				for (i, note_second) in timing_info
+					.ticks_to_seconds(&self.ticks)
+					.into_iter()
+					.enumerate()
+				{
+					push_note(i, note_second)?;
+				}
+			}
+			Some(order) => {
+				let sorted_ticks: Vec<u32> =
+					order.iter().map(|&i| self.ticks[i as usize]).collect();
+				for (&i, note_second) in order
+					.iter()
+					.zip(timing_info.ticks_to_seconds(&sorted_ticks))
+				{
+					push_note(i as usize, note_second)?;
+				}
+			}
+		}
+
+		// Deviations can shuffle hits around, so those still need sorting
+		// UNWRAP: seconds are derived from finite ticks and deviations, so they're never NaN
+		for lane in lanes.iter_mut() {
+			lane.hit_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+		}
+
+		Some(())
+	}
+
+	/// Like [`Self::split_into_lanes`], but for when the chart's timing info is not available. The
+	/// note seconds are approximated by assuming the entire chart is at the given constant BPM.
+	///
+	/// The resulting seconds will be off for charts with BPM changes, but since all seconds are
+	/// shifted consistently within a lane, rescoring still yields sensible results.
+	pub fn split_into_lanes_constant_bpm(
+		&self,
+		bpm: f32,
+	) -> Option<crate::Lanes<crate::NoteAndHitSeconds>> {
+		self.split_into_lanes(&crate::TimingInfo::from_constant_bpm(bpm as f64))
+	}
+
+	/// Like [`Self::split_into_lanes`], but with precomputed note seconds. `note_seconds` must
+	/// be in the same order as `self.ticks`, e.g. as returned by [`Self::note_seconds`].
+	///
+	/// Returns None if a note's column is outside the 4k range, if `note_seconds` doesn't have one
+	/// entry per note, or if it contains NaN
+	pub fn split_into_lanes_with_note_seconds(
+		&self,
+		note_seconds: &[f32],
+	) -> Option<crate::Lanes<crate::NoteAndHitSeconds>> {
+		if note_seconds.len() != self.ticks.len() || note_seconds.iter().any(|s| s.is_nan()) {
+			return None;
+		}
+
+		let mut lanes: crate::Lanes<crate::NoteAndHitSeconds> = Default::default();
+		for ((&note_second, &deviation), &column) in
+			note_seconds.iter().zip(&self.deviations).zip(&self.columns)
+		{
+			let lane = lanes.get_mut(column as usize)?;
+
+			lane.note_seconds.push(note_second);
+			if deviation < MISS_DEVIATION {
+				lane.hit_seconds.push(note_second + deviation);
+			}
+		}
+
+		// UNWRAP: the note seconds were checked above, and deviations are finite
+		for lane in lanes.iter_lanes_mut() {
+			lane.note_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+			lane.hit_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+		}
+
+		Some(lanes)
+	}
+}
+
+/// A single hit in a replay scatter plot. See [`ReplayV2Fast::scatter_plot`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScatterPoint {
+	/// Position of the note in the song, in real time seconds (i.e. adjusted for rate)
+	pub second: f32,
+	pub hit: crate::Hit,
+	pub column: u8,
+	pub judgement: crate::TapJudgement,
+}
+
+impl ScatterPoint {
+	/// Creates a scatter point, with the judgement that the hit receives on the given judge
+	pub fn new(second: f32, column: u8, hit: crate::Hit, judge: &crate::Judge) -> Self {
+		Self {
+			second,
+			hit,
+			column,
+			judgement: hit.classify(judge),
+		}
+	}
+}
+
+impl ReplayV2Fast {
+	/// Generates the data for a replay scatter plot: every note with its position in the song,
+	/// how it was hit, on which column, and which judgement it received on the given judge.
+	///
+	/// The points are sorted chronologically.
+	pub fn scatter_plot(
+		&self,
+		timing_info: &crate::TimingInfo,
+		rate: crate::Rate,
+		judge: &crate::Judge,
+	) -> Vec<ScatterPoint> {
+		use crate::SimpleReplay as _;
+
+		let mut points: Vec<ScatterPoint> = self
+			.note_seconds(timing_info)
+			.into_iter()
+			.zip(self.iter_hits())
+			.zip(&self.columns)
+			.map(|((note_second, hit), &column)| {
+				ScatterPoint::new(rate.apply(note_second), column, hit, judge)
+			})
+			.collect();
+
+		// UNWRAP: seconds are derived from finite ticks, so they're never NaN
+		points.sort_by(|a, b| a.second.partial_cmp(&b.second).unwrap());
+		points
+	}
+}
+
+/// How many wifescore percentage points were lost to what. See [`ReplayV2Fast::wifescore_loss`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WifescoreLoss {
+	/// Percentage points lost on the notes of each judgement
+	pub by_judgement: crate::WifeLossByJudgement,
+	/// Percentage points lost to mine hits
+	pub mine_hits: f32,
+	/// Percentage points lost to hold drops
+	pub hold_drops: f32,
+}
+
+impl WifescoreLoss {
+	/// Total percentage points lost. Subtracting this from 100% yields the wifescore
+	pub fn total(&self) -> f32 {
+		self.by_judgement.total() + self.mine_hits + self.hold_drops
+	}
+}
+
+impl ReplayV2Fast {
+	/// Attributes the wifescore percentage points that were lost in this replay to each judgement,
+	/// to mine hits and to hold drops, using the given scoring system and judge. This shows players
+	/// what to fix first.
+	///
+	/// Returns None if the replay contains no notes
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"0 0.0 0\n48 0.0 1\n96 0.0 2\n96 0.0 3 4\n").unwrap();
+	///
+	/// let loss = replay.wifescore_loss::<Wife3>(J4).unwrap();
+	/// assert_eq!(loss.by_judgement.total(), 0.0);
+	/// assert_eq!(loss.mine_hits, -Wife3::MINE_HIT_WEIGHT / 3.0 * 100.0);
+	/// ```
+	pub fn wifescore_loss<W: crate::Wife>(&self, judge: &crate::Judge) -> Option<WifescoreLoss> {
+		use crate::SimpleReplay as _;
+
+		if self.deviations.is_empty() {
+			return None;
+		}
+		let percent_per_point = 100.0 / self.deviations.len() as f32;
+
+		let mut by_judgement = self.wife_points_lost_by_judgement::<W>(judge);
+		for judgement in [
+			crate::TapJudgement::Marvelous,
+			crate::TapJudgement::Perfect,
+			crate::TapJudgement::Great,
+			crate::TapJudgement::Good,
+			crate::TapJudgement::Bad,
+			crate::TapJudgement::Miss,
+		] {
+			by_judgement[judgement] *= percent_per_point;
+		}
+
+		Some(WifescoreLoss {
+			by_judgement,
+			mine_hits: -W::MINE_HIT_WEIGHT * self.num_mine_hits as f32 * percent_per_point,
+			hold_drops: -W::HOLD_DROP_WEIGHT * self.num_hold_drops() as f32 * percent_per_point,
+		})
+	}
+}
+
+impl ReplayV2Full {
+	/// Like [`ReplayV2Fast::filter_columns`], but also filters mine hits, lifts and fakes. Since
+	/// mine hit positions are known here, `num_mine_hits` is adjusted accordingly.
+	pub fn filter_columns(&self, mut keep_column: impl FnMut(u8) -> bool) -> Self {
+		let mut filter_events = |events: &[ReplayNoteEvent]| -> Vec<ReplayNoteEvent> {
+			events
+				.iter()
+				.filter(|event| keep_column(event.column))
+				.copied()
+				.collect()
+		};
+		let mine_hits = filter_events(&self.mine_hits);
+		let lifts = filter_events(&self.lifts);
+		let fakes = filter_events(&self.fakes);
+
+		let mut replay = self.replay.filter_columns(keep_column);
+		replay.num_mine_hits = mine_hits.len() as u32;
+		Self {
+			replay,
+			mine_hits,
+			lifts,
+			fakes,
+		}
+	}
+}
+
+impl crate::SimpleReplay for ReplayV2Fast {
+	fn iter_hits(&self) -> Box<dyn '_ + Iterator<Item = crate::Hit>> {
+		Box::new(self.deviations.iter().map(|&deviation| {
+			if deviation < MISS_DEVIATION {
+				crate::Hit::Hit { deviation }
+			} else {
+				crate::Hit::Miss
+			}
+		}))
+	}
+}
+
+impl crate::SimpleReplay for ReplayV2Full {
+	fn iter_hits(&self) -> Box<dyn '_ + Iterator<Item = crate::Hit>> {
+		self.replay.iter_hits()
+	}
+}
+
+impl crate::LaneReplay for ReplayV2Fast {
+	fn iter_lane_hits(&self) -> Box<dyn '_ + Iterator<Item = (u8, crate::Hit)>> {
+		use crate::SimpleReplay as _;
+
+		Box::new(self.columns.iter().copied().zip(self.iter_hits()))
+	}
+}
+
+impl crate::LaneReplay for ReplayV2Full {
+	fn iter_lane_hits(&self) -> Box<dyn '_ + Iterator<Item = (u8, crate::Hit)>> {
+		self.replay.iter_lane_hits()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{LaneReplay as _, SimpleReplay as _, Wife as _};
+
+	const REPLAY: &[u8] = b"0 0.010000 0
+48 -0.020000 1
+24 0.005000 0
+96 1.000000 3
+96 0.030000 2 4
+H 120 1
+144 0.000000 2 2
+";
+
+	#[test]
+	fn test_parse_replay_v2_fast() {
+		let replay = parse_replay_v2_fast(REPLAY).unwrap();
+		assert_eq!(replay.ticks, vec![0, 48, 24, 96, 144]);
+		assert_eq!(replay.deviations, vec![0.01, -0.02, 0.005, 1.0, 0.0]);
+		assert_eq!(replay.columns, vec![0, 1, 0, 3, 2]);
+		assert_eq!(replay.num_mine_hits, 1);
+		assert_eq!(
+			replay.hold_drops,
+			vec![ReplayHoldDrop {
+				tick: 120,
+				column: 1
+			}]
+		);
+		assert_eq!(replay.num_hold_drops(), 1);
+		assert_eq!(replay.iter_hits().filter(|h| h.was_missed()).count(), 1);
+
+		assert_eq!(parse_replay_v2_fast(b"H 120 1\n"), None);
+	}
+
+	#[test]
+	fn test_parse_replay_v2_full() {
+		let replay =
+			parse_replay_v2_full(b"0 0.010000 0\n24 0.020000 1 5\n48 -0.05 3 4\n72 0 2 8\n")
+				.unwrap();
+		assert_eq!(replay.replay.ticks, vec![0]);
+		assert_eq!(replay.replay.num_mine_hits, 1);
+		assert_eq!(
+			replay.mine_hits,
+			vec![ReplayNoteEvent {
+				tick: 48,
+				column: 3,
+				deviation: -0.05
+			}]
+		);
+		assert_eq!(
+			replay.lifts,
+			vec![ReplayNoteEvent {
+				tick: 24,
+				column: 1,
+				deviation: 0.02
+			}]
+		);
+		assert_eq!(replay.fakes.len(), 1);
+		assert_eq!(replay.fakes[0].column, 2);
+	}
+
+	#[test]
+	fn test_parse_replay_file() {
+		let path = std::env::temp_dir().join("etterna_test_parse_replay_file");
+		std::fs::write(&path, REPLAY).unwrap();
+		assert_eq!(
+			parse_replay_file(&path).unwrap(),
+			parse_replay_v2_fast(REPLAY).unwrap()
+		);
+		#[cfg(feature = "mmap")]
+		assert_eq!(
+			parse_replay_file_mmap(&path).unwrap(),
+			parse_replay_v2_fast(REPLAY).unwrap()
+		);
+
+		std::fs::write(&path, b"H 0 0\n").unwrap();
+		assert!(matches!(
+			parse_replay_file(&path),
+			Err(ReplayFileError::Parse {
+				source: ReplayParseError::NoNotes,
+				..
+			})
+		));
+
+		std::fs::remove_file(&path).unwrap();
+		assert!(matches!(
+			parse_replay_file(&path),
+			Err(ReplayFileError::Io { .. })
+		));
+	}
+
+	#[test]
+	fn test_filter_columns() {
+		let replay = parse_replay_v2_fast(REPLAY).unwrap();
+		let filtered = replay.filter_columns(|column| column != 0);
+		assert_eq!(filtered.ticks, vec![48, 96, 144]);
+		assert_eq!(filtered.columns, vec![1, 3, 2]);
+		assert_eq!(filtered.hold_drops.len(), 1);
+		assert_eq!(filtered.num_mine_hits, replay.num_mine_hits);
+		assert_eq!(
+			filtered.iter_hits_within(0.025).collect::<Vec<_>>(),
+			vec![
+				crate::Hit::Hit { deviation: -0.02 },
+				crate::Hit::Hit { deviation: 0.0 }
+			],
+		);
+
+		let full = parse_replay_v2_full(b"0 0.010000 0\n24 0.020000 1 5\n48 -0.05 3 4\n72 0 2 8\n")
+			.unwrap();
+		let filtered = full.filter_columns(|column| column < 3);
+		assert_eq!(filtered.replay.ticks, vec![0]);
+		assert_eq!(filtered.replay.num_mine_hits, 0);
+		assert!(filtered.mine_hits.is_empty());
+		assert_eq!((filtered.lifts.len(), filtered.fakes.len()), (1, 1));
+	}
+
+	#[test]
+	fn test_wifescore_loss() {
+		let replay = parse_replay_v2_fast(REPLAY).unwrap();
+		let loss = replay.wifescore_loss::<crate::Wife3>(crate::J4).unwrap();
+		assert!(loss.by_judgement.misses > loss.by_judgement.marvelouses);
+		assert!(loss.mine_hits > 0.0);
+		assert!(loss.hold_drops > 0.0);
+
+		let wifescore = crate::Wife3::apply(
+			replay.iter_hits(),
+			replay.num_mine_hits,
+			replay.num_hold_drops(),
+			crate::J4,
+		)
+		.unwrap();
+		assert!((100.0 - loss.total() - wifescore.as_percent()).abs() < 0.0001);
+
+		assert_eq!(
+			crate::ReplayV2Fast::default().wifescore_loss::<crate::Wife3>(crate::J4),
+			None
+		);
+	}
+
+	#[test]
+	fn test_lane_replay() {
+		let replay = parse_replay_v2_fast(REPLAY).unwrap();
+		let judgements = replay.lane_tap_judgements(crate::J4);
+		assert_eq!(judgements.len(), 4);
+		assert_eq!(judgements[0].marvelouses, 2);
+		assert_eq!(judgements[1].marvelouses, 1);
+		assert_eq!(judgements[3].misses, 1);
+
+		// In 7k, the middle lane belongs to neither hand
+		let replay = parse_replay_v2_fast(b"0 0.01 2\n0 0.01 3\n0 0.01 4\n0 1.0 6\n").unwrap();
+		let [left, right] = replay.hand_statistics(crate::J4, 7);
+		assert_eq!(left.judgements.marvelouses, 1);
+		assert_eq!(right.judgements.marvelouses, 1);
+		assert_eq!(right.judgements.misses, 1);
+		assert_eq!(right.mean_deviation, Some(0.01));
+		assert_eq!(
+			crate::ReplayV2Fast::default().hand_statistics(crate::J4, 4),
+			[
+				crate::HandStatistics::default(),
+				crate::HandStatistics::default()
+			],
+		);
+	}
+
+	#[test]
+	fn test_parse_replay_v2_errors() {
+		assert_eq!(
+			parse_replay_v2(REPLAY),
+			Ok(parse_replay_v2_fast(REPLAY).unwrap())
+		);
+
+		let malformed = b"0 0.01 0\n24 0.02\n48 abc 1\nH 72\n";
+		assert_eq!(
+			parse_replay_v2(malformed),
+			Err(ReplayParseError::InvalidLine(ReplayLineError {
+				line_number: 2,
+				kind: ReplayLineErrorKind::MissingField("column"),
+			}))
+		);
+
+		let (replay, warnings) = parse_replay_v2_lossy(malformed).unwrap();
+		assert_eq!(replay.ticks, vec![0]);
+		assert_eq!(
+			warnings.iter().map(|w| w.line_number).collect::<Vec<_>>(),
+			vec![2, 3, 4]
+		);
+		assert_eq!(warnings[1].kind, ReplayLineErrorKind::InvalidDeviation);
+		assert_eq!(
+			warnings[2].kind,
+			ReplayLineErrorKind::MissingField("column")
+		);
+
+		assert_eq!(parse_replay_v2(b"\n"), Err(ReplayParseError::NoNotes));
+		assert_eq!(parse_replay_v2_fast(malformed).unwrap().ticks, vec![0]);
+		assert_eq!(parse_replay_v2_fast(b"0 0.01 0\nH").unwrap().ticks, vec![0]);
+	}
+
+	#[test]
+	fn test_split_into_lanes() {
+		let replay = parse_replay_v2_fast(REPLAY).unwrap();
+
+		// 120 BPM means half a second per beat
+		let timing_info = crate::TimingInfo::from_sm_bpm_string(b"0.000=120.000").unwrap();
+		let lanes = replay.split_into_lanes(&timing_info).unwrap();
+		assert_eq!(lanes, replay.split_into_lanes_constant_bpm(120.0).unwrap());
+
+		assert_eq!(lanes[0].note_seconds, vec![0.0, 0.25]);
+		assert_eq!(lanes[0].hit_seconds, vec![0.01, 0.255]);
+		assert_eq!(lanes[1].note_seconds, vec![0.5]);
+		assert_eq!(lanes[1].hit_seconds, vec![0.48]);
+		assert_eq!(lanes[2].note_seconds, vec![1.5]);
+		assert_eq!(lanes[3].note_seconds, vec![1.0]);
+		assert!(lanes[3].hit_seconds.is_empty());
+
+		let points = replay.scatter_plot(&timing_info, crate::Rate::from_x20(40), crate::J4);
+		assert_eq!(
+			points.iter().map(|p| p.second).collect::<Vec<_>>(),
+			vec![0.0, 0.125, 0.25, 0.5, 0.75]
+		);
+		assert_eq!(points[1].hit, crate::Hit::Hit { deviation: 0.005 });
+		assert_eq!(points[3].column, 3);
+		assert_eq!(points[3].judgement, crate::TapJudgement::Miss);
+
+		let note_seconds = replay.note_seconds(&timing_info);
+		assert_eq!(
+			replay.split_into_lanes_with_note_seconds(&note_seconds),
+			Some(lanes)
+		);
+		assert_eq!(
+			replay.split_into_lanes_with_note_seconds(&note_seconds[1..]),
+			None
+		);
+		let mut nan_seconds = note_seconds;
+		nan_seconds[0] = f32::NAN;
+		assert_eq!(
+			replay.split_into_lanes_with_note_seconds(&nan_seconds),
+			None
+		);
+
+		let mut bad_replay = replay;
+		bad_replay.columns[0] = 4;
+		assert_eq!(bad_replay.split_into_lanes(&timing_info), None);
+	}
+
+	proptest::proptest! {
+		#[test]
+		fn test_replay_parsers_dont_panic(
+			bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..256),
+		) {
+			let _ = parse_replay_v2_fast(&bytes);
+			let _ = parse_replay_v2_full(&bytes);
+			let _ = parse_replay_v2(&bytes);
+			let _ = parse_replay_v2_lossy(&bytes);
+		}
+
+		#[test]
+		fn test_replay_parsers_structured_dont_panic(string in "([H0-9 .-]{0,12}\\r?\\n){0,16}") {
+			let _ = parse_replay_v2_fast(string.as_bytes());
+			let _ = parse_replay_v2_full(string.as_bytes());
+			let _ = parse_replay_v2(string.as_bytes());
+			let _ = parse_replay_v2_lossy(string.as_bytes());
+		}
+
+		#[test]
+		fn test_replay_roundtrip(
+			notes in proptest::collection::vec((0u32..1_000_000, -180i32..=180, 0u8..10), 1..64),
+		) {
+			let mut string = String::new();
+			for &(tick, deviation_ms, column) in &notes {
+				string += &format!("{} {:.6} {}\n", tick, deviation_ms as f32 / 1000.0, column);
+			}
+
+			let replay = parse_replay_v2(string.as_bytes()).unwrap();
+			proptest::prop_assert_eq!(&parse_replay_v2_fast(string.as_bytes()).unwrap(), &replay);
+			for (i, &(tick, deviation_ms, column)) in notes.iter().enumerate() {
+				proptest::prop_assert_eq!(replay.ticks[i], tick);
+				proptest::prop_assert_eq!(replay.columns[i], column);
+				let deviation = deviation_ms as f32 / 1000.0;
+				proptest::prop_assert!((replay.deviations[i] - deviation).abs() < 1e-6);
+			}
+		}
+	}
+
+	#[test]
+	fn test_normalize_replay_v2() {
+		let normalized = normalize_replay_v2(REPLAY).unwrap();
+		assert_eq!(
+			normalized,
+			&b"0 0.010000 0
+24 0.005000 0
+48 -0.020000 1
+96 0.030000 2 4
+96 1.000000 3
+144 0.000000 2 2
+H 120 1
+"[..]
+		);
+		assert_eq!(
+			parse_replay_v2_full(&normalized).unwrap().mine_hits,
+			parse_replay_v2_full(REPLAY).unwrap().mine_hits
+		);
+
+		assert_eq!(
+			normalize_replay_v2(b"0 -0.000000 0\n").unwrap(),
+			b"0 0.000000 0\n"
+		);
+		assert_eq!(normalize_replay_v2(b"0 0.01 0 4\nH 0 0\n"), None);
+	}
+}
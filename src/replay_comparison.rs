@@ -0,0 +1,300 @@
+/// A note that was played in both compared replays. See [`compare_replays`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoteComparison {
+	pub tick: u32,
+	pub column: u8,
+	pub hit_a: crate::Hit,
+	pub hit_b: crate::Hit,
+}
+
+impl NoteComparison {
+	/// Difference of the absolute deviations of both hits, in seconds. Negative values mean that
+	/// player A was more accurate on this note.
+	///
+	/// Returns None if either player missed the note
+	pub fn deviation_difference(&self) -> Option<f32> {
+		Some(self.hit_a.deviation()?.abs() - self.hit_b.deviation()?.abs())
+	}
+}
+
+/// A section of two compared replays. See [`compare_replays`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComparisonSection {
+	/// Tick of the first note in this section
+	pub start_tick: u32,
+	/// Tick of the last note in this section
+	pub end_tick: u32,
+	/// Wife3 points of player A in this section, where each note is worth at most 1.0
+	pub wife_points_a: f32,
+	/// Wife3 points of player B in this section, where each note is worth at most 1.0
+	pub wife_points_b: f32,
+}
+
+impl ComparisonSection {
+	/// Returns true if player A was more accurate than player B in this section
+	pub fn a_was_better(&self) -> bool {
+		self.wife_points_a > self.wife_points_b
+	}
+}
+
+/// Result of [`compare_replays`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayComparison {
+	/// All notes that appear in both replays, in chronological order
+	pub notes: Vec<NoteComparison>,
+	pub sections: Vec<ComparisonSection>,
+	/// Number of notes on which player A got a better judgement than player B
+	pub num_better_judgements_a: u32,
+	/// Number of notes on which player B got a better judgement than player A
+	pub num_better_judgements_b: u32,
+	/// Number of notes that only appear in one of the two replays
+	pub num_unmatched_notes: u32,
+}
+
+fn sorted_notes(replay: &crate::ReplayV2Fast) -> Vec<(u32, u8, crate::Hit)> {
+	use crate::SimpleReplay as _;
+
+	let mut notes: Vec<(u32, u8, crate::Hit)> = replay
+		.ticks
+		.iter()
+		.zip(&replay.columns)
+		.zip(replay.iter_hits())
+		.map(|((&tick, &column), hit)| (tick, column, hit))
+		.collect();
+	notes.sort_by_key(|&(tick, column, _)| (tick, column));
+	notes
+}
+
+/// Compares two replays of the same chart note by note, for example for "score battle"
+/// visualizations between two players.
+///
+/// Notes are aligned by their tick and column. The aligned notes are split into `num_sections`
+/// sections with equal note count, and each section reports the Wife3 points of both players.
+/// Judgements are determined using the given judge.
+///
+/// ```rust
+/// # use etterna::*;
+/// let a = parse_replay_v2_fast(b"0 0.01 0\n48 0.02 1\n96 0.15 2\n").unwrap();
+/// let b = parse_replay_v2_fast(b"0 0.03 0\n48 -0.01 1\n96 0.01 2\n").unwrap();
+///
+/// let comparison = compare_replays(&a, &b, J4, 1);
+/// assert_eq!(comparison.notes.len(), 3);
+/// assert_eq!(comparison.num_better_judgements_a, 1);
+/// assert_eq!(comparison.num_better_judgements_b, 1);
+/// assert!(!comparison.sections[0].a_was_better());
+/// ```
+pub fn compare_replays(
+	a: &crate::ReplayV2Fast,
+	b: &crate::ReplayV2Fast,
+	judge: &crate::Judge,
+	num_sections: usize,
+) -> ReplayComparison {
+	let notes_a = sorted_notes(a);
+	let notes_b = sorted_notes(b);
+
+	let mut comparison = ReplayComparison::default();
+	let (mut i_a, mut i_b) = (0, 0);
+	while i_a < notes_a.len() && i_b < notes_b.len() {
+		let (tick_a, column_a, hit_a) = notes_a[i_a];
+		let (tick_b, column_b, hit_b) = notes_b[i_b];
+		match (tick_a, column_a).cmp(&(tick_b, column_b)) {
+			std::cmp::Ordering::Less => i_a += 1,
+			std::cmp::Ordering::Greater => i_b += 1,
+			std::cmp::Ordering::Equal => {
+				comparison.notes.push(NoteComparison {
+					tick: tick_a,
+					column: column_a,
+					hit_a,
+					hit_b,
+				});
+				i_a += 1;
+				i_b += 1;
+			}
+		}
+	}
+	comparison.num_unmatched_notes =
+		(notes_a.len() + notes_b.len() - 2 * comparison.notes.len()) as u32;
+
+	for note in &comparison.notes {
+		// TapJudgement variants are ordered from best to worst
+		let judgement_a = note.hit_a.classify(judge) as u8;
+		let judgement_b = note.hit_b.classify(judge) as u8;
+		if judgement_a < judgement_b {
+			comparison.num_better_judgements_a += 1;
+		} else if judgement_b < judgement_a {
+			comparison.num_better_judgements_b += 1;
+		}
+	}
+
+	if num_sections > 0 && !comparison.notes.is_empty() {
+		let section_size = comparison.notes.len().div_ceil(num_sections);
+		comparison.sections = comparison
+			.notes
+			.chunks(section_size)
+			.map(|notes| ComparisonSection {
+				start_tick: notes[0].tick,
+				end_tick: notes[notes.len() - 1].tick,
+				wife_points_a: notes.iter().map(|n| crate::wife3(n.hit_a, judge)).sum(),
+				wife_points_b: notes.iter().map(|n| crate::wife3(n.hit_b, judge)).sum(),
+			})
+			.collect();
+	}
+
+	comparison
+}
+
+/// Result of [`splice_best_replay`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplicedReplay {
+	/// The hypothetical replay made up of the best hit on each note
+	pub replay: crate::ReplayV2Fast,
+	pub wifescore: crate::Wifescore,
+}
+
+/// Given multiple replays of the same chart, computes the hypothetical best run that takes the best
+/// hit on each note across all replays. Its wifescore is a popular "potential score" statistic.
+///
+/// Notes are aligned by their tick and column, and the best hit is determined by the given
+/// scoring system and judge. Mine hits and hold drops are taken from the replay with the fewest of
+/// each.
+///
+/// Returns None if the replays contain no notes
+///
+/// ```rust
+/// # use etterna::*;
+/// let a = parse_replay_v2_fast(b"0 0.01 0\n48 0.09 1\n").unwrap();
+/// let b = parse_replay_v2_fast(b"0 0.08 0\n48 0.00 1\n").unwrap();
+///
+/// let spliced = splice_best_replay::<Wife3>(&[&a, &b], J4).unwrap();
+/// assert_eq!(spliced.replay.deviations, vec![0.01, 0.00]);
+/// assert!(spliced.wifescore.as_percent() > 99.0);
+/// ```
+pub fn splice_best_replay<W: crate::Wife>(
+	replays: &[&crate::ReplayV2Fast],
+	judge: &crate::Judge,
+) -> Option<SplicedReplay> {
+	use crate::SimpleReplay as _;
+
+	let mut best_notes: Vec<(u32, u8, f32, crate::Hit)> = Vec::new();
+	for replay in replays {
+		best_notes.extend(
+			replay
+				.ticks
+				.iter()
+				.zip(&replay.columns)
+				.zip(&replay.deviations)
+				.zip(replay.iter_hits())
+				.map(|(((&tick, &column), &deviation), hit)| (tick, column, deviation, hit)),
+		);
+	}
+	// Sort each note's hits from best to worst, then only keep the first of each note
+	best_notes.sort_by(|a, b| {
+		(a.0, a.1).cmp(&(b.0, b.1)).then_with(|| {
+			W::calc(b.3, judge)
+				.partial_cmp(&W::calc(a.3, judge))
+				.unwrap_or(std::cmp::Ordering::Equal)
+		})
+	});
+	best_notes.dedup_by_key(|&mut (tick, column, _, _)| (tick, column));
+
+	let num_mine_hits = replays.iter().map(|r| r.num_mine_hits).min()?;
+	let hold_drops = replays
+		.iter()
+		.map(|r| &r.hold_drops)
+		.min_by_key(|hold_drops| hold_drops.len())?
+		.clone();
+
+	let wifescore = crate::rescore_from_note_hits::<W, _>(
+		best_notes.iter().map(|&(_, _, _, hit)| hit),
+		num_mine_hits,
+		hold_drops.len() as u32,
+		judge,
+	)?;
+
+	Some(SplicedReplay {
+		replay: crate::ReplayV2Fast {
+			ticks: best_notes.iter().map(|&(tick, _, _, _)| tick).collect(),
+			deviations: best_notes
+				.iter()
+				.map(|&(_, _, deviation, _)| deviation)
+				.collect(),
+			columns: best_notes.iter().map(|&(_, column, _, _)| column).collect(),
+			num_mine_hits,
+			hold_drops,
+		},
+		wifescore,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::SimpleReplay as _;
+
+	#[test]
+	fn test_compare_replays() {
+		let a = crate::parse_replay_v2_fast(
+			b"0 0.000000 0\n48 0.010000 1\n96 1.000000 2\n144 0.005000 3\n192 0.000000 0\n",
+		)
+		.unwrap();
+		// Different order, one note less, one extra note
+		let b = crate::parse_replay_v2_fast(
+			b"48 0.050000 1\n0 -0.002000 0\n96 0.030000 2\n144 0.005000 3\n240 0.000000 0\n",
+		)
+		.unwrap();
+
+		let comparison = compare_replays(&a, &b, crate::J4, 2);
+		assert_eq!(
+			comparison.notes.iter().map(|n| n.tick).collect::<Vec<_>>(),
+			vec![0, 48, 96, 144],
+		);
+		assert_eq!(comparison.num_unmatched_notes, 2);
+		assert_eq!(comparison.num_better_judgements_a, 1);
+		assert_eq!(comparison.num_better_judgements_b, 1);
+
+		assert_eq!(comparison.notes[2].deviation_difference(), None);
+		assert!(comparison.notes[1].deviation_difference().unwrap() < 0.0);
+
+		assert_eq!(comparison.sections.len(), 2);
+		assert_eq!(
+			(
+				comparison.sections[0].start_tick,
+				comparison.sections[0].end_tick
+			),
+			(0, 48),
+		);
+		assert!(comparison.sections[0].a_was_better());
+		assert!(!comparison.sections[1].a_was_better());
+	}
+
+	#[test]
+	fn test_splice_best_replay() {
+		let a = crate::parse_replay_v2_fast(
+			b"0 0.000000 0\n48 1.000000 1\n96 0.050000 2\n96 0.000000 0 4\nH 48 1\n",
+		)
+		.unwrap();
+		let b =
+			crate::parse_replay_v2_fast(b"48 -0.020000 1\n0 0.100000 0\n144 0.010000 3\n").unwrap();
+
+		let spliced = splice_best_replay::<crate::Wife3>(&[&a, &b], crate::J4).unwrap();
+		assert_eq!(spliced.replay.ticks, vec![0, 48, 96, 144]);
+		assert_eq!(spliced.replay.deviations, vec![0.0, -0.02, 0.05, 0.01]);
+		assert_eq!(spliced.replay.num_mine_hits, 0);
+		assert!(spliced.replay.hold_drops.is_empty());
+		assert_eq!(
+			Some(spliced.wifescore),
+			crate::rescore_from_note_hits::<crate::Wife3, _>(
+				spliced.replay.iter_hits(),
+				0,
+				0,
+				crate::J4
+			),
+		);
+
+		assert_eq!(splice_best_replay::<crate::Wife3>(&[], crate::J4), None);
+	}
+}
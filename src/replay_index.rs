@@ -0,0 +1,206 @@
+/// A note of a [`ReplaySecondsIndex`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexedNote {
+	/// Position in real time seconds (i.e. adjusted for rate). Depending on the query, this is
+	/// either the note's position in the song or the time it was hit
+	pub second: f32,
+	/// Index of the note in the replay's parallel vectors (`ticks`, `deviations`, `columns`)
+	pub index: u32,
+}
+
+/// Index over the notes of a [`crate::ReplayV2Fast`] by time, for repeatedly querying the notes or
+/// hits in a time window, e.g. in a scrubbing UI that redraws the visible section of a replay.
+///
+/// Building the index sorts the notes once; every query after that is a binary search that returns
+/// a slice, instead of a scan over the whole replay.
+///
+/// ```rust
+/// # use etterna::*;
+/// let replay = parse_replay_v2_fast(b"0 0.01 0\n48 1.0 1\n96 -0.02 2\n144 0.0 3\n").unwrap();
+/// let timing_info = TimingInfo::from_constant_bpm(60.0);
+/// let index = ReplaySecondsIndex::new(&replay, &timing_info, Rate::from_f32(1.0).unwrap());
+///
+/// let notes: Vec<u32> = index.notes_in(1.0..3.0).iter().map(|note| note.index).collect();
+/// assert_eq!(notes, vec![1, 2]);
+/// // the note at 1s was missed, and the note at 2s was hit slightly early
+/// let hits = index.hits_in(1.0..3.0);
+/// assert_eq!(hits.len(), 1);
+/// assert_eq!(hits[0].index, 2);
+/// assert!((hits[0].second - 1.98).abs() < 0.0001);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedReplaySecondsIndex"))]
+pub struct ReplaySecondsIndex {
+	/// By note position, sorted chronologically
+	notes: Vec<IndexedNote>,
+	/// By hit time, sorted chronologically. Misses are excluded
+	hits: Vec<IndexedNote>,
+}
+
+/// Returned when deserializing a [`ReplaySecondsIndex`] whose notes or hits aren't sorted by
+/// second, or whose hits refer to notes that don't exist
+#[cfg(feature = "serde")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("Indexed notes and hits must be sorted by second and refer to existing notes")]
+pub struct InvalidReplaySecondsIndexError;
+
+/// Deserialized form of [`ReplaySecondsIndex`], before the invariants are checked
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedReplaySecondsIndex {
+	notes: Vec<IndexedNote>,
+	hits: Vec<IndexedNote>,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<UncheckedReplaySecondsIndex> for ReplaySecondsIndex {
+	type Error = InvalidReplaySecondsIndexError;
+
+	fn try_from(index: UncheckedReplaySecondsIndex) -> Result<Self, Self::Error> {
+		let UncheckedReplaySecondsIndex { notes, hits } = index;
+		let is_sorted = |entries: &[IndexedNote]| {
+			entries
+				.windows(2)
+				.all(|pair| pair[0].second <= pair[1].second)
+				&& !entries.iter().any(|entry| entry.second.is_nan())
+		};
+		let is_note_index = |entry: &IndexedNote| (entry.index as usize) < notes.len();
+
+		if is_sorted(&notes)
+			&& is_sorted(&hits)
+			&& hits.len() <= notes.len()
+			&& hits.iter().all(is_note_index)
+		{
+			Ok(Self { notes, hits })
+		} else {
+			Err(InvalidReplaySecondsIndexError)
+		}
+	}
+}
+
+impl ReplaySecondsIndex {
+	/// Indexes the notes of a replay. The note positions are scaled by the given rate, like in
+	/// [`crate::ReplayV2Fast::hit_seconds`]
+	pub fn new(
+		replay: &crate::ReplayV2Fast,
+		timing_info: &crate::TimingInfo,
+		rate: crate::Rate,
+	) -> Self {
+		let note_seconds: Vec<f32> = replay
+			.note_seconds(timing_info)
+			.into_iter()
+			.map(|second| rate.apply(second))
+			.collect();
+
+		let mut notes: Vec<IndexedNote> = note_seconds
+			.iter()
+			.enumerate()
+			.map(|(index, &second)| IndexedNote {
+				second,
+				index: index as u32,
+			})
+			.collect();
+		let mut hits: Vec<IndexedNote> = notes
+			.iter()
+			.zip(&replay.deviations)
+			.filter(|&(_, &deviation)| deviation < crate::replay::MISS_DEVIATION)
+			.map(|(note, &deviation)| IndexedNote {
+				second: note.second + deviation,
+				index: note.index,
+			})
+			.collect();
+
+		// UNWRAP: seconds are derived from finite ticks and deviations, so they're never NaN
+		notes.sort_by(|a, b| a.second.partial_cmp(&b.second).unwrap());
+		hits.sort_by(|a, b| a.second.partial_cmp(&b.second).unwrap());
+
+		Self { notes, hits }
+	}
+
+	/// Slice of the entries whose second is within the range
+	fn query(entries: &[IndexedNote], seconds: std::ops::Range<f32>) -> &[IndexedNote] {
+		let start = entries.partition_point(|entry| entry.second < seconds.start);
+		let end = entries.partition_point(|entry| entry.second < seconds.end);
+		entries.get(start..end).unwrap_or(&[])
+	}
+
+	/// All notes positioned within the given time window, sorted chronologically. Includes missed
+	/// notes
+	pub fn notes_in(&self, seconds: std::ops::Range<f32>) -> &[IndexedNote] {
+		Self::query(&self.notes, seconds)
+	}
+
+	/// All hits that happened within the given time window, sorted chronologically. Unlike
+	/// [`Self::notes_in`], this goes by the time the note was hit, i.e. its position plus the
+	/// deviation. Misses are excluded
+	pub fn hits_in(&self, seconds: std::ops::Range<f32>) -> &[IndexedNote] {
+		Self::query(&self.hits, seconds)
+	}
+
+	/// All notes, sorted chronologically by position
+	pub fn notes(&self) -> &[IndexedNote] {
+		&self.notes
+	}
+
+	/// All hits, sorted chronologically by hit time
+	pub fn hits(&self) -> &[IndexedNote] {
+		&self.hits
+	}
+}
+
+impl crate::ReplayV2Fast {
+	/// Shorthand for [`ReplaySecondsIndex::new`]
+	pub fn seconds_index(
+		&self,
+		timing_info: &crate::TimingInfo,
+		rate: crate::Rate,
+	) -> ReplaySecondsIndex {
+		ReplaySecondsIndex::new(self, timing_info, rate)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_replay_seconds_index() {
+		// unsorted ticks, and a hit that's early enough to be hit before the previous note
+		let replay =
+			crate::parse_replay_v2_fast(b"96 0.0 0\n0 0.0 1\n48 0.1 2\n52 -0.1 3\n").unwrap();
+		let timing_info = crate::TimingInfo::from_constant_bpm(60.0);
+		let index = replay.seconds_index(&timing_info, crate::Rate::from_f32(2.0).unwrap());
+
+		let indices = |entries: &[IndexedNote]| entries.iter().map(|e| e.index).collect::<Vec<_>>();
+		assert_eq!(indices(index.notes()), vec![1, 2, 3, 0]);
+		assert_eq!(indices(index.hits()), vec![1, 3, 2, 0]);
+		assert_eq!(indices(index.notes_in(0.5..1.0)), vec![2, 3]);
+		assert_eq!(indices(index.hits_in(0.4..1.0)), vec![3, 2]);
+		// the range end is exclusive
+		assert_eq!(indices(index.notes_in(0.0..0.5)), vec![1]);
+		assert!(index.notes_in(5.0..10.0).is_empty());
+		assert!(index.notes_in(1.0..0.0).is_empty());
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn test_serde() {
+		let replay = crate::parse_replay_v2_fast(b"0 0.0 0\n48 1.0 1\n96 -0.02 2\n").unwrap();
+		let timing_info = crate::TimingInfo::from_constant_bpm(60.0);
+		let index = replay.seconds_index(&timing_info, crate::Rate::from_f32(1.0).unwrap());
+
+		let json = serde_json::to_string(&index).unwrap();
+		assert_eq!(
+			serde_json::from_str::<ReplaySecondsIndex>(&json).unwrap(),
+			index
+		);
+
+		let unsorted = r#"{"notes":[{"second":1.0,"index":0},{"second":0.0,"index":1}],"hits":[]}"#;
+		assert!(serde_json::from_str::<ReplaySecondsIndex>(unsorted).is_err());
+		let dangling_hit =
+			r#"{"notes":[{"second":0.0,"index":0}],"hits":[{"second":0.0,"index":5}]}"#;
+		assert!(serde_json::from_str::<ReplaySecondsIndex>(dangling_hit).is_err());
+	}
+}
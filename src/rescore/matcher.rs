@@ -0,0 +1,230 @@
+//! Claim-window matching of hits to notes: each hit, in order, claims an unclaimed note within the
+//! miss window. This is the matching used by [`crate::NaiveScorer`], exposed so that other scorers
+//! and analysis (e.g. attributing deviations to notes) can reuse it.
+
+/// Decides which of the unclaimed notes within the miss window a hit claims in [`claim_match`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClaimPolicy {
+	/// The note closest to the hit. On ties, the earlier note wins. This is what Etterna does and
+	/// what [`crate::NaiveScorer`] uses
+	Nearest,
+	/// The earliest note, regardless of how far away it is
+	Earliest,
+	/// Like [`Self::Nearest`], but only notes after the most recently claimed note are considered.
+	/// Notes are claimed strictly in chart order, so once a note is claimed, any unclaimed notes
+	/// before it count as missed
+	GreedyByHitOrder,
+}
+
+/// A hit that claimed a note in [`claim_match`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Claim {
+	/// Index into the hit seconds
+	pub hit_index: usize,
+	/// Index into the note seconds
+	pub note_index: usize,
+	/// Hit second minus note second, i.e. negative if the hit was early
+	pub deviation: f32,
+}
+
+/// Result of [`claim_match`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClaimMatching {
+	/// Claims in hit order
+	pub claims: Vec<Claim>,
+	pub num_notes: usize,
+	pub num_hits: usize,
+}
+
+impl ClaimMatching {
+	/// Notes that weren't claimed by any hit
+	pub fn num_unclaimed_notes(&self) -> usize {
+		self.num_notes - self.claims.len()
+	}
+
+	/// Hits that didn't claim any note
+	pub fn num_stray_hits(&self) -> usize {
+		self.num_hits - self.claims.len()
+	}
+
+	/// For each note, the claim of the hit that claimed it, if any
+	pub fn claims_by_note(&self) -> Vec<Option<Claim>> {
+		let mut claims_by_note = vec![None; self.num_notes];
+		for &claim in &self.claims {
+			claims_by_note[claim.note_index] = Some(claim);
+		}
+		claims_by_note
+	}
+
+	/// Converts into a [`crate::LaneMatching`]. Stray hits are not punished, like in
+	/// [`crate::NaiveScorer`]
+	pub fn to_lane_matching(&self) -> crate::LaneMatching {
+		crate::LaneMatching {
+			deviations: self
+				.claims
+				.iter()
+				.map(|claim| claim.deviation.abs())
+				.collect(),
+			num_misses: self.num_unclaimed_notes() as u32,
+			num_stray_taps: 0,
+		}
+	}
+}
+
+/// Matches the hits of a single column to its notes. Hits are processed in order, and each hit
+/// claims one of the notes within `miss_window` seconds that haven't been claimed yet, as chosen by
+/// `policy`. Hits without such a note are stray hits.
+///
+/// Panics if the hit seconds are not sorted.
+///
+/// ```rust
+/// # use etterna::*;
+/// # use etterna::matcher::*;
+/// let lane = NoteAndHitSeconds {
+/// 	note_seconds: vec![1.0, 1.1, 2.0],
+/// 	hit_seconds: vec![1.08, 1.12, 5.0],
+/// };
+///
+/// let nearest = claim_match(&lane, 0.18, ClaimPolicy::Nearest);
+/// let note_indices: Vec<_> = nearest.claims.iter().map(|claim| claim.note_index).collect();
+/// assert_eq!(note_indices, vec![1, 0]);
+/// assert_eq!(nearest.num_unclaimed_notes(), 1);
+/// assert_eq!(nearest.num_stray_hits(), 1);
+///
+/// let earliest = claim_match(&lane, 0.18, ClaimPolicy::Earliest);
+/// let note_indices: Vec<_> = earliest.claims.iter().map(|claim| claim.note_index).collect();
+/// assert_eq!(note_indices, vec![0, 1]);
+/// ```
+pub fn claim_match(
+	lane: &crate::NoteAndHitSeconds,
+	miss_window: f32,
+	policy: ClaimPolicy,
+) -> ClaimMatching {
+	let crate::NoteAndHitSeconds {
+		note_seconds,
+		hit_seconds,
+	} = lane;
+
+	assert!(crate::util::is_sorted(hit_seconds));
+
+	let mut is_claimed = vec![false; note_seconds.len()];
+	// Notes before this index can't be claimed anymore with ClaimPolicy::GreedyByHitOrder
+	let mut next_claimable_note = 0;
+	let mut claims = Vec::with_capacity(note_seconds.len().min(hit_seconds.len()));
+	for (hit_index, &hit_second) in hit_seconds.iter().enumerate() {
+		let first_candidate = match policy {
+			ClaimPolicy::GreedyByHitOrder => next_claimable_note,
+			ClaimPolicy::Nearest | ClaimPolicy::Earliest => 0,
+		};
+		let mut candidates = note_seconds
+			.iter()
+			.enumerate()
+			.skip(first_candidate)
+			.filter(|&(note_index, &note_second)| {
+				!is_claimed[note_index] && (hit_second - note_second).abs() <= miss_window
+			});
+
+		let best_note = match policy {
+			ClaimPolicy::Nearest | ClaimPolicy::GreedyByHitOrder => {
+				let mut best_note: Option<(usize, &f32)> = None;
+				for (note_index, note_second) in candidates {
+					let is_better = match best_note {
+						Some((_, best_second)) => {
+							(hit_second - note_second).abs() < (hit_second - best_second).abs()
+						}
+						None => true,
+					};
+					if is_better {
+						best_note = Some((note_index, note_second));
+					}
+				}
+				best_note
+			}
+			ClaimPolicy::Earliest => candidates.next(),
+		};
+
+		if let Some((note_index, &note_second)) = best_note {
+			is_claimed[note_index] = true;
+			next_claimable_note = note_index + 1;
+			claims.push(Claim {
+				hit_index,
+				note_index,
+				deviation: hit_second - note_second,
+			});
+		}
+	}
+
+	ClaimMatching {
+		claims,
+		num_notes: note_seconds.len(),
+		num_hits: hit_seconds.len(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn claimed_notes(lane: &crate::NoteAndHitSeconds, policy: ClaimPolicy) -> Vec<(usize, usize)> {
+		claim_match(lane, 0.18, policy)
+			.claims
+			.iter()
+			.map(|claim| (claim.hit_index, claim.note_index))
+			.collect()
+	}
+
+	#[test]
+	fn test_claim_policies() {
+		let lane = crate::NoteAndHitSeconds {
+			note_seconds: vec![1.0, 1.1, 1.2],
+			hit_seconds: vec![1.12, 1.13, 1.14],
+		};
+		assert_eq!(
+			claimed_notes(&lane, ClaimPolicy::Nearest),
+			vec![(0, 1), (1, 2), (2, 0)]
+		);
+		assert_eq!(
+			claimed_notes(&lane, ClaimPolicy::Earliest),
+			vec![(0, 0), (1, 1), (2, 2)]
+		);
+
+		// the first hit claims the note at 1.1, so the note at 1.0 can't be claimed anymore
+		let lane = crate::NoteAndHitSeconds {
+			note_seconds: vec![0.8, 1.0, 1.1],
+			hit_seconds: vec![1.09, 1.1],
+		};
+		assert_eq!(
+			claimed_notes(&lane, ClaimPolicy::Nearest),
+			vec![(0, 2), (1, 1)]
+		);
+		assert_eq!(
+			claimed_notes(&lane, ClaimPolicy::GreedyByHitOrder),
+			vec![(0, 2)]
+		);
+	}
+
+	#[test]
+	fn test_claim_matching() {
+		let lane = crate::NoteAndHitSeconds {
+			note_seconds: vec![1.0, 2.0, 3.0],
+			hit_seconds: vec![0.9, 1.5, 3.05],
+		};
+		let matching = claim_match(&lane, 0.18, ClaimPolicy::Nearest);
+		assert_eq!(matching.num_unclaimed_notes(), 1);
+		assert_eq!(matching.num_stray_hits(), 1);
+
+		let claims_by_note = matching.claims_by_note();
+		assert_eq!(claims_by_note[0].unwrap().hit_index, 0);
+		assert!(claims_by_note[0].unwrap().deviation < 0.0);
+		assert_eq!(claims_by_note[1], None);
+		assert_eq!(claims_by_note[2].unwrap().hit_index, 2);
+
+		let lane_matching = matching.to_lane_matching();
+		assert_eq!(lane_matching.num_misses, 1);
+		assert_eq!(lane_matching.num_stray_taps, 0);
+		assert!(lane_matching.deviations.iter().all(|&d| d > 0.0));
+	}
+}
@@ -1,6 +1,4 @@
-use super::{ScoringResult, ScoringSystem};
-
-const DEBUG: bool = false;
+use super::{LaneMatching, ScoringSystem};
 
 struct Note {
 	second: f32,
@@ -8,205 +6,140 @@ struct Note {
 }
 
 struct AssignedHit {
-	hit: *mut Hit,
+	hit_index: usize,
 	deviation: f32,
 }
 
-struct Hit {
-	second: f32,
-	assigned_note: Option<*const Note>,
+/// Range of notes that may lie within the miss window around `hit_second`. This is conservative: the
+/// exact miss window check is still done on each note in the range.
+///
+/// Relies on the notes being sorted. Float subtraction is monotonic, so the predicates are too
+fn window_bounds(notes: &[Note], hit_second: f32, miss_window: f32) -> (usize, usize) {
+	let start = notes.partition_point(|note| hit_second - note.second > miss_window);
+	let end = notes.partition_point(|note| note.second - hit_second <= miss_window);
+	(start, end.max(start))
 }
 
-impl Hit {
-	unsafe fn find_matching_note(&mut self, notes: *mut Vec<Note>, judge: &crate::Judge) {
-		if DEBUG {
-			println!(
-				"Ok so, we're searching for a matching note for {}",
-				self.second
-			)
-		}
-		let mut best_note: Option<&mut Note> = None;
-		let mut best_deviation = f32::INFINITY;
+/// Finds the best matching note for the given hit, among the notes in `candidates`, which is either
+/// still free, or whose assigned hit is worse than this one. Among equally good notes, the later
+/// one is chosen.
+fn find_matching_note(
+	notes: &[Note],
+	candidates: std::ops::Range<usize>,
+	hit_second: f32,
+	miss_window: f32,
+) -> Option<(usize, f32)> {
+	let mut best_note = None;
+	let mut best_deviation = f32::INFINITY;
+	for note_index in candidates {
+		let note = &notes[note_index];
+		let deviation = (note.second - hit_second).abs();
 
-		// Find the best matching note that's either still free, or whose assigned hit is worse
-		// than this one.
-		for note in &mut *notes {
-			let deviation = (note.second - self.second).abs();
+		if deviation > best_deviation {
+			continue;
+		}
+		if deviation > miss_window {
+			continue;
+		} // this is too far to be considered a match
 
-			if deviation > best_deviation {
+		if let Some(assigned_hit) = &note.assigned_hit {
+			// Give a tiny bit of bias to the existing hit, so that when we have two exact same
+			// hits, we'll not keep favoring the new hit and keep overwriting each other endlessly
+			if assigned_hit.deviation - 0.000001 < deviation {
+				// the note already has an assigned hit that fits even better than this one would,
+				// so we leave it be
 				continue;
 			}
-			if deviation > judge.bad_window {
-				continue;
-			} // this is too far to be considered a match
-
-			if DEBUG {
-				println!(
-					"Found best note so far at {} (dev={})",
-					note.second, deviation
-				)
-			}
-
-			if let Some(assigned_hit) = &note.assigned_hit {
-				// Give a tiny bit of bias to the existing hit, so that when we have two exact
-				// same hits, we'll not keep favoring the new hit and keep overwriting each
-				// other endlessly (if that makes sense lol)
-				if assigned_hit.deviation - 0.000001 < deviation {
-					// the note already has an assigned hit that fits even better than this one
-					// would, so we leave it be
-					if DEBUG {
-						println!(
-							"Already assigned to something better (dev={}) unfortunately..",
-							assigned_hit.deviation
-						);
-					}
-					continue;
-				}
-
-				if DEBUG {
-					println!(
-						"Already assigned to hit {} but we could overwrite! :) ({} < {})",
-						(*assigned_hit.hit).second,
-						deviation,
-						assigned_hit.deviation
-					);
-				}
-			}
-
-			best_note = Some(note);
-			best_deviation = deviation;
 		}
-		let best_note: &mut Note = match best_note {
-			Some(a) => a,
-			None => {
-				// this hit has no place :'( in other words, it's a stray hit
-				self.assigned_note = None;
-				return;
-			}
-		};
 
-		if DEBUG {
-			println!(
-				"After iterating notes, the best note is at {}",
-				best_note.second
-			)
-		}
-
-		// Save prev owner for later, so that we can make it find itself a new note later after
-		// we assigned ourselves to the note (we can only do it _after_ we assigned ourselves,
-		// cuz otherwise it's just gonna pick the same note again)
-		let prev_assigned_hit_to_be_relocated: Option<*mut Hit> = best_note
-			.assigned_hit
-			.as_ref()
-			.map(|assigned_hit| assigned_hit.hit);
-
-		// Assign ourselves to the note
-		best_note.assigned_hit = Some(AssignedHit {
-			hit: self,
-			deviation: best_deviation,
-		});
-		self.assigned_note = Some(best_note as *const Note);
-
-		// If the note previously had a hit assigned to it, we have just
-		if let Some(prev_assigned_hit_to_be_relocated) = prev_assigned_hit_to_be_relocated {
-			if DEBUG {
-				println!(">>> Ah yes, it was already assigned. Starting inner re-find...\n")
-			}
-			(*prev_assigned_hit_to_be_relocated).find_matching_note(notes, judge);
-			if DEBUG {
-				println!("\n<<< Inner re-find done")
-			}
-		}
+		best_note = Some(note_index);
+		best_deviation = deviation;
 	}
+	best_note.map(|note_index| (note_index, best_deviation))
 }
 
-// Evaluates wifescore_sum and num_judged_notes for a single column
-// This function is unsafe because I'm using raw pointers within for ease of use. I really did
-// _not_ want to bother with RefCell/Rc/lifetimes. The rescoring algorithm is hard enough to
-// implement as is.
-unsafe fn column_rescore<W: crate::Wife>(
-	mut notes: Vec<Note>,
-	mut hits: Vec<Hit>,
-	judge: &crate::Judge,
-) -> (f32, u64) {
-	// use miss weights for stray taps for now. Maybe it would make for a better system with a
-	// tweaked value - but who cares, my system won't ever get serious adoption anyway /shrug
-	let stray_tap_weight: f32 = W::MISS_WEIGHT;
-
-	for hit in &mut hits {
-		if DEBUG {
-			println!("Initial search for hit at {}", hit.second)
-		}
-		hit.find_matching_note(&mut notes, judge);
-		if DEBUG {
-			println!(
-				"Initial search for hit at {} completed -> {:?}",
-				hit.second,
-				hit.assigned_note.map(|n| (*n).second)
-			);
+/// Matches the hits of a single column to its notes.
+///
+/// Hits are assigned in order, with a two-pointer sweep over the sorted notes that tracks which
+/// notes are within the miss window of the current hit. If a hit steals a note from a worse hit,
+/// the displaced hit has to find itself a new note; it's located with a binary search instead,
+/// because it lies behind the sweep.
+fn column_match(note_seconds: &[f32], hit_seconds: &[f32], miss_window: f32) -> LaneMatching {
+	let mut notes: Vec<Note> = note_seconds
+		.iter()
+		.map(|&second| Note {
+			second,
+			assigned_hit: None,
+		})
+		.collect();
+	let mut hit_assigned_notes: Vec<Option<usize>> = vec![None; hit_seconds.len()];
+
+	let (mut window_start, mut window_end) = (0, 0);
+	for (hit_index, &hit_second) in hit_seconds.iter().enumerate() {
+		while window_start < notes.len() && hit_second - notes[window_start].second > miss_window {
+			window_start += 1;
 		}
-		if DEBUG {
-			println!(".")
+		window_end = window_end.max(window_start);
+		while window_end < notes.len() && notes[window_end].second - hit_second <= miss_window {
+			window_end += 1;
 		}
-	}
 
-	if DEBUG {
-		println!(".");
-		for hit in &hits {
-			println!(
-				"Hit {}\t-> Note {:?}\t(dev={:?})",
-				hit.second,
-				hit.assigned_note.map(|n| (*n).second),
-				// UNWRAP: I don't even know, but if we're in debug mode, this is not running in
-				// prod anyway so panicking is fine
-				hit.assigned_note
-					.map(|n| (*n).assigned_hit.as_ref().unwrap().deviation)
-			);
-		}
-		println!(".\nTHE NOTE PERSPECTIVE OF THINGS");
-		for note in &notes {
-			println!(
-				"Note {}\t-> Hit {:?}\t(dev={:?})",
-				note.second,
-				note.assigned_hit.as_ref().map(|h| (*h.hit).second),
-				note.assigned_hit.as_ref().map(|h| h.deviation)
-			);
+		let mut hit_to_assign = hit_index;
+		let mut candidates = window_start..window_end;
+		loop {
+			let hit_second = hit_seconds[hit_to_assign];
+			let (note_index, deviation) =
+				match find_matching_note(&notes, candidates, hit_second, miss_window) {
+					Some(best_note) => best_note,
+					None => {
+						// this hit has no place :'( in other words, it's a stray hit
+						hit_assigned_notes[hit_to_assign] = None;
+						break;
+					}
+				};
+
+			// Assign ourselves to the note, and remember the previous owner of the note. It has to
+			// find itself a new note now (which can only be done _after_ we assigned ourselves,
+			// cuz otherwise it's just gonna pick the same note again)
+			let prev_assigned_hit = notes[note_index].assigned_hit.replace(AssignedHit {
+				hit_index: hit_to_assign,
+				deviation,
+			});
+			hit_assigned_notes[hit_to_assign] = Some(note_index);
+
+			match prev_assigned_hit {
+				Some(prev_assigned_hit) => {
+					hit_to_assign = prev_assigned_hit.hit_index;
+					let (start, end) =
+						window_bounds(&notes, hit_seconds[hit_to_assign], miss_window);
+					candidates = start..end;
+				}
+				None => break,
+			}
 		}
 	}
 
-	let num_stray_taps = hits
+	let num_stray_taps = hit_assigned_notes
 		.iter()
-		.filter(|hit| hit.assigned_note.is_none())
+		.filter(|assigned_note| assigned_note.is_none())
 		.count();
 	let num_misses = notes
 		.iter()
 		.filter(|note| note.assigned_hit.is_none())
 		.count();
 
-	if DEBUG {
-		println!(
-			"Found {} misses and {} stray taps",
-			num_misses, num_stray_taps
-		);
-	}
-
-	let mut num_matched_hits = 0;
-	// These are only the matched notes/hits! Still need to punish for misses and strays
-	let mut wifescore_sum: f32 = notes
+	// These are only the matched notes/hits! Misses and strays are punished by LaneMatching
+	let deviations = notes
 		.iter()
 		.filter_map(|note| note.assigned_hit.as_ref()) // only notes with assigned hits (i.e. notes that were hit)
-		.map(|assigned_hit| W::calc_deviation(assigned_hit.deviation, judge))
-		.inspect(|_| num_matched_hits += 1)
-		.sum();
-
-	// penalize
-	wifescore_sum += W::MISS_WEIGHT * num_misses as f32;
-	wifescore_sum += stray_tap_weight * num_stray_taps as f32;
+		.map(|assigned_hit| assigned_hit.deviation)
+		.collect();
 
-	// is this correct?? I think so but I'm insecure about this
-	let num_judged_notes = num_matched_hits + num_misses + num_stray_taps;
-
-	(wifescore_sum, num_judged_notes as u64)
+	LaneMatching {
+		deviations,
+		num_misses: num_misses as u32,
+		num_stray_taps: num_stray_taps as u32,
+	}
 }
 
 /// Scoring system that assigns hits to notes by trying to find the optimal mapping between the two,
@@ -214,13 +147,17 @@ unsafe fn column_rescore<W: crate::Wife>(
 ///
 /// This makes for a robust system against bullshit cb rushes (for lack of a better term). To combat
 /// mashing, this scorer treats any stray taps (not mapped to any note) as a miss.
+///
+/// Panics if the hit seconds are not sorted. Unsorted note seconds are sorted first, which costs an
+/// extra allocation.
 pub struct MatchingScorer;
 
 impl ScoringSystem for MatchingScorer {
-	fn evaluate<W: crate::Wife>(
+	fn match_hits<W: crate::Wife>(
 		lane: &crate::NoteAndHitSeconds,
 		judge: &crate::Judge,
-	) -> ScoringResult {
+		options: &super::ScoringOptions,
+	) -> LaneMatching {
 		let crate::NoteAndHitSeconds {
 			note_seconds,
 			hit_seconds,
@@ -228,25 +165,139 @@ impl ScoringSystem for MatchingScorer {
 
 		assert!(crate::util::is_sorted(hit_seconds));
 
-		let notes: Vec<Note> = note_seconds
-			.iter()
-			.map(|&second| Note {
-				second,
-				assigned_hit: None,
-			})
-			.collect();
-		let hits: Vec<Hit> = hit_seconds
-			.iter()
-			.map(|&second| Hit {
-				second,
-				assigned_note: None,
-			})
-			.collect();
-
-		let (wifescore_sum, num_judged_notes) = unsafe { column_rescore::<W>(notes, hits, judge) };
-		ScoringResult {
-			wifescore_sum,
-			num_judged_notes,
+		let miss_window = options.miss_window.window(judge);
+		if crate::util::is_sorted(note_seconds) {
+			column_match(note_seconds, hit_seconds, miss_window)
+		} else {
+			// The sweep relies on sorted notes. The order of the notes doesn't matter otherwise
+			let mut note_seconds = note_seconds.clone();
+			note_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+			column_match(&note_seconds, hit_seconds, miss_window)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MISS_WINDOWS: [f32; 4] = [0.0, 0.05, 0.18, 0.5];
+
+	/// Checks the matching against the output of the original implementation, which scanned all
+	/// notes for every hit. Expects (deviations, misses, stray taps) for each of [`MISS_WINDOWS`]
+	fn check(note_seconds: &[f32], hit_seconds: &[f32], expected: [(&[f32], u32, u32); 4]) {
+		for (&miss_window, (deviations, num_misses, num_stray_taps)) in
+			MISS_WINDOWS.iter().zip(expected.iter())
+		{
+			assert_eq!(
+				column_match(note_seconds, hit_seconds, miss_window),
+				LaneMatching {
+					deviations: deviations.to_vec(),
+					num_misses: *num_misses,
+					num_stray_taps: *num_stray_taps,
+				},
+				"miss window {}",
+				miss_window
+			);
+		}
+	}
+
+	#[test]
+	fn test_matches_original_implementation() {
+		check(
+			&[1.0, 2.0, 3.0, 4.0],
+			&[0.9, 3.1, 4.1],
+			[
+				(&[], 4, 3),
+				(&[], 4, 3),
+				(&[0.100000024, 0.099999905, 0.099999905], 1, 0),
+				(&[0.100000024, 0.099999905, 0.099999905], 1, 0),
+			],
+		);
+		check(
+			&[0.10, 0.20, 0.30, 0.40],
+			&[0.09, 0.10, 0.30, 0.40],
+			[
+				(&[0.0, 0.0, 0.0], 1, 1),
+				(&[0.0, 0.0, 0.0], 1, 1),
+				(&[0.0, 0.11, 0.0, 0.0], 0, 0),
+				(&[0.0, 0.11, 0.0, 0.0], 0, 0),
+			],
+		);
+		check(
+			&[0.05, 0.10, 0.15, 0.20],
+			&[0.01, 0.02, 0.03, 0.04, 0.05, 0.10, 0.15, 0.20],
+			[(&[0.0; 4], 0, 4); 4],
+		);
+		check(&[0.05, 0.10, 0.15, 0.20], &[], [(&[], 4, 0); 4]);
+		check(&[], &[0.05, 0.10], [(&[], 0, 2); 4]);
+		check(
+			&[1.0, 1.0, 1.0],
+			&[1.0, 1.0, 1.0, 1.0],
+			[(&[0.0; 3], 0, 1); 4],
+		);
+	}
+
+	#[test]
+	fn test_unsorted_notes() {
+		let lane = |note_seconds| crate::NoteAndHitSeconds {
+			note_seconds,
+			hit_seconds: vec![0.9, 3.1, 4.1],
+		};
+		let options = crate::ScoringOptions::default();
+		assert_eq!(
+			MatchingScorer::match_hits::<crate::Wife3>(
+				&lane(vec![4.0, 1.0, 3.0, 2.0]),
+				crate::J4,
+				&options
+			),
+			MatchingScorer::match_hits::<crate::Wife3>(
+				&lane(vec![1.0, 2.0, 3.0, 4.0]),
+				crate::J4,
+				&options
+			),
+		);
+	}
+
+	#[test]
+	fn test_dense_stream_matches_original_implementation() {
+		// Dense stream with misses and mashing, generated deterministically
+		let mut rng_state: u32 = 12345;
+		let mut next_random = || {
+			rng_state = rng_state.wrapping_mul(1103515245).wrapping_add(12345);
+			(rng_state >> 8) % 1000
+		};
+		let note_seconds: Vec<f32> = (0..5000).map(|i| i as f32 * 0.03).collect();
+		let mut hit_seconds = Vec::new();
+		for &second in &note_seconds {
+			if next_random() < 100 {
+				continue; // miss
+			}
+			let deviation = next_random() as f32 / 4000.0 - 0.125;
+			hit_seconds.push(second + deviation);
+			if next_random() < 100 {
+				hit_seconds.push(second + deviation + 0.01); // mash
+			}
+		}
+		hit_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+		// (number of matched hits, sum of deviations, misses, stray taps) of the original
+		// implementation, for each of MISS_WINDOWS
+		let expected = [
+			(33, 0.0, 4967, 4988),
+			(4115, 46.501941146329045, 885, 906),
+			(4701, 100.36296741105616, 299, 320),
+			(4864, 148.57171506620944, 136, 157),
+		];
+		for (&miss_window, &(num_matched, deviation_sum, num_misses, num_stray_taps)) in
+			MISS_WINDOWS.iter().zip(expected.iter())
+		{
+			let matching = column_match(&note_seconds, &hit_seconds, miss_window);
+			assert_eq!(matching.deviations.len(), num_matched);
+			let actual_sum: f64 = matching.deviations.iter().map(|&d| d as f64).sum();
+			assert!((actual_sum - deviation_sum).abs() < 1e-9, "{}", actual_sum);
+			assert_eq!(matching.num_misses, num_misses);
+			assert_eq!(matching.num_stray_taps, num_stray_taps);
 		}
 	}
 }
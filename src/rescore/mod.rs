@@ -1,25 +1,220 @@
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator;
+
 mod matching_scorer;
 pub use matching_scorer::MatchingScorer;
 
 mod naive_scorer;
 pub use naive_scorer::NaiveScorer;
 
+mod optimal_scorer;
+pub use optimal_scorer::OptimalScorer;
+
+pub mod matcher;
+
 /// Result of evaluating a [`ScoringSystem`] on a list of notes and hits
 /// ([`ScoringSystem::evaluate`])
+///
+/// Results of multiple lanes can be combined by adding them up:
+///
+/// ```rust
+/// # use etterna::*;
+/// let lane = |hit_seconds| NoteAndHitSeconds {
+/// 	note_seconds: vec![1.0, 2.0],
+/// 	hit_seconds,
+/// };
+/// let left = NaiveScorer::evaluate::<Wife3>(&lane(vec![1.0, 2.0]), J4);
+/// let right = NaiveScorer::evaluate::<Wife3>(&lane(vec![]), J4);
+///
+/// let combined = left + right;
+/// assert_eq!(combined.num_judged_notes(), 4);
+/// assert_eq!(combined, vec![left, right].into_iter().sum());
+/// assert_eq!(left.to_wifescore(), Wifescore::from_percent(100.0));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScoringResult {
 	wifescore_sum: f32,
 	num_judged_notes: u64,
 }
 
+impl ScoringResult {
+	pub fn new(wifescore_sum: f32, num_judged_notes: u64) -> Self {
+		Self {
+			wifescore_sum,
+			num_judged_notes,
+		}
+	}
+
+	/// Sum of the wife points of all judged notes, including penalties
+	pub fn wifescore_sum(&self) -> f32 {
+		self.wifescore_sum
+	}
+
+	/// Number of notes that the wifescore sum is spread across. Depending on the scoring system,
+	/// this may include stray taps
+	pub fn num_judged_notes(&self) -> u64 {
+		self.num_judged_notes
+	}
+
+	/// Calculates the wifescore. Mine hits and hold drops are not included; add them with
+	/// [`Self::add_penalties`] first.
+	///
+	/// Returns None if there are no judged notes
+	pub fn to_wifescore(&self) -> Option<crate::Wifescore> {
+		if self.num_judged_notes == 0 {
+			return None;
+		}
+		crate::Wifescore::from_proportion(self.wifescore_sum / self.num_judged_notes as f32)
+	}
+
+	/// Adds the penalties for mine hits and hold drops
+	pub fn add_penalties<W: crate::Wife>(&mut self, num_mine_hits: u32, num_hold_drops: u32) {
+		self.wifescore_sum += W::MINE_HIT_WEIGHT * num_mine_hits as f32;
+		self.wifescore_sum += W::HOLD_DROP_WEIGHT * num_hold_drops as f32;
+	}
+}
+
+impl std::ops::Add for ScoringResult {
+	type Output = Self;
+
+	fn add(self, other: Self) -> Self {
+		Self {
+			wifescore_sum: self.wifescore_sum + other.wifescore_sum,
+			num_judged_notes: self.num_judged_notes + other.num_judged_notes,
+		}
+	}
+}
+
+impl std::ops::AddAssign for ScoringResult {
+	fn add_assign(&mut self, other: Self) {
+		*self = *self + other;
+	}
+}
+
+impl std::iter::Sum for ScoringResult {
+	fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+		iter.fold(Self::default(), |a, b| a + b)
+	}
+}
+
+/// Which window a hit must be in to be matched to a note. Hits outside of this window are not
+/// matched and the note counts as a miss (see [`ScoringOptions`])
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MissWindowPolicy {
+	/// The judge's bad window. This is larger than 180ms on J1 to J3
+	#[default]
+	JudgeProportional,
+	/// 180ms regardless of judge, like the game historically did
+	GameAccurate,
+	/// A custom window in seconds
+	Custom(f32),
+}
+
+impl MissWindowPolicy {
+	/// The window in seconds on the given judge
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// assert_eq!(MissWindowPolicy::JudgeProportional.window(J1), 0.27);
+	/// assert_eq!(MissWindowPolicy::GameAccurate.window(J1), 0.18);
+	/// ```
+	pub fn window(&self, judge: &crate::Judge) -> f32 {
+		match *self {
+			Self::JudgeProportional => judge.bad_window,
+			Self::GameAccurate => 0.18,
+			Self::Custom(window) => window,
+		}
+	}
+}
+
+/// Knobs for tweaking the behavior of a [`ScoringSystem`]. The default options match the behavior
+/// of [`rescore`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoringOptions {
+	pub miss_window: MissWindowPolicy,
+}
+
+/// How a [`ScoringSystem`] matched the hits of a single column to its notes
+/// ([`ScoringSystem::match_hits`]). Unless [`ScoringSystem::MATCHING_DEPENDS_ON_JUDGE`] is set, the
+/// matching only depends on the miss window, so it can be evaluated on every judge with the same
+/// miss window.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LaneMatching {
+	/// Absolute deviations of the hits that were matched to a note
+	pub deviations: Vec<f32>,
+	/// Notes without a matched hit
+	pub num_misses: u32,
+	/// Hits that weren't matched to any note and are punished like misses. Scoring systems which
+	/// don't punish stray taps leave this at zero
+	pub num_stray_taps: u32,
+}
+
+impl LaneMatching {
+	/// Applies the given wife algorithm and judge to the matched hits
+	pub fn evaluate<W: crate::Wife>(&self, judge: &crate::Judge) -> ScoringResult {
+		let mut wifescore_sum: crate::util::WifeSum = self
+			.deviations
+			.iter()
+			.map(|&deviation| W::calc_deviation(deviation, judge))
+			.sum();
+
+		// use miss weights for stray taps for now. Maybe it would make for a better system with a
+		// tweaked value - but who cares, my system won't ever get serious adoption anyway /shrug
+		let stray_tap_weight: f32 = W::MISS_WEIGHT;
+
+		// penalize
+		wifescore_sum += W::MISS_WEIGHT * self.num_misses as f32;
+		wifescore_sum += stray_tap_weight * self.num_stray_taps as f32;
+
+		ScoringResult {
+			wifescore_sum: wifescore_sum.get(),
+			num_judged_notes: self.deviations.len() as u64
+				+ self.num_misses as u64
+				+ self.num_stray_taps as u64,
+		}
+	}
+}
+
 /// Trait for a scorer that operates on a single column and evaluates all hits on that column. It
 /// needs the entire list of hits available to it at the same time
+///
+/// Implementors only need to provide [`Self::match_hits`]; everything else has a default.
 pub trait ScoringSystem: Sized {
+	/// Whether [`Self::match_hits`] depends on the wife algorithm and judge, not just on the miss
+	/// window. If false, [`rescore_all_judges`] reuses a matching for all judges with the same
+	/// miss window
+	const MATCHING_DEPENDS_ON_JUDGE: bool = false;
+
+	/// Matches the hits to the notes. Hits further than the miss window
+	/// ([`ScoringOptions::miss_window`] on the given judge) away from a note are never matched to
+	/// it. The lists must be sorted by the hits!
+	fn match_hits<W: crate::Wife>(
+		lane: &crate::NoteAndHitSeconds,
+		judge: &crate::Judge,
+		options: &ScoringOptions,
+	) -> LaneMatching;
+
 	/// Evaluate the scoring system on the given list of notes and hits. The lists must be sorted
 	/// by the hits!
+	fn evaluate_with_options<W: crate::Wife>(
+		lane: &crate::NoteAndHitSeconds,
+		judge: &crate::Judge,
+		options: &ScoringOptions,
+	) -> ScoringResult {
+		Self::match_hits::<W>(lane, judge, options).evaluate::<W>(judge)
+	}
+
+	/// Like [`Self::evaluate_with_options`] with the default options
 	fn evaluate<W: crate::Wife>(
 		lane: &crate::NoteAndHitSeconds,
 		judge: &crate::Judge,
-	) -> ScoringResult;
+	) -> ScoringResult {
+		Self::evaluate_with_options::<W>(lane, judge, &ScoringOptions::default())
+	}
 }
 
 /// Calculates a wifescore from a list of notes per column and hits per column, plus the mine hits
@@ -27,8 +222,13 @@ pub trait ScoringSystem: Sized {
 /// parameters.
 ///
 /// Prefer [`rescore_from_note_hits`] if all you need is a judge conversion.
+///
+/// # Panics
+///
+/// Panics if the lanes contain no notes or aren't sorted. See [`try_rescore`] for a non-panicking
+/// version.
 pub fn rescore<S, W>(
-	lanes: &[crate::NoteAndHitSeconds; 4],
+	lanes: &[crate::NoteAndHitSeconds],
 	num_mine_hits: u32,
 	num_hold_drops: u32,
 	judge: &crate::Judge,
@@ -37,25 +237,205 @@ where
 	S: ScoringSystem,
 	W: crate::Wife,
 {
-	let mut wifescore_sum = 0.0;
-	let mut num_judged_notes = 0;
+	rescore_with_options::<S, W>(
+		lanes,
+		num_mine_hits,
+		num_hold_drops,
+		judge,
+		&ScoringOptions::default(),
+	)
+}
+
+/// Like [`rescore`], but returns an error instead of panicking on empty or unsorted input
+///
+/// ```rust
+/// # use etterna::*;
+/// let lanes: [NoteAndHitSeconds; 4] = Default::default();
+/// assert!(matches!(
+/// 	try_rescore::<MatchingScorer, Wife3>(&lanes, 0, 0, J4),
+/// 	Err(Error::NoNotes)
+/// ));
+/// ```
+pub fn try_rescore<S, W>(
+	lanes: &[crate::NoteAndHitSeconds],
+	num_mine_hits: u32,
+	num_hold_drops: u32,
+	judge: &crate::Judge,
+) -> Result<crate::Wifescore, crate::Error>
+where
+	S: ScoringSystem,
+	W: crate::Wife,
+{
+	try_rescore_with_options::<S, W>(
+		lanes,
+		num_mine_hits,
+		num_hold_drops,
+		judge,
+		&ScoringOptions::default(),
+	)
+}
+
+/// Like [`rescore`], but with custom [`ScoringOptions`]
+///
+/// # Panics
+///
+/// Panics if the lanes contain no notes or aren't sorted. See [`try_rescore_with_options`] for a
+/// non-panicking version.
+pub fn rescore_with_options<S, W>(
+	lanes: &[crate::NoteAndHitSeconds],
+	num_mine_hits: u32,
+	num_hold_drops: u32,
+	judge: &crate::Judge,
+	options: &ScoringOptions,
+) -> crate::Wifescore
+where
+	S: ScoringSystem,
+	W: crate::Wife,
+{
+	try_rescore_with_options::<S, W>(lanes, num_mine_hits, num_hold_drops, judge, options)
+		.unwrap_or_else(|e| panic!("Can't rescore: {}", e))
+}
+
+/// Like [`rescore_with_options`], but returns an error instead of panicking on empty or unsorted
+/// input
+pub fn try_rescore_with_options<S, W>(
+	lanes: &[crate::NoteAndHitSeconds],
+	num_mine_hits: u32,
+	num_hold_drops: u32,
+	judge: &crate::Judge,
+	options: &ScoringOptions,
+) -> Result<crate::Wifescore, crate::Error>
+where
+	S: ScoringSystem,
+	W: crate::Wife,
+{
 	for lane in lanes {
-		assert!(crate::util::is_sorted(&lane.hit_seconds));
-		assert!(crate::util::is_sorted(&lane.note_seconds));
+		check_lane_sorted(lane)?;
+	}
+	let column_results = lanes
+		.iter()
+		.map(|lane| S::evaluate_with_options::<W>(lane, judge, options));
+	combine_column_results::<W>(column_results, num_mine_hits, num_hold_drops)
+}
 
-		let column_scoring_result = S::evaluate::<W>(lane, judge);
+fn check_lane_sorted(lane: &crate::NoteAndHitSeconds) -> Result<(), crate::Error> {
+	if !crate::util::is_sorted(&lane.hit_seconds) {
+		return Err(crate::Error::Unsorted("Hit seconds"));
+	}
+	if !crate::util::is_sorted(&lane.note_seconds) {
+		return Err(crate::Error::Unsorted("Note seconds"));
+	}
+	Ok(())
+}
 
+fn combine_column_results<W: crate::Wife>(
+	column_results: impl Iterator<Item = ScoringResult>,
+	num_mine_hits: u32,
+	num_hold_drops: u32,
+) -> Result<crate::Wifescore, crate::Error> {
+	let mut wifescore_sum = crate::util::WifeSum::default();
+	let mut num_judged_notes = 0;
+	for column_scoring_result in column_results {
 		wifescore_sum += column_scoring_result.wifescore_sum;
 		num_judged_notes += column_scoring_result.num_judged_notes;
 	}
+	if num_judged_notes == 0 {
+		return Err(crate::Error::NoNotes);
+	}
 
 	wifescore_sum += W::MINE_HIT_WEIGHT * num_mine_hits as f32;
 	wifescore_sum += W::HOLD_DROP_WEIGHT * num_hold_drops as f32;
 
-	let wifescore = wifescore_sum / num_judged_notes as f32;
-	crate::Wifescore::from_proportion(wifescore).expect(
-		"Invalid wifescore was generated. Maybe the given notes and hits vectors were empty",
-	)
+	let wifescore = wifescore_sum.get() / num_judged_notes as f32;
+	Ok(crate::Wifescore::from_proportion(wifescore)
+		.expect("a wifescore with judged notes and finite weights is always valid"))
+}
+
+/// Calculates the wifescore on every built-in judge, from J1 to J9, like [`rescore_with_options`]
+/// on each judge.
+///
+/// Judges with the same miss window share the note/hit matching, so this is much faster than
+/// rescoring nine times.
+///
+/// ```rust
+/// # use etterna::*;
+/// let lane = NoteAndHitSeconds {
+/// 	note_seconds: vec![1.0, 2.0, 3.0],
+/// 	hit_seconds: vec![1.01, 2.03, 3.2],
+/// };
+/// let lanes = [lane, Default::default(), Default::default(), Default::default()];
+///
+/// let options = ScoringOptions::default();
+/// let wifescores = rescore_all_judges::<MatchingScorer, Wife3>(&lanes, 0, 0, &options);
+/// assert_eq!(wifescores[3], rescore::<MatchingScorer, Wife3>(&lanes, 0, 0, J4));
+/// assert!(wifescores[0] > wifescores[8]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the lanes contain no notes or aren't sorted. See [`try_rescore_all_judges`] for a
+/// non-panicking version.
+pub fn rescore_all_judges<S, W>(
+	lanes: &[crate::NoteAndHitSeconds],
+	num_mine_hits: u32,
+	num_hold_drops: u32,
+	options: &ScoringOptions,
+) -> [crate::Wifescore; 9]
+where
+	S: ScoringSystem,
+	W: crate::Wife,
+{
+	try_rescore_all_judges::<S, W>(lanes, num_mine_hits, num_hold_drops, options)
+		.unwrap_or_else(|e| panic!("Can't rescore: {}", e))
+}
+
+/// Like [`rescore_all_judges`], but returns an error instead of panicking on empty or unsorted input
+pub fn try_rescore_all_judges<S, W>(
+	lanes: &[crate::NoteAndHitSeconds],
+	num_mine_hits: u32,
+	num_hold_drops: u32,
+	options: &ScoringOptions,
+) -> Result<[crate::Wifescore; 9], crate::Error>
+where
+	S: ScoringSystem,
+	W: crate::Wife,
+{
+	for lane in lanes {
+		check_lane_sorted(lane)?;
+	}
+
+	let mut matchings: Vec<(f32, Vec<LaneMatching>)> = Vec::new();
+	let mut wifescores = [crate::Wifescore::default(); 9];
+	for (wifescore, judge) in wifescores.iter_mut().zip(crate::Judge::list()) {
+		if S::MATCHING_DEPENDS_ON_JUDGE {
+			let column_results = lanes
+				.iter()
+				.map(|lane| S::evaluate_with_options::<W>(lane, judge, options));
+			*wifescore =
+				combine_column_results::<W>(column_results, num_mine_hits, num_hold_drops)?;
+			continue;
+		}
+
+		let miss_window = options.miss_window.window(judge);
+		let lane_matchings = match matchings.iter().position(|&(w, _)| w == miss_window) {
+			Some(i) => &matchings[i].1,
+			None => {
+				let lane_matchings = lanes
+					.iter()
+					.map(|lane| S::match_hits::<W>(lane, judge, options))
+					.collect();
+				matchings.push((miss_window, lane_matchings));
+				// UNWRAP: we just pushed an element
+				&matchings.last().unwrap().1
+			}
+		};
+
+		let column_results = lane_matchings
+			.iter()
+			.map(|lane_matching| lane_matching.evaluate::<W>(judge));
+		*wifescore = combine_column_results::<W>(column_results, num_mine_hits, num_hold_drops)?;
+	}
+	Ok(wifescores)
 }
 
 /// Calculate a wifescore from a replay's note hits, mine hits and hold drops.
@@ -79,6 +459,38 @@ where
 	W::apply(note_hits, num_mine_hits, num_hold_drops, judge)
 }
 
+/// Rescores many replays at once with the given judge, like [`rescore_from_note_hits`] on each
+/// replay. Replays are processed in parallel if the `parallel` feature is enabled.
+///
+/// The returned vector is in the same order as `replays`
+///
+/// ```rust
+/// # use etterna::*;
+/// let replays = [
+/// 	parse_replay_v2_fast(b"0 0.01 0\n48 0.02 1\n").unwrap(),
+/// 	parse_replay_v2_fast(b"0 0.05 0\n48 0.02 1\n").unwrap(),
+/// ];
+/// let wifescores = rescore_many::<Wife3>(&replays, J4);
+/// assert!(wifescores[0].unwrap() > wifescores[1].unwrap());
+/// ```
+pub fn rescore_many<W: crate::Wife>(
+	replays: &[crate::ReplayV2Fast],
+	judge: &crate::Judge,
+) -> Vec<Option<crate::Wifescore>> {
+	use crate::SimpleReplay as _;
+
+	crate::par_iter_maybe(replays)
+		.map(|replay| {
+			rescore_from_note_hits::<W, _>(
+				replay.iter_hits(),
+				replay.num_mine_hits,
+				replay.num_hold_drops(),
+				judge,
+			)
+		})
+		.collect()
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -95,11 +507,11 @@ mod tests {
 			|note_and_hit_seconds, target_naive_wifescore: f32, target_matching_wifescore: f32| {
 				let naive_wifescore = {
 					let result = NaiveScorer::evaluate::<Wife>(&note_and_hit_seconds, judge);
-					result.wifescore_sum / result.num_judged_notes as f32
+					result.wifescore_sum() / result.num_judged_notes() as f32
 				};
 				let matching_wifescore = {
 					let result = MatchingScorer::evaluate::<Wife>(&note_and_hit_seconds, judge);
-					result.wifescore_sum / result.num_judged_notes as f32
+					result.wifescore_sum() / result.num_judged_notes() as f32
 				};
 
 				println!("{} == {} ?", naive_wifescore, target_naive_wifescore);
@@ -164,4 +576,97 @@ mod tests {
 			wife(1.0),
 		);
 	}
+
+	#[test]
+	fn test_miss_window_policy() {
+		let lane = crate::NoteAndHitSeconds {
+			note_seconds: vec![1.0, 2.0],
+			hit_seconds: vec![1.0, 2.2],
+		};
+		let lanes = [
+			lane,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		];
+		let score = |policy| {
+			let options = ScoringOptions {
+				miss_window: policy,
+			};
+			rescore_with_options::<NaiveScorer, crate::Wife3>(&lanes, 0, 0, crate::J1, &options)
+		};
+
+		assert_eq!(
+			score(MissWindowPolicy::JudgeProportional),
+			rescore::<NaiveScorer, crate::Wife3>(&lanes, 0, 0, crate::J1)
+		);
+		assert!(score(MissWindowPolicy::JudgeProportional) > score(MissWindowPolicy::GameAccurate));
+		assert_eq!(
+			score(MissWindowPolicy::GameAccurate),
+			score(MissWindowPolicy::Custom(0.1))
+		);
+	}
+
+	#[test]
+	fn test_rescore_all_judges() {
+		let lane = |offset: f32| crate::NoteAndHitSeconds {
+			note_seconds: vec![1.0, 2.0, 3.0, 4.0],
+			hit_seconds: vec![0.7, 1.0 + offset, 2.25, 3.0 - offset, 3.5],
+		};
+		let lanes = [lane(0.01), lane(0.05), lane(0.1), lane(0.2)];
+
+		fn check<S: ScoringSystem>(
+			lanes: &[crate::NoteAndHitSeconds; 4],
+			options: &ScoringOptions,
+		) {
+			let wifescores = rescore_all_judges::<S, crate::Wife3>(lanes, 1, 2, options);
+			for (&wifescore, judge) in wifescores.iter().zip(crate::Judge::list()) {
+				assert_eq!(
+					wifescore,
+					rescore_with_options::<S, crate::Wife3>(lanes, 1, 2, judge, options),
+					"{}",
+					judge.name
+				);
+			}
+		}
+		for &miss_window in &[
+			MissWindowPolicy::JudgeProportional,
+			MissWindowPolicy::GameAccurate,
+			MissWindowPolicy::Custom(0.1),
+		] {
+			let options = ScoringOptions { miss_window };
+			check::<NaiveScorer>(&lanes, &options);
+			check::<MatchingScorer>(&lanes, &options);
+			check::<OptimalScorer>(&lanes, &options);
+		}
+	}
+
+	#[test]
+	fn test_try_rescore() {
+		let empty: [crate::NoteAndHitSeconds; 4] = Default::default();
+		assert!(matches!(
+			try_rescore::<MatchingScorer, crate::Wife3>(&empty, 3, 0, crate::J4),
+			Err(crate::Error::NoNotes)
+		));
+		let options = ScoringOptions::default();
+		assert!(
+			try_rescore_all_judges::<NaiveScorer, crate::Wife3>(&empty, 0, 0, &options).is_err()
+		);
+
+		let mut lanes = empty;
+		lanes[0] = crate::NoteAndHitSeconds {
+			note_seconds: vec![1.0, 2.0],
+			hit_seconds: vec![2.0, 1.0],
+		};
+		assert!(matches!(
+			try_rescore::<MatchingScorer, crate::Wife3>(&lanes, 0, 0, crate::J4),
+			Err(crate::Error::Unsorted(_))
+		));
+
+		lanes[0].hit_seconds.reverse();
+		assert_eq!(
+			try_rescore::<MatchingScorer, crate::Wife3>(&lanes, 0, 0, crate::J4).unwrap(),
+			rescore::<MatchingScorer, crate::Wife3>(&lanes, 0, 0, crate::J4)
+		);
+	}
 }
@@ -0,0 +1,374 @@
+use super::{LaneMatching, ScoringSystem};
+
+/// Splits the notes and hits of a lane into independent groups, where no note of one group is
+/// within the miss window of a hit of another group. Returns (note range, hit range) per group
+fn independent_groups(
+	note_seconds: &[f32],
+	hit_seconds: &[f32],
+	miss_window: f32,
+) -> Vec<(std::ops::Range<usize>, std::ops::Range<usize>)> {
+	let is_in_window = |note: Option<&f32>, hit: Option<&f32>| match (note, hit) {
+		(Some(note), Some(hit)) => (hit - note).abs() <= miss_window,
+		_ => false,
+	};
+
+	let mut groups = Vec::new();
+	let (mut note_start, mut hit_start) = (0, 0);
+	let (mut note_end, mut hit_end) = (0, 0);
+	while note_end < note_seconds.len() || hit_end < hit_seconds.len() {
+		let next_is_note = match (note_seconds.get(note_end), hit_seconds.get(hit_end)) {
+			(Some(note), Some(hit)) => note <= hit,
+			(Some(_), None) => true,
+			(None, _) => false,
+		};
+		if next_is_note {
+			note_end += 1;
+		} else {
+			hit_end += 1;
+		}
+
+		// Everything consumed so far is independent of the rest if the latest note and hit aren't
+		// within the window of the earliest remaining hit and note. Those are the closest pairs
+		// across the boundary, because both lists are sorted
+		let is_boundary = !is_in_window(
+			note_end.checked_sub(1).map(|i| &note_seconds[i]),
+			hit_seconds.get(hit_end),
+		) && !is_in_window(
+			note_seconds.get(note_end),
+			hit_end.checked_sub(1).map(|i| &hit_seconds[i]),
+		);
+		if is_boundary {
+			groups.push((note_start..note_end, hit_start..hit_end));
+			note_start = note_end;
+			hit_start = hit_end;
+		}
+	}
+	groups
+}
+
+/// Solves the assignment problem on a `num_rows`x`num_columns` cost matrix with the Hungarian
+/// algorithm, in O(num_rows² * num_columns). There must not be more rows than columns.
+///
+/// Returns the assigned column for each row, such that the sum of costs is minimal
+fn hungarian(num_rows: usize, num_columns: usize, cost: &[f64]) -> Vec<usize> {
+	debug_assert!(num_rows <= num_columns);
+
+	// Potentials and assignments are 1-indexed, with index 0 acting as a virtual column
+	let mut row_potentials = vec![0.0; num_rows + 1];
+	let mut column_potentials = vec![0.0; num_columns + 1];
+	let mut column_rows = vec![0; num_columns + 1];
+	let mut way = vec![0; num_columns + 1];
+	for row in 1..=num_rows {
+		column_rows[0] = row;
+		let mut column = 0;
+		let mut min_slack = vec![f64::INFINITY; num_columns + 1];
+		let mut is_used = vec![false; num_columns + 1];
+		loop {
+			is_used[column] = true;
+			let current_row = column_rows[column];
+			let mut delta = f64::INFINITY;
+			let mut next_column = 0;
+			for j in 1..=num_columns {
+				if is_used[j] {
+					continue;
+				}
+				let slack = cost[(current_row - 1) * num_columns + (j - 1)]
+					- row_potentials[current_row]
+					- column_potentials[j];
+				if slack < min_slack[j] {
+					min_slack[j] = slack;
+					way[j] = column;
+				}
+				if min_slack[j] < delta {
+					delta = min_slack[j];
+					next_column = j;
+				}
+			}
+			for j in 0..=num_columns {
+				if is_used[j] {
+					row_potentials[column_rows[j]] += delta;
+					column_potentials[j] -= delta;
+				} else {
+					min_slack[j] -= delta;
+				}
+			}
+			column = next_column;
+			if column_rows[column] == 0 {
+				break;
+			}
+		}
+		// Flip the augmenting path
+		while column != 0 {
+			let prev_column = way[column];
+			column_rows[column] = column_rows[prev_column];
+			column = prev_column;
+		}
+	}
+
+	let mut row_columns = vec![0; num_rows];
+	for column in 1..=num_columns {
+		if column_rows[column] != 0 {
+			row_columns[column_rows[column] - 1] = column - 1;
+		}
+	}
+	row_columns
+}
+
+/// Finds the assignment of hits to notes that maximizes the total wife points, and returns the
+/// absolute deviations of the matched pairs. Groups above [`OptimalScorer::MAX_GROUP_SIZE`] are
+/// matched like [`crate::NaiveScorer`] instead
+fn optimal_deviations<W: crate::Wife>(
+	note_seconds: &[f32],
+	hit_seconds: &[f32],
+	judge: &crate::Judge,
+	miss_window: f32,
+) -> Vec<f32> {
+	let mut deviations = Vec::new();
+	for (notes, hits) in independent_groups(note_seconds, hit_seconds, miss_window) {
+		let notes = &note_seconds[notes];
+		let hits = &hit_seconds[hits];
+		if notes.is_empty() || hits.is_empty() {
+			continue;
+		}
+		if notes.len().max(hits.len()) > OptimalScorer::MAX_GROUP_SIZE {
+			let lane = crate::NoteAndHitSeconds {
+				note_seconds: notes.to_vec(),
+				hit_seconds: hits.to_vec(),
+			};
+			let claims = super::matcher::claim_match(
+				&lane,
+				miss_window,
+				super::matcher::ClaimPolicy::Nearest,
+			);
+			deviations.extend(claims.claims.iter().map(|claim| claim.deviation.abs()));
+			continue;
+		}
+
+		// The Hungarian algorithm needs at least as many columns as rows
+		let (rows, columns) = if hits.len() <= notes.len() {
+			(hits, notes)
+		} else {
+			(notes, hits)
+		};
+		let deviation = |row: usize, column: usize| (rows[row] - columns[column]).abs();
+		// Matching a pair gains its wife points over the miss that the note would be otherwise.
+		// Pairs outside the miss window have zero cost, so assigning them is equivalent to not
+		// matching
+		let mut cost = Vec::with_capacity(rows.len() * columns.len());
+		for row in 0..rows.len() {
+			for column in 0..columns.len() {
+				let deviation = deviation(row, column);
+				cost.push(if deviation <= miss_window {
+					let gain = W::calc_deviation(deviation, judge) - W::MISS_WEIGHT;
+					-(gain.max(0.0) as f64)
+				} else {
+					0.0
+				});
+			}
+		}
+
+		for (row, column) in hungarian(rows.len(), columns.len(), &cost)
+			.into_iter()
+			.enumerate()
+		{
+			if cost[row * columns.len() + column] < 0.0 {
+				deviations.push(deviation(row, column));
+			}
+		}
+	}
+	deviations
+}
+
+/// Scoring system that assigns hits to notes such that the total wife points are maximal. Each
+/// note can be matched to at most one hit within the miss window, and vice versa.
+///
+/// This is the theoretical upper bound of a replay under fair matching: what's the best this
+/// replay could have scored. Any matching done by [`crate::NaiveScorer`] is a candidate, so the
+/// result is never worse than its result. Like [`crate::NaiveScorer`], stray taps are not punished.
+///
+/// The matching depends on the wife algorithm and judge: [`ScoringSystem::match_hits`] optimizes
+/// for the ones it's given.
+///
+/// # Cost
+///
+/// The assignment is solved exactly with the Hungarian algorithm, per group of notes and hits that
+/// are connected by overlapping miss windows. A group of n notes and hits needs an n² cost matrix
+/// and O(n³) time. That's fast for typical files, but long dense streams can form large groups;
+/// this scorer is meant for analysis, not bulk rescoring.
+///
+/// To keep the cost bounded, groups with more than [`Self::MAX_GROUP_SIZE`] notes or hits are
+/// matched like [`crate::NaiveScorer`] instead. The result is then not guaranteed to be optimal.
+///
+/// ```rust
+/// # use etterna::*;
+/// let lane = NoteAndHitSeconds {
+/// 	note_seconds: vec![0.10, 0.20],
+/// 	hit_seconds: vec![0.16, 0.30],
+/// };
+/// // NaiveScorer greedily matches the first hit to the closer second note, so the first note is
+/// // missed
+/// let naive = NaiveScorer::evaluate::<Wife3>(&lane, J4);
+/// let optimal = OptimalScorer::evaluate::<Wife3>(&lane, J4);
+/// assert_eq!(optimal.num_judged_notes(), 2);
+/// assert!(optimal.wifescore_sum() > naive.wifescore_sum());
+/// ```
+pub struct OptimalScorer;
+
+impl OptimalScorer {
+	/// Largest number of notes or hits in a group that is still matched optimally. At this size,
+	/// the cost matrix takes 2 MB
+	pub const MAX_GROUP_SIZE: usize = 512;
+}
+
+impl ScoringSystem for OptimalScorer {
+	const MATCHING_DEPENDS_ON_JUDGE: bool = true;
+
+	fn match_hits<W: crate::Wife>(
+		lane: &crate::NoteAndHitSeconds,
+		judge: &crate::Judge,
+		options: &super::ScoringOptions,
+	) -> LaneMatching {
+		let crate::NoteAndHitSeconds {
+			note_seconds,
+			hit_seconds,
+		} = lane;
+
+		assert!(crate::util::is_sorted(hit_seconds));
+		assert!(crate::util::is_sorted(note_seconds));
+
+		let miss_window = options.miss_window.window(judge);
+		let deviations = optimal_deviations::<W>(note_seconds, hit_seconds, judge, miss_window);
+		LaneMatching {
+			num_misses: (note_seconds.len() - deviations.len()) as u32,
+			deviations,
+			num_stray_taps: 0,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Tries every possible matching
+	fn brute_force_best<W: crate::Wife>(
+		notes: &[f32],
+		hits: &[f32],
+		is_note_used: &mut Vec<bool>,
+		judge: &crate::Judge,
+		miss_window: f32,
+	) -> f32 {
+		let (hit, remaining_hits) = match hits.split_first() {
+			Some(x) => x,
+			None => {
+				let num_misses = is_note_used.iter().filter(|&&used| !used).count();
+				return W::MISS_WEIGHT * num_misses as f32;
+			}
+		};
+
+		// leave this hit unmatched
+		let mut best =
+			brute_force_best::<W>(notes, remaining_hits, is_note_used, judge, miss_window);
+		for (i, note) in notes.iter().enumerate() {
+			let deviation = (hit - note).abs();
+			if is_note_used[i] || deviation > miss_window {
+				continue;
+			}
+			is_note_used[i] = true;
+			let points = W::calc_deviation(deviation, judge)
+				+ brute_force_best::<W>(notes, remaining_hits, is_note_used, judge, miss_window);
+			is_note_used[i] = false;
+			best = best.max(points);
+		}
+		best
+	}
+
+	#[test]
+	fn test_optimal_scorer() {
+		let mut rng_state: u32 = 12345;
+		let mut next_random = || {
+			rng_state = rng_state.wrapping_mul(1103515245).wrapping_add(12345);
+			(rng_state >> 8) % 1000
+		};
+
+		for _ in 0..200 {
+			let num_notes = next_random() as usize % 6;
+			let num_hits = next_random() as usize % 6;
+			let mut note_seconds: Vec<f32> = (0..num_notes)
+				.map(|_| next_random() as f32 / 2000.0)
+				.collect();
+			let mut hit_seconds: Vec<f32> = (0..num_hits)
+				.map(|_| next_random() as f32 / 2000.0)
+				.collect();
+			note_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+			hit_seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+			let lane = crate::NoteAndHitSeconds {
+				note_seconds,
+				hit_seconds,
+			};
+
+			for &judge in &[crate::J4, crate::J9] {
+				let optimal = OptimalScorer::evaluate::<crate::Wife3>(&lane, judge);
+				let expected = brute_force_best::<crate::Wife3>(
+					&lane.note_seconds,
+					&lane.hit_seconds,
+					&mut vec![false; num_notes],
+					judge,
+					judge.bad_window,
+				);
+				assert!(
+					(optimal.wifescore_sum() - expected).abs() < 0.0001,
+					"{:?}: {} != {}",
+					lane,
+					optimal.wifescore_sum(),
+					expected
+				);
+				assert_eq!(optimal.num_judged_notes(), num_notes as u64);
+
+				let naive = super::super::NaiveScorer::evaluate::<crate::Wife3>(&lane, judge);
+				assert!(optimal.wifescore_sum() >= naive.wifescore_sum() - 0.0001);
+			}
+		}
+	}
+
+	#[test]
+	fn test_large_group_fallback() {
+		// The example from the OptimalScorer docs, repeated close enough to form a single group.
+		// NaiveScorer misses the first note of each repetition
+		let lane = |num_repetitions: usize| {
+			let mut lane = crate::NoteAndHitSeconds::default();
+			for i in 0..num_repetitions {
+				let offset = i as f32 * 0.35;
+				lane.note_seconds.extend(&[offset + 0.10, offset + 0.20]);
+				lane.hit_seconds.extend(&[offset + 0.16, offset + 0.30]);
+			}
+			lane
+		};
+		let optimal = |lane| OptimalScorer::evaluate::<crate::Wife3>(lane, crate::J4);
+		let naive = |lane| super::super::NaiveScorer::evaluate::<crate::Wife3>(lane, crate::J4);
+
+		let small = lane(10);
+		assert!(optimal(&small).wifescore_sum() > naive(&small).wifescore_sum());
+
+		let large = lane(OptimalScorer::MAX_GROUP_SIZE / 2 + 1);
+		assert_eq!(
+			independent_groups(
+				&large.note_seconds,
+				&large.hit_seconds,
+				crate::J4.bad_window
+			)
+			.len(),
+			1
+		);
+		assert_eq!(optimal(&large), naive(&large));
+	}
+
+	#[test]
+	fn test_independent_groups() {
+		let groups = independent_groups(&[1.0, 1.1, 2.0, 3.0], &[0.95, 1.2, 1.25, 3.5], 0.18);
+		assert_eq!(
+			groups,
+			vec![(0..2, 0..3), (2..3, 3..3), (3..4, 3..3), (4..4, 3..4)]
+		);
+	}
+}
@@ -0,0 +1,349 @@
+use thiserror::Error;
+
+/// Reason why a [`Score`] is inconsistent. See [`Score::validate`]
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ScoreValidationError {
+	#[error("Score has no tap judgements")]
+	NoJudgements,
+	#[error("Wifescore {wifescore} is higher than the judgements allow (at most {max})")]
+	WifescoreTooHigh {
+		wifescore: crate::Wifescore,
+		max: crate::Wifescore,
+	},
+	#[error("SSR value {0} is negative or not finite")]
+	InvalidSsr(f32),
+}
+
+/// A single score of a player, with everything that the rest of this crate needs to work with it.
+///
+/// The timestamp type is generic, so that any time representation can be used. For use with
+/// [`crate::find_sessions`], it needs to implement [`crate::Timestamp`].
+///
+/// ```rust
+/// # use etterna::*;
+/// let chartkey = Chartkey::new("X0123456789abcdef0123456789abcdef01234567".into()).unwrap();
+/// let judgements = FullJudgements { marvelouses: 95, perfects: 5, ..Default::default() };
+/// let score = Score::new(
+/// 	chartkey,
+/// 	Rate::from_f32(1.2).unwrap(),
+/// 	Wifescore::from_percent(98.5).unwrap(),
+/// 	J4.clone(),
+/// 	judgements,
+/// 	1_600_000_000_u64,
+/// )
+/// .with_ssr(Skillsets7 { stream: 25.0, ..Default::default() });
+///
+/// assert!(score.validate::<Wife3>().is_ok());
+/// assert_eq!(score.overall_ssr(), Some(25.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Score<T> {
+	pub chartkey: crate::Chartkey,
+	pub rate: crate::Rate,
+	pub wifescore: crate::Wifescore,
+	/// The judge that the wifescore and judgements refer to
+	pub judge: crate::Judge,
+	pub judgements: crate::FullJudgements,
+	/// The score-specific rating. None if it hasn't been calculated, or if the score isn't eligible
+	pub ssr: Option<crate::Skillsets7>,
+	pub timestamp: T,
+}
+
+impl<T> Score<T> {
+	/// Creates a score without an SSR. See [`Self::with_ssr`]
+	pub fn new(
+		chartkey: crate::Chartkey,
+		rate: crate::Rate,
+		wifescore: crate::Wifescore,
+		judge: crate::Judge,
+		judgements: crate::FullJudgements,
+		timestamp: T,
+	) -> Self {
+		Self {
+			chartkey,
+			rate,
+			wifescore,
+			judge,
+			judgements,
+			ssr: None,
+			timestamp,
+		}
+	}
+
+	pub fn with_ssr(self, ssr: crate::Skillsets7) -> Self {
+		Self {
+			ssr: Some(ssr),
+			..self
+		}
+	}
+
+	/// Overall SSR, calculated with the current (0.70+) algorithm. None if there's no SSR
+	pub fn overall_ssr(&self) -> Option<f32> {
+		Some(self.ssr.as_ref()?.calc_ssr_overall().overall)
+	}
+
+	/// The SSR including overall, e.g. for [`crate::SessionScore`]. None if there's no SSR
+	pub fn ssr_with_overall(&self) -> Option<crate::Skillsets8> {
+		Some(self.ssr.as_ref()?.calc_ssr_overall())
+	}
+
+	/// Checks that the score is internally consistent: it must have at least one tap judgement,
+	/// the wifescore must be reachable with the judgements using the wifescore algorithm `W`, and
+	/// the SSR values must be finite and non-negative.
+	///
+	/// The check for the wifescore is lenient: it only verifies the upper bound, since mine hits
+	/// and hold drops can bring a wifescore arbitrarily low.
+	pub fn validate<W: crate::Wife>(&self) -> Result<(), ScoreValidationError> {
+		let taps = crate::TapJudgements::from(self.judgements.clone());
+		let (_, max) = taps
+			.wifescore_bounds::<W>(&self.judge)
+			.ok_or(ScoreValidationError::NoJudgements)?;
+		// Leave some room for floating point inaccuracies in the stored wifescore
+		if self.wifescore.as_proportion() > max.as_proportion() + 0.00001 {
+			return Err(ScoreValidationError::WifescoreTooHigh {
+				wifescore: self.wifescore,
+				max,
+			});
+		}
+
+		if let Some(ssr) = &self.ssr {
+			for skillset in crate::Skillset7::iter() {
+				let value = ssr.get(skillset);
+				if !value.is_finite() || value < 0.0 {
+					return Err(ScoreValidationError::InvalidSsr(value));
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Reason why a string couldn't be parsed into a [`ScoreIdentity`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ScoreIdentityParseError {
+	#[error("Expected `chartkey@rate` or `chartkey@rate@judge`")]
+	InvalidFormat,
+	#[error("Invalid chartkey: {0}")]
+	InvalidChartkey(crate::KeyParseError),
+	#[error("Invalid rate")]
+	InvalidRate,
+	#[error("Unknown judge (must be J1 to J9)")]
+	InvalidJudge,
+}
+
+/// Identifies "a chart at a rate", optionally on a specific judge, for keying leaderboards and
+/// score databases.
+///
+/// The canonical string form is `chartkey@rate`, or `chartkey@rate@judge` with a judge, e.g.
+/// `X0123456789abcdef0123456789abcdef01234567@1.10x@J4`. [`Display`](std::fmt::Display) produces
+/// it and [`FromStr`](std::str::FromStr) parses it.
+///
+/// Identities are ordered by chartkey, then rate, then judge, with "no judge" first.
+///
+/// ```rust
+/// # use etterna::*;
+/// let chartkey = Chartkey::new("X0123456789abcdef0123456789abcdef01234567".into()).unwrap();
+/// let identity = ScoreIdentity::new(chartkey, Rate::from_f32(1.1).unwrap());
+/// assert_eq!(identity.to_string(), "X0123456789abcdef0123456789abcdef01234567@1.10x");
+///
+/// let on_j7 = identity.clone().with_judge(J7).unwrap();
+/// assert_eq!(on_j7.to_string(), "X0123456789abcdef0123456789abcdef01234567@1.10x@J7");
+/// assert_eq!(on_j7.to_string().parse(), Ok(on_j7.clone()));
+/// assert!(identity < on_j7);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreIdentity {
+	pub chartkey: crate::Chartkey,
+	pub rate: crate::Rate,
+	/// Index into [`crate::Judge::list`]. Stored as an index, because judges themselves can't be
+	/// hashed or ordered
+	judge_index: Option<u8>,
+}
+
+impl ScoreIdentity {
+	/// Creates an identity that doesn't distinguish between judges
+	pub fn new(chartkey: crate::Chartkey, rate: crate::Rate) -> Self {
+		Self {
+			chartkey,
+			rate,
+			judge_index: None,
+		}
+	}
+
+	/// Restricts this identity to the given judge. Returns None if the judge isn't one of the
+	/// built-in judges J1 to J9
+	pub fn with_judge(self, judge: &crate::Judge) -> Option<Self> {
+		let judge_index = crate::Judge::list()
+			.iter()
+			.position(|&builtin| builtin == judge)?;
+		Some(Self {
+			judge_index: Some(judge_index as u8),
+			..self
+		})
+	}
+
+	/// Removes the judge from this identity, so that it covers scores on all judges
+	pub fn without_judge(self) -> Self {
+		Self {
+			judge_index: None,
+			..self
+		}
+	}
+
+	/// The judge of this identity, or None if it doesn't distinguish between judges
+	pub fn judge(&self) -> Option<&'static crate::Judge> {
+		Some(crate::Judge::list()[self.judge_index? as usize])
+	}
+}
+
+impl<T> Score<T> {
+	/// The score's chart and rate, without the judge
+	pub fn identity(&self) -> ScoreIdentity {
+		ScoreIdentity::new(self.chartkey.clone(), self.rate)
+	}
+}
+
+impl std::fmt::Display for ScoreIdentity {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}@{}", self.chartkey, self.rate)?;
+		if let Some(judge) = self.judge() {
+			write!(f, "@{}", judge.name)?;
+		}
+		Ok(())
+	}
+}
+
+impl std::str::FromStr for ScoreIdentity {
+	type Err = ScoreIdentityParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.trim().split('@');
+		let (chartkey, rate) = match (parts.next(), parts.next()) {
+			(Some(chartkey), Some(rate)) => (chartkey, rate),
+			_ => return Err(ScoreIdentityParseError::InvalidFormat),
+		};
+		let judge = parts.next();
+		if parts.next().is_some() {
+			return Err(ScoreIdentityParseError::InvalidFormat);
+		}
+
+		let identity = Self::new(
+			chartkey
+				.parse()
+				.map_err(ScoreIdentityParseError::InvalidChartkey)?,
+			rate.parse()
+				.map_err(|_| ScoreIdentityParseError::InvalidRate)?,
+		);
+		match judge {
+			Some(judge) => crate::Judge::from_name(judge)
+				.and_then(|judge| identity.with_judge(judge))
+				.ok_or(ScoreIdentityParseError::InvalidJudge),
+			None => Ok(identity),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_validate() {
+		let chartkey =
+			crate::Chartkey::new("X0123456789abcdef0123456789abcdef01234567".into()).unwrap();
+		let judgements = crate::FullJudgements {
+			marvelouses: 50,
+			misses: 50,
+			..Default::default()
+		};
+		let score = |percent| {
+			Score::new(
+				chartkey.clone(),
+				crate::Rate::from_f32(1.0).unwrap(),
+				crate::Wifescore::from_percent(percent).unwrap(),
+				crate::J4.clone(),
+				judgements.clone(),
+				(),
+			)
+		};
+
+		assert!(score(-225.0).validate::<crate::Wife3>().is_ok());
+		assert!(matches!(
+			score(50.0).validate::<crate::Wife3>(),
+			Err(ScoreValidationError::WifescoreTooHigh { .. })
+		));
+
+		let no_judgements = Score {
+			judgements: Default::default(),
+			..score(0.0)
+		};
+		assert_eq!(
+			no_judgements.validate::<crate::Wife3>(),
+			Err(ScoreValidationError::NoJudgements)
+		);
+
+		let invalid_ssr = score(-225.0).with_ssr(crate::Skillsets7 {
+			stamina: f32::NAN,
+			..Default::default()
+		});
+		assert!(matches!(
+			invalid_ssr.validate::<crate::Wife3>(),
+			Err(ScoreValidationError::InvalidSsr(_))
+		));
+	}
+
+	#[test]
+	fn test_score_identity() {
+		let chartkey = "X0123456789abcdef0123456789abcdef01234567";
+		let identity: ScoreIdentity = format!("{}@0.85x", chartkey).parse().unwrap();
+		assert_eq!(identity.rate, crate::Rate::from_f32(0.85).unwrap());
+		assert_eq!(identity.judge(), None);
+		assert_eq!(
+			format!("{}@1.0@j4", chartkey).parse::<ScoreIdentity>(),
+			Ok(
+				ScoreIdentity::new(identity.chartkey.clone(), crate::Rate::default())
+					.with_judge(crate::J4)
+					.unwrap()
+			)
+		);
+
+		let parse = |s: &str| s.parse::<ScoreIdentity>().unwrap_err();
+		assert_eq!(parse(chartkey), ScoreIdentityParseError::InvalidFormat);
+		assert_eq!(
+			parse("X123@1.0x"),
+			ScoreIdentityParseError::InvalidChartkey(crate::KeyParseError::InvalidLength(4))
+		);
+		assert_eq!(
+			parse(&format!("{}@fast", chartkey)),
+			ScoreIdentityParseError::InvalidRate
+		);
+		assert_eq!(
+			parse(&format!("{}@1.0x@J10", chartkey)),
+			ScoreIdentityParseError::InvalidJudge
+		);
+		assert_eq!(
+			parse(&format!("{}@1.0x@J4@J5", chartkey)),
+			ScoreIdentityParseError::InvalidFormat
+		);
+
+		// custom judges can't be part of an identity
+		let custom = crate::Judge::with_timing_scale(0.9);
+		assert_eq!(identity.clone().with_judge(&custom), None);
+
+		// sorted by chartkey, then rate, then judge
+		let mut identities = [
+			identity.clone().with_judge(crate::J5).unwrap(),
+			ScoreIdentity::new(
+				identity.chartkey.clone(),
+				crate::Rate::from_f32(0.5).unwrap(),
+			),
+			identity.clone(),
+		];
+		identities.sort();
+		assert_eq!(identities[1], identity);
+		assert_eq!(identities[2].judge(), Some(crate::J5));
+	}
+}
@@ -0,0 +1,148 @@
+//! Helpers for analyzing sequences of values, e.g. for pattern or combo detection
+
+/// Returns the length of the longest run of consecutive `true` values
+///
+/// ```rust
+/// # use etterna::sequences::longest_true_sequence;
+/// assert_eq!(longest_true_sequence(vec![true, false, true, true, false]), 2);
+/// assert_eq!(longest_true_sequence(vec![]), 0);
+/// ```
+#[allow(clippy::collapsible_if)]
+pub fn longest_true_sequence(iterator: impl IntoIterator<Item = bool>) -> u32 {
+	let mut longest_so_far = 0;
+	let mut current_run = 0;
+	for is_true in iterator {
+		if is_true {
+			current_run += 1;
+		} else {
+			if current_run > longest_so_far {
+				longest_so_far = current_run;
+			}
+			current_run = 0;
+		}
+	}
+
+	if current_run > longest_so_far {
+		longest_so_far = current_run;
+	}
+
+	longest_so_far
+}
+
+/// Iterator over runs of equal consecutive values. See [`runs`]
+pub struct Runs<I: Iterator> {
+	iterator: std::iter::Peekable<I>,
+}
+
+impl<I> Iterator for Runs<I>
+where
+	I: Iterator,
+	I::Item: PartialEq,
+{
+	type Item = (I::Item, u32);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let value = self.iterator.next()?;
+		let mut run_length = 1;
+		while self.iterator.next_if_eq(&value).is_some() {
+			run_length += 1;
+		}
+		Some((value, run_length))
+	}
+}
+
+/// Groups consecutive equal values into runs and yields each run's value and length
+///
+/// ```rust
+/// # use etterna::sequences::runs;
+/// let runs: Vec<_> = runs("aaabcc".chars()).collect();
+/// assert_eq!(runs, vec![('a', 3), ('b', 1), ('c', 2)]);
+/// ```
+pub fn runs<I>(iterator: I) -> Runs<I::IntoIter>
+where
+	I: IntoIterator,
+	I::Item: PartialEq,
+{
+	Runs {
+		iterator: iterator.into_iter().peekable(),
+	}
+}
+
+/// Returns the largest number of values that fit into a window of the given length, for example
+/// the highest number of notes within any one second.
+///
+/// A value lies within a window if `window_start <= value < window_start + window_length`, so a
+/// window length of zero or less (or NaN) contains no values and yields 0.
+///
+/// The input must be sorted. This is checked in debug builds only; in release builds, the result
+/// for unsorted input is unspecified.
+///
+/// ```rust
+/// # use etterna::sequences::max_count_in_window;
+/// assert_eq!(max_count_in_window(&[0.0, 0.5, 0.9, 1.0, 3.0], 1.0), 3);
+/// assert_eq!(max_count_in_window(&[], 1.0), 0);
+/// ```
+pub fn max_count_in_window(sorted_values: &[f32], window_length: f32) -> usize {
+	debug_assert!(crate::util::is_sorted(sorted_values));
+	if window_length.is_nan() || window_length <= 0.0 {
+		return 0;
+	}
+
+	let mut max_count = 0;
+	let mut window_start = 0;
+	for (window_end, &value) in sorted_values.iter().enumerate() {
+		// not written as `start <= value - window_length`, because for large values the subtraction
+		// can round back to `value`, which would move the window start past the window end
+		while value - sorted_values[window_start] >= window_length {
+			window_start += 1;
+		}
+		max_count = max_count.max(window_end - window_start + 1);
+	}
+	max_count
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_runs() {
+		assert_eq!(
+			runs(vec![true, true, false, true, true, true]).collect::<Vec<_>>(),
+			vec![(true, 2), (false, 1), (true, 3)],
+		);
+		assert_eq!(runs(Vec::<u32>::new()).next(), None);
+
+		// longest_true_sequence must agree with runs
+		let values = [false, true, true, false, true, true, true, false];
+		assert_eq!(
+			longest_true_sequence(values.iter().copied()),
+			runs(values.iter().copied())
+				.filter(|&(value, _)| value)
+				.map(|(_, len)| len)
+				.max()
+				.unwrap_or(0),
+		);
+	}
+
+	#[test]
+	fn test_max_count_in_window() {
+		assert_eq!(max_count_in_window(&[0.0, 1.0, 2.0, 3.0], 1.0), 1);
+		assert_eq!(max_count_in_window(&[0.0, 1.0, 2.0, 3.0], 1.5), 2);
+		assert_eq!(max_count_in_window(&[5.0, 5.0, 5.0], 0.1), 3);
+
+		// used to index out of bounds
+		assert_eq!(max_count_in_window(&[5.0, 5.0, 5.0], 0.0), 0);
+		assert_eq!(max_count_in_window(&[1.0, 2.0], -1.0), 0);
+		assert_eq!(max_count_in_window(&[1.0, 2.0], f32::NAN), 0);
+
+		// values so large that subtracting the window length doesn't change them
+		assert_eq!(max_count_in_window(&[1e9], 1.0), 1);
+		assert_eq!(max_count_in_window(&[0.0, 1e8, 1e8], 1.0), 2);
+		assert_eq!(max_count_in_window(&[f32::INFINITY], 1.0), 1);
+		assert_eq!(
+			max_count_in_window(&[0.0, f32::INFINITY, f32::INFINITY], 1.0),
+			2
+		);
+	}
+}
@@ -0,0 +1,286 @@
+/// A point in time, as used in session detection.
+///
+/// Implemented for [`std::time::SystemTime`], and for integers and floats, which are interpreted
+/// as seconds since an arbitrary epoch (e.g. Unix timestamps). With the `chrono` or `time`
+/// feature enabled, it's also implemented for those crates' date and date-time types.
+pub trait Timestamp {
+	/// Returns the number of seconds elapsed from `earlier` to `self`. May be negative if `earlier`
+	/// is actually later than `self`.
+	fn seconds_since(&self, earlier: &Self) -> f64;
+}
+
+impl Timestamp for std::time::SystemTime {
+	fn seconds_since(&self, earlier: &Self) -> f64 {
+		match self.duration_since(*earlier) {
+			Ok(duration) => duration.as_secs_f64(),
+			Err(e) => -e.duration().as_secs_f64(),
+		}
+	}
+}
+
+macro_rules! impl_timestamp_for_number {
+	($($t:ty),*) => {
+		$(
+			impl Timestamp for $t {
+				fn seconds_since(&self, earlier: &Self) -> f64 {
+					*self as f64 - *earlier as f64
+				}
+			}
+		)*
+	};
+}
+
+impl_timestamp_for_number!(u32, u64, i32, i64, f32, f64);
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::NaiveDateTime {
+	fn seconds_since(&self, earlier: &Self) -> f64 {
+		self.signed_duration_since(*earlier).num_milliseconds() as f64 / 1000.0
+	}
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> Timestamp for chrono::DateTime<Tz> {
+	fn seconds_since(&self, earlier: &Self) -> f64 {
+		self.clone()
+			.signed_duration_since(earlier.clone())
+			.num_milliseconds() as f64
+			/ 1000.0
+	}
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::NaiveDate {
+	fn seconds_since(&self, earlier: &Self) -> f64 {
+		self.signed_duration_since(*earlier).num_seconds() as f64
+	}
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::OffsetDateTime {
+	fn seconds_since(&self, earlier: &Self) -> f64 {
+		(*self - *earlier).as_seconds_f64()
+	}
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::PrimitiveDateTime {
+	fn seconds_since(&self, earlier: &Self) -> f64 {
+		(*self - *earlier).as_seconds_f64()
+	}
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::Date {
+	fn seconds_since(&self, earlier: &Self) -> f64 {
+		(*self - *earlier).as_seconds_f64()
+	}
+}
+
+/// The score data needed for session statistics
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionScore {
+	pub wifescore: crate::Wifescore,
+	pub ssr: crate::Skillsets8,
+}
+
+/// A play session, i.e. a series of scores without long breaks in between. See [`find_sessions`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Session<T> {
+	/// Timestamp of the first score in the session
+	pub start: T,
+	/// Timestamp of the last score in the session
+	pub end: T,
+	pub num_scores: usize,
+	/// Highest overall SSR in this session
+	pub best_ssr: f32,
+	pub average_wifescore: crate::Wifescore,
+	/// Player rating before this session
+	pub rating_before: crate::Skillsets8,
+	/// Player rating after this session
+	pub rating_after: crate::Skillsets8,
+}
+
+impl<T> Session<T> {
+	/// How much overall rating the player gained in this session
+	pub fn rating_gained(&self) -> f32 {
+		self.rating_after.overall - self.rating_before.overall
+	}
+}
+
+/// Splits a player's scores into play sessions and calculates statistics for each session.
+///
+/// A new session begins whenever more than `max_gap_seconds` elapsed between two consecutive
+/// scores. The scores must be sorted chronologically. The player ratings are calculated using
+/// [`crate::SkillTimeline`] with the algorithm of the given game version.
+///
+/// ```rust
+/// # use etterna::*;
+/// let score = |overall| SessionScore {
+/// 	wifescore: Wifescore::from_percent(95.0).unwrap(),
+/// 	ssr: Skillsets8 { overall, ..Default::default() },
+/// };
+/// let scores = vec![(0, score(20.0)), (600, score(21.0)), (20000, score(22.0))];
+///
+/// let sessions = find_sessions(scores, 3600.0, EtternaVersion::LATEST);
+/// assert_eq!(sessions.len(), 2);
+/// assert_eq!((sessions[0].start, sessions[0].end), (0, 600));
+/// assert_eq!(sessions[0].num_scores, 2);
+/// assert_eq!(sessions[1].best_ssr, 22.0);
+/// ```
+pub fn find_sessions<T, I>(
+	scores: I,
+	max_gap_seconds: f64,
+	version: crate::EtternaVersion,
+) -> Vec<Session<T>>
+where
+	T: Timestamp + Clone,
+	I: IntoIterator<Item = (T, SessionScore)>,
+{
+	let scores: Vec<(T, SessionScore)> = scores.into_iter().collect();
+
+	// Assign each score the index of the session it belongs to
+	let mut session_indices = Vec::with_capacity(scores.len());
+	let mut session_index = 0;
+	for (i, (timestamp, _)) in scores.iter().enumerate() {
+		if i > 0 && timestamp.seconds_since(&scores[i - 1].0) > max_gap_seconds {
+			session_index += 1;
+		}
+		session_indices.push(session_index);
+	}
+
+	let timeline = crate::SkillTimeline::calculate(
+		session_indices
+			.iter()
+			.zip(&scores)
+			.map(|(&session_index, (_, score))| (session_index, score.ssr.to_skillsets7())),
+		version,
+	);
+
+	let mut sessions: Vec<Session<T>> = Vec::with_capacity(timeline.changes.len());
+	let mut session_start_i = 0;
+	for (_, rating_after) in timeline.changes {
+		let session_end_i = session_indices[session_start_i..]
+			.iter()
+			.position(|&index| index != session_indices[session_start_i])
+			.map_or(scores.len(), |len| session_start_i + len);
+		let session_scores = &scores[session_start_i..session_end_i];
+
+		let num_scores = session_scores.len();
+		let wifescore_sum: f32 = session_scores
+			.iter()
+			.map(|(_, score)| score.wifescore.as_proportion())
+			.sum();
+
+		sessions.push(Session {
+			start: session_scores[0].0.clone(),
+			end: session_scores[num_scores - 1].0.clone(),
+			num_scores,
+			best_ssr: session_scores
+				.iter()
+				.map(|(_, score)| score.ssr.overall)
+				.fold(f32::NEG_INFINITY, f32::max),
+			// UNWRAP: the average of valid wifescores is a valid wifescore
+			average_wifescore: crate::Wifescore::from_proportion(wifescore_sum / num_scores as f32)
+				.unwrap(),
+			rating_before: sessions
+				.last()
+				.map(|session| session.rating_after.clone())
+				.unwrap_or_default(),
+			rating_after,
+		});
+
+		session_start_i = session_end_i;
+	}
+
+	sessions
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_find_sessions() {
+		let score = |wifescore, stream| SessionScore {
+			wifescore: crate::Wifescore::from_proportion(wifescore).unwrap(),
+			ssr: crate::Skillsets7 {
+				stream,
+				..Default::default()
+			}
+			.calc_ssr_overall(),
+		};
+		let scores = vec![
+			(100.0, score(0.90, 20.0)),
+			(200.0, score(0.96, 22.0)),
+			(5000.0, score(0.93, 25.0)),
+			(5100.0, score(0.97, 10.0)),
+			(5150.0, score(0.99, 15.0)),
+		];
+
+		let sessions = find_sessions(scores, 1000.0, crate::EtternaVersion::LATEST);
+		assert_eq!(sessions.len(), 2);
+
+		assert_eq!(sessions[0].num_scores, 2);
+		assert_eq!(sessions[0].start, 100.0);
+		assert_eq!(sessions[0].end, 200.0);
+		assert!((sessions[0].average_wifescore.as_proportion() - 0.93).abs() < 0.0001);
+		assert_eq!(sessions[0].rating_before, crate::Skillsets8::default());
+		assert!(sessions[0].rating_gained() > 0.0);
+
+		assert_eq!(sessions[1].num_scores, 3);
+		assert_eq!(sessions[1].best_ssr, 25.0);
+		assert_eq!(sessions[1].rating_before, sessions[0].rating_after);
+		assert!(sessions[1].rating_gained() > 0.0);
+
+		assert!(find_sessions(
+			Vec::<(u64, SessionScore)>::new(),
+			1000.0,
+			crate::EtternaVersion::LATEST
+		)
+		.is_empty());
+	}
+
+	#[test]
+	fn test_system_time_timestamp() {
+		let now = std::time::SystemTime::now();
+		let later = now + std::time::Duration::from_secs(90);
+		assert_eq!(later.seconds_since(&now), 90.0);
+		assert_eq!(now.seconds_since(&later), -90.0);
+	}
+
+	#[test]
+	#[cfg(feature = "chrono")]
+	fn test_chrono_timestamp() {
+		let day = chrono::NaiveDate::from_ymd_opt(2021, 3, 14).unwrap();
+		let next_day = day.succ_opt().unwrap();
+		assert_eq!(next_day.seconds_since(&day), 86400.0);
+
+		let start = day.and_hms_opt(12, 0, 0).unwrap();
+		let end = day.and_hms_milli_opt(12, 1, 30, 500).unwrap();
+		assert_eq!(end.seconds_since(&start), 90.5);
+		assert_eq!(start.seconds_since(&end), -90.5);
+
+		let start = chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(start, chrono::Utc);
+		let end = chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(end, chrono::Utc);
+		assert_eq!(end.seconds_since(&start), 90.5);
+	}
+
+	#[test]
+	#[cfg(feature = "time")]
+	fn test_time_timestamp() {
+		let day = time::Date::from_calendar_date(2021, time::Month::March, 14).unwrap();
+		let next_day = day.next_day().unwrap();
+		assert_eq!(next_day.seconds_since(&day), 86400.0);
+
+		let start = day.with_hms(12, 0, 0).unwrap();
+		let end = day.with_hms_milli(12, 1, 30, 500).unwrap();
+		assert_eq!(end.seconds_since(&start), 90.5);
+		assert_eq!(start.seconds_since(&end), -90.5);
+
+		let (start, end) = (start.assume_utc(), end.assume_utc());
+		assert_eq!(end.seconds_since(&start), 90.5);
+	}
+}
@@ -0,0 +1,315 @@
+//! Parsing of StepMania and Etterna simfiles (.sm, .ssc) into [`crate::NoteData`].
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SimfileParseError {
+	#[error("Simfile doesn't contain a single chart with notes")]
+	NoCharts,
+	#[error("Simfile doesn't specify any BPMs")]
+	MissingBpms,
+	#[error("Invalid #BPMS tag: {0}")]
+	InvalidBpms(#[from] crate::SmBpmStringParseError),
+}
+
+/// A simfile parsed with [`parse_simfile`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simfile {
+	pub title: Option<String>,
+	pub artist: Option<String>,
+	pub charts: Vec<SimfileChart>,
+}
+
+/// A single chart of a [`Simfile`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimfileChart {
+	/// For example `dance-single`
+	pub steps_type: String,
+	/// None if the difficulty slot isn't recognized by [`crate::Difficulty::from_long_string`]
+	pub difficulty: Option<crate::Difficulty>,
+	/// The numeric difficulty set by the chart author
+	pub meter: Option<u32>,
+	/// The keycount is taken from the width of the note rows
+	pub note_data: crate::NoteData,
+	/// The chart's own BPMs for .ssc files that have them, and the song's BPMs otherwise
+	pub timing_info: crate::TimingInfo,
+}
+
+impl SimfileChart {
+	/// Shorthand for [`crate::NoteData::summary`]
+	pub fn summary(&self) -> crate::ChartSummary {
+		self.note_data.summary(&self.timing_info)
+	}
+}
+
+/// Splits a simfile into its `#NAME:value;` tags, with comments removed. Names are uppercased
+pub(crate) fn parse_tags(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+	let mut without_comments = Vec::with_capacity(bytes.len());
+	for line in bytes.split(|&c| c == b'\n') {
+		let line = match twoway::find_bytes(line, b"//") {
+			Some(comment_start) => &line[..comment_start],
+			None => line,
+		};
+		without_comments.extend_from_slice(line);
+		without_comments.push(b'\n');
+	}
+
+	let mut tags = Vec::new();
+	let mut rest = &without_comments[..];
+	while let Some(tag_start) = rest.iter().position(|&c| c == b'#') {
+		rest = &rest[tag_start + 1..];
+		let tag_end = rest.iter().position(|&c| c == b';').unwrap_or(rest.len());
+		let tag = &rest[..tag_end];
+		rest = &rest[(tag_end + 1).min(rest.len())..];
+
+		let name_end = crate::some_or_continue!(tag.iter().position(|&c| c == b':'));
+		let name = String::from_utf8_lossy(crate::util::trim_bstr(&tag[..name_end]));
+		tags.push((name.to_ascii_uppercase(), tag[name_end + 1..].to_vec()));
+	}
+	tags
+}
+
+pub(crate) fn bytes_to_string(bytes: &[u8]) -> String {
+	String::from_utf8_lossy(crate::util::trim_bstr(bytes)).into_owned()
+}
+
+/// Parses the measures of a `#NOTES` tag. Returns None if there isn't a single row
+fn parse_note_data(notes: &[u8]) -> Option<crate::NoteData> {
+	let measures: Vec<Vec<&[u8]>> = notes
+		.split(|&c| c == b',')
+		.map(|measure| {
+			measure
+				.split(|&c| c == b'\n')
+				.map(crate::util::trim_bstr)
+				.filter(|row| !row.is_empty())
+				.collect()
+		})
+		.collect();
+
+	let keycount = measures.iter().flatten().next()?.len();
+	if keycount > crate::NoteData::MAX_KEYCOUNT as usize {
+		return None;
+	}
+
+	let mut note_data = crate::NoteData::new(keycount as u8);
+	for (measure_index, rows) in measures.iter().enumerate() {
+		for (row_index, row) in rows.iter().enumerate() {
+			// 192 ticks per measure. Snaps that don't divide that are rounded to the nearest tick
+			let tick = measure_index * 192 + (row_index * 192 + rows.len() / 2) / rows.len();
+			for (column, &note) in row.iter().enumerate().take(keycount) {
+				let note_type = match note {
+					b'1' => crate::NoteType::Tap,
+					// rolls are treated like holds
+					b'2' | b'4' => crate::NoteType::HoldHead,
+					b'3' => crate::NoteType::HoldTail,
+					b'M' => crate::NoteType::Mine,
+					b'L' => crate::NoteType::Lift,
+					b'F' => crate::NoteType::Fake,
+					_ => continue,
+				};
+				note_data.add_note(tick as u32, column as u8, note_type);
+			}
+		}
+	}
+	Some(note_data)
+}
+
+/// A chart whose tags are still being collected
+#[derive(Default)]
+struct PendingChart {
+	steps_type: String,
+	difficulty: Option<crate::Difficulty>,
+	meter: Option<u32>,
+	timing_info: Option<crate::TimingInfo>,
+}
+
+/// Parses a .sm or .ssc simfile, for analysis and rescoring. The offset, stops, delays and other
+/// gimmicks are ignored, so note seconds are relative to beat zero.
+///
+/// Charts without any note rows are skipped.
+///
+/// ```rust
+/// # use etterna::*;
+/// let simfile = parse_simfile(b"
+/// #TITLE:Example;
+/// #BPMS:0.000=120.000;
+/// #NOTES:
+///      dance-single:
+///      :
+///      Challenge:
+///      12:
+///      0,0,0,0,0:
+/// 1000
+/// 0100
+/// 0010
+/// 0001
+/// ,
+/// 1M00
+/// 0000
+/// ;
+/// ").unwrap();
+///
+/// assert_eq!(simfile.title.as_deref(), Some("Example"));
+/// let chart = &simfile.charts[0];
+/// assert_eq!(chart.difficulty, Some(Difficulty::Challenge));
+/// assert_eq!(chart.meter, Some(12));
+/// assert_eq!(chart.note_data.keycount(), 4);
+/// assert_eq!(chart.note_data.ticks(), vec![0, 48, 96, 144, 192]);
+/// assert_eq!(chart.summary().num_mines, 1);
+/// ```
+pub fn parse_simfile(bytes: &[u8]) -> Result<Simfile, SimfileParseError> {
+	let mut title = None;
+	let mut artist = None;
+	let mut song_timing_info = None;
+	// Set once a .ssc #NOTEDATA section begins
+	let mut pending_chart: Option<PendingChart> = None;
+	// (chart tags, note data) of every chart
+	let mut charts: Vec<(PendingChart, crate::NoteData)> = Vec::new();
+
+	for (name, value) in parse_tags(bytes) {
+		match (name.as_str(), &mut pending_chart) {
+			("TITLE", None) => title = Some(bytes_to_string(&value)),
+			("ARTIST", None) => artist = Some(bytes_to_string(&value)),
+			("BPMS", None) => {
+				song_timing_info = Some(crate::TimingInfo::from_sm_bpm_string(
+					crate::util::trim_bstr(&value),
+				)?)
+			}
+			("NOTEDATA", _) => pending_chart = Some(PendingChart::default()),
+			("STEPSTYPE", Some(chart)) => chart.steps_type = bytes_to_string(&value),
+			("DIFFICULTY", Some(chart)) => {
+				chart.difficulty = crate::Difficulty::from_long_string(&bytes_to_string(&value))
+			}
+			("METER", Some(chart)) => chart.meter = bytes_to_string(&value).parse().ok(),
+			("BPMS", Some(chart)) => {
+				chart.timing_info = Some(crate::TimingInfo::from_sm_bpm_string(
+					crate::util::trim_bstr(&value),
+				)?)
+			}
+			("NOTES", Some(_)) | ("NOTES2", Some(_)) => {
+				// UNWRAP: we just matched on Some
+				let chart = pending_chart.take().unwrap();
+				if let Some(note_data) = parse_note_data(&value) {
+					charts.push((chart, note_data));
+				}
+			}
+			("NOTES", None) => {
+				let fields: Vec<&[u8]> = value.splitn(6, |&c| c == b':').collect();
+				if let [steps_type, _description, difficulty, meter, _radar_values, notes] =
+					fields[..]
+				{
+					let chart = PendingChart {
+						steps_type: bytes_to_string(steps_type),
+						difficulty: crate::Difficulty::from_long_string(&bytes_to_string(
+							difficulty,
+						)),
+						meter: bytes_to_string(meter).parse().ok(),
+						timing_info: None,
+					};
+					if let Some(note_data) = parse_note_data(notes) {
+						charts.push((chart, note_data));
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+
+	if charts.is_empty() {
+		return Err(SimfileParseError::NoCharts);
+	}
+	let charts = charts
+		.into_iter()
+		.map(|(chart, note_data)| {
+			let timing_info = chart
+				.timing_info
+				.or_else(|| song_timing_info.clone())
+				.ok_or(SimfileParseError::MissingBpms)?;
+			Ok(SimfileChart {
+				steps_type: chart.steps_type,
+				difficulty: chart.difficulty,
+				meter: chart.meter,
+				note_data,
+				timing_info,
+			})
+		})
+		.collect::<Result<_, SimfileParseError>>()?;
+
+	Ok(Simfile {
+		title,
+		artist,
+		charts,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_ssc() {
+		let simfile = parse_simfile(
+			b"
+#VERSION:0.83;
+// a comment
+#TITLE:Song;
+#BPMS:0.000=60.000;
+#NOTEDATA:;
+#STEPSTYPE:dance-single;
+#DIFFICULTY:Hard;
+#METER:9;
+#NOTES:
+1000
+0100
+0010
+0001
+;
+#NOTEDATA:;
+#STEPSTYPE:kb7-single;
+#DIFFICULTY:Edit;
+#BPMS:0.000=120.000;
+#NOTES:
+0000000
+000
+0000001
+;
+",
+		)
+		.unwrap();
+
+		assert_eq!(simfile.title.as_deref(), Some("Song"));
+		assert_eq!(simfile.charts.len(), 2);
+
+		let chart = &simfile.charts[0];
+		assert_eq!(chart.steps_type, "dance-single");
+		assert_eq!(chart.difficulty, Some(crate::Difficulty::Hard));
+		assert_eq!(chart.meter, Some(9));
+		assert_eq!(
+			chart.note_data.row_seconds(&chart.timing_info),
+			vec![0.0, 1.0, 2.0, 3.0]
+		);
+
+		// the chart's own BPMs take precedence, and 3rds are rounded to the nearest tick
+		let chart = &simfile.charts[1];
+		assert_eq!(chart.note_data.keycount(), 7);
+		assert_eq!(chart.meter, None);
+		assert_eq!(chart.note_data.ticks(), vec![128]);
+		assert_eq!(chart.summary().length_seconds, 4.0 / 3.0);
+	}
+
+	#[test]
+	fn test_parse_errors() {
+		assert!(matches!(
+			parse_simfile(b"#TITLE:No charts;"),
+			Err(SimfileParseError::NoCharts)
+		));
+		assert!(matches!(
+			parse_simfile(b"#NOTES:dance-single::Easy:1::1000;"),
+			Err(SimfileParseError::MissingBpms)
+		));
+		assert!(matches!(
+			parse_simfile(b"#BPMS:nonsense;#NOTES:dance-single::Easy:1::1000;"),
+			Err(SimfileParseError::InvalidBpms(_))
+		));
+	}
+}
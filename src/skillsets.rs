@@ -1,5 +1,8 @@
 use std::convert::{TryFrom, TryInto};
 
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator;
+
 /// Skillset information, excluding overall
 #[derive(Debug, Clone, PartialEq, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -13,6 +16,17 @@ pub struct Skillsets7 {
 	pub technical: f32,
 }
 
+/// What a [`Skillsets7`] describes, which determines how its overall is calculated. See
+/// [`Skillsets7::calc_overall`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OverallKind {
+	/// A player's rating
+	Player,
+	/// A score's SSRs, or a chart's MSDs
+	Score,
+}
+
 impl Skillsets7 {
 	pub fn get(&self, ss: Skillset7) -> f32 {
 		match ss {
@@ -39,6 +53,28 @@ impl Skillsets7 {
 		}
 	}
 
+	/// Calculates the overall according to the kind of skillsets, with the algorithm of the given
+	/// game version. This is the same as calling the respective `calc_*_overall*` method.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let skillsets = Skillsets7 { stream: 25.0, technical: 27.0, ..Default::default() };
+	/// assert_eq!(
+	/// 	skillsets.calc_overall(OverallKind::Score, EtternaVersion::V0_68),
+	/// 	skillsets.calc_ssr_overall_pre_070(),
+	/// );
+	/// // before 0.70, a player's overall was the average of their skillsets
+	/// assert_eq!(skillsets.calc_overall(OverallKind::Player, EtternaVersion::V0_68).overall, 52.0 / 7.0);
+	/// ```
+	pub fn calc_overall(&self, kind: OverallKind, version: crate::EtternaVersion) -> Skillsets8 {
+		match (kind, version.is_pre_070()) {
+			(OverallKind::Player, false) => self.calc_player_overall(),
+			(OverallKind::Player, true) => self.calc_player_overall_pre_070(),
+			(OverallKind::Score, false) => self.calc_ssr_overall(),
+			(OverallKind::Score, true) => self.calc_ssr_overall_pre_070(),
+		}
+	}
+
 	pub fn calc_player_overall(&self) -> Skillsets8 {
 		let overall = crate::rating_calc::calculate_player_overall(&[
 			self.stream,
@@ -53,6 +89,114 @@ impl Skillsets7 {
 		self.with_overall(overall)
 	}
 
+	/// Calculate a player's rating in all skillsets, including overall, in one go. The input
+	/// contains the SSRs of all the player's scores, per skillset, in the order of
+	/// [`Skillset7::list`].
+	///
+	/// With the `parallel` feature enabled, the skillsets are calculated in parallel.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let ssrs = [20.0, 22.0, 25.0];
+	/// let rating = Skillsets7::calc_player_rating(&[&ssrs; 7]);
+	/// assert_eq!(rating.stream, calculate_player_skillset_rating(&ssrs));
+	/// ```
+	pub fn calc_player_rating(ssrs_per_skillset: &[&[f32]; 7]) -> Skillsets8 {
+		Self::calc_skillset_ratings(
+			ssrs_per_skillset,
+			crate::rating_calc::calculate_player_skillset_rating,
+		)
+		.calc_player_overall()
+	}
+
+	/// This is the pre-0.70 variant of [`Self::calc_player_rating`]
+	pub fn calc_player_rating_pre_070(ssrs_per_skillset: &[&[f32]; 7]) -> Skillsets8 {
+		Self::calc_skillset_ratings(
+			ssrs_per_skillset,
+			crate::rating_calc::calculate_player_skillset_rating_pre_070,
+		)
+		.calc_player_overall_pre_070()
+	}
+
+	fn calc_skillset_ratings(
+		ssrs_per_skillset: &[&[f32]; 7],
+		skillset_calc_function: fn(&[f32]) -> f32,
+	) -> Self {
+		let ratings: Vec<f32> = crate::par_iter_maybe(&ssrs_per_skillset[..])
+			.map(|ssrs| skillset_calc_function(ssrs))
+			.collect();
+		Self::generate(|ss| ratings[ss as usize])
+	}
+
+	/// For each skillset, calculates the SSR that a single new score in that skillset would need
+	/// to raise the player's overall rating by `overall_gain`. Uses the 0.70+ algorithm. The input
+	/// is the same as in [`Self::calc_player_rating`].
+	///
+	/// The result is accurate to about 0.01. Skillsets in which no single score could achieve the
+	/// gain are set to infinity.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let ssrs = [20.0, 21.0, 22.0];
+	/// let ssrs_per_skillset = [&ssrs[..]; 7];
+	/// let needed = Skillsets7::needed_ssrs_for_overall_gain(&ssrs_per_skillset, 0.1);
+	///
+	/// let mut new_stream_ssrs = ssrs.to_vec();
+	/// new_stream_ssrs.push(needed.stream);
+	/// let mut new_ssrs_per_skillset = ssrs_per_skillset;
+	/// new_ssrs_per_skillset[0] = &new_stream_ssrs;
+	///
+	/// let old_overall = Skillsets7::calc_player_rating(&ssrs_per_skillset).overall;
+	/// let new_overall = Skillsets7::calc_player_rating(&new_ssrs_per_skillset).overall;
+	/// assert!(new_overall >= old_overall + 0.1);
+	/// ```
+	pub fn needed_ssrs_for_overall_gain(
+		ssrs_per_skillset: &[&[f32]; 7],
+		overall_gain: f32,
+	) -> Self {
+		// No real score comes anywhere near this
+		const MAX_SSR: f32 = 200.0;
+
+		let current_rating = Self::calc_player_rating(ssrs_per_skillset);
+		let target_overall = current_rating.overall + overall_gain;
+		let current_rating = current_rating.to_skillsets7();
+
+		Self::generate(|ss| {
+			let mut ssrs = ssrs_per_skillset[ss as usize].to_vec();
+			ssrs.push(0.0);
+			let mut overall_with_new_score = |new_ssr: f32| {
+				*ssrs.last_mut().unwrap() = new_ssr;
+				let new_skillset_rating =
+					crate::rating_calc::calculate_player_skillset_rating(&ssrs);
+				Self::generate(|other_ss| {
+					if other_ss == ss {
+						new_skillset_rating
+					} else {
+						current_rating.get(other_ss)
+					}
+				})
+				.calc_player_overall()
+				.overall
+			};
+
+			if overall_with_new_score(MAX_SSR) < target_overall {
+				return f32::INFINITY;
+			}
+
+			// Binary search for the lowest SSR that reaches the target
+			let (mut low, mut high) = (0.0, MAX_SSR);
+			while high - low > 0.005 {
+				let mid = (low + high) / 2.0;
+				if overall_with_new_score(mid) >= target_overall {
+					high = mid;
+				} else {
+					low = mid;
+				}
+			}
+			high
+		})
+	}
+
 	pub fn calc_player_overall_pre_070(&self) -> Skillsets8 {
 		let overall =
 			(self.stream
@@ -102,6 +246,29 @@ impl Skillsets7 {
 		self.with_overall(max_skillset)
 	}
 
+	/// Returns the `n` highest skillsets, highest first. If two skillsets are equal, the one that
+	/// comes first in [`Skillset7::list`] is ranked higher.
+	///
+	/// ```rust
+	/// # use etterna::{Skillset7, Skillsets7};
+	/// let skillsets = Skillsets7 { stream: 25.0, jackspeed: 28.0, technical: 26.0, ..Default::default() };
+	/// assert_eq!(
+	/// 	skillsets.dominant_skillsets(2),
+	/// 	vec![Skillset7::Jackspeed, Skillset7::Technical],
+	/// );
+	/// ```
+	pub fn dominant_skillsets(&self, n: usize) -> Vec<Skillset7> {
+		let mut skillsets = Skillset7::list().to_vec();
+		// sort_by is stable, so equal skillsets keep their order
+		skillsets.sort_by(|&a, &b| {
+			self.get(b)
+				.partial_cmp(&self.get(a))
+				.unwrap_or(std::cmp::Ordering::Equal)
+		});
+		skillsets.truncate(n);
+		skillsets
+	}
+
 	pub fn generate<F: FnMut(crate::Skillset7) -> f32>(mut generator: F) -> Self {
 		Self {
 			stream: (generator)(crate::Skillset7::Stream),
@@ -161,6 +328,34 @@ impl Skillsets8 {
 		}
 	}
 
+	/// Returns the `n` highest skillsets, excluding overall. See [`Skillsets7::dominant_skillsets`]
+	pub fn dominant_skillsets(&self, n: usize) -> Vec<Skillset7> {
+		self.to_skillsets7().dominant_skillsets(n)
+	}
+
+	/// Normalizes each skillset relative to overall, for use in radar charts. The resulting values
+	/// are clamped to `0.0..=1.0`.
+	///
+	/// ```rust
+	/// # use etterna::Skillsets8;
+	/// let skillsets = Skillsets8 { overall: 20.0, stream: 15.0, jackspeed: 22.0, ..Default::default() };
+	/// let radar = skillsets.radar_relative_to_overall();
+	/// assert_eq!(radar.stream, 0.75);
+	/// assert_eq!(radar.jackspeed, 1.0);
+	/// ```
+	pub fn radar_relative_to_overall(&self) -> Skillsets7 {
+		Skillsets7::generate(|ss| normalize_radar_value(self.get(ss.into()), self.overall))
+	}
+
+	/// Normalizes each skillset relative to the corresponding skillset of a reference player (for
+	/// example the top player of a leaderboard), for use in radar charts. The resulting values are
+	/// clamped to `0.0..=1.0`.
+	pub fn radar_relative_to(&self, reference: &Skillsets8) -> Skillsets7 {
+		Skillsets7::generate(|ss| {
+			normalize_radar_value(self.get(ss.into()), reference.get(ss.into()))
+		})
+	}
+
 	pub fn generate<F: FnMut(crate::Skillset8) -> f32>(mut generator: F) -> Self {
 		Self {
 			overall: (generator)(crate::Skillset8::Overall),
@@ -175,6 +370,59 @@ impl Skillsets8 {
 	}
 }
 
+fn normalize_radar_value(value: f32, reference: f32) -> f32 {
+	if reference > 0.0 {
+		(value / reference).clamp(0.0, 1.0)
+	} else {
+		0.0
+	}
+}
+
+/// Anything with a value per skillset, like [`Skillsets8`] or [`crate::UserRank`]. Allows code to be
+/// generic over them, e.g. to render tables of skillset values.
+///
+/// ```rust
+/// # use etterna::*;
+/// fn format_row<T: GetSkillset>(values: &T) -> String
+/// where
+/// 	T::Value: std::fmt::Debug,
+/// {
+/// 	Skillset8::iter()
+/// 		.map(|ss| format!("{:?}", values.get_skillset(ss)))
+/// 		.collect::<Vec<_>>()
+/// 		.join(" | ")
+/// }
+///
+/// let rank = UserRank { overall: 5, stream: 2, ..Default::default() };
+/// assert_eq!(format_row(&rank), "5 | 2 | 0 | 0 | 0 | 0 | 0 | 0");
+/// let ssrs = Skillsets7 { stream: 20.0, ..Default::default() };
+/// assert!(format_row(&ssrs).starts_with("None | Some(20.0) | Some(0.0)"));
+/// ```
+pub trait GetSkillset {
+	type Value;
+
+	/// Get a specific skillset value. Named differently from the inherent `get` methods, which may
+	/// take a narrower skillset type or return a different type
+	fn get_skillset(&self, skillset: impl Into<Skillset8>) -> Self::Value;
+}
+
+impl GetSkillset for Skillsets8 {
+	type Value = f32;
+
+	fn get_skillset(&self, skillset: impl Into<Skillset8>) -> f32 {
+		Skillsets8::get(self, skillset.into())
+	}
+}
+
+/// Overall isn't part of [`Skillsets7`], so it's returned as None
+impl GetSkillset for Skillsets7 {
+	type Value = Option<f32>;
+
+	fn get_skillset(&self, skillset: impl Into<Skillset8>) -> Option<f32> {
+		Some(Skillsets7::get(self, skillset.into().into_skillset7()?))
+	}
+}
+
 /// Skillsets enum, excluding overall
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -301,7 +549,7 @@ impl Skillset8 {
 	/// assert_eq!(Skillset8::from_user_input("handstreams"), None);
 	/// ```
 	pub fn from_user_input(input: &str) -> Option<Self> {
-		match &input.to_lowercase() as &str {
+		match crate::aliases::normalize_user_input(input).as_str() {
 			"overall" => Some(Self::Overall),
 			"stream" => Some(Self::Stream),
 			"js" | "jumpstream" => Some(Self::Jumpstream),
@@ -314,6 +562,15 @@ impl Skillset8 {
 		}
 	}
 
+	/// Like [`Self::from_user_input`], but additionally recognizes the spellings registered in
+	/// `aliases`. See [`crate::AliasTable`]
+	pub fn from_user_input_with_aliases(
+		input: &str,
+		aliases: &crate::AliasTable<Self>,
+	) -> Option<Self> {
+		Self::from_user_input(input).or_else(|| aliases.get(input))
+	}
+
 	/// Get a list of all skillsets
 	///
 	/// Useful for tasks that require operating on all skillsets individually
@@ -382,6 +639,22 @@ impl std::str::FromStr for Skillset8 {
 	}
 }
 
+impl TryFrom<&str> for Skillset7 {
+	type Error = UnrecognizedSkillset;
+
+	fn try_from(s: &str) -> Result<Self, Self::Error> {
+		s.parse()
+	}
+}
+
+impl TryFrom<&str> for Skillset8 {
+	type Error = UnrecognizedSkillset;
+
+	fn try_from(s: &str) -> Result<Self, Self::Error> {
+		s.parse()
+	}
+}
+
 impl TryFrom<Skillset8> for Skillset7 {
 	type Error = ();
 
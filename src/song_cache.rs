@@ -0,0 +1,236 @@
+//! Parsing of Etterna's song cache, to find out which chart a chartkey belongs to without loading
+//! every simfile. See [`SongCacheIndex`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SongCacheError {
+	#[error("Could not read {}: {source}", path.display())]
+	Io {
+		path: PathBuf,
+		source: std::io::Error,
+	},
+}
+
+/// A chart entry of the song cache
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CachedChart {
+	pub chartkey: crate::Chartkey,
+	/// Path of the song's simfile as the game sees it, e.g. `/Songs/Pack/Song/song.sm`
+	pub song_path: Option<String>,
+	pub title: Option<String>,
+	pub artist: Option<String>,
+	/// For example `dance-single`
+	pub steps_type: String,
+	pub difficulty: Option<crate::Difficulty>,
+	pub meter: Option<u32>,
+}
+
+/// A chart whose tags are still being collected
+#[derive(Default)]
+struct PendingChart {
+	steps_type: String,
+	difficulty: Option<crate::Difficulty>,
+	meter: Option<u32>,
+	chartkey: Option<crate::Chartkey>,
+}
+
+/// Parses a single file of the song cache (`Cache/Songs/*`). These are simfiles in the .ssc format
+/// with additional tags like `#SONGFILENAME` and `#CHARTKEY`.
+///
+/// Charts without a valid chartkey are skipped.
+///
+/// ```rust
+/// # use etterna::*;
+/// let charts = parse_song_cache_file(b"
+/// #SONGFILENAME:/Songs/Pack/Song/song.sm;
+/// #TITLE:Song;
+/// #NOTEDATA:;
+/// #STEPSTYPE:dance-single;
+/// #DIFFICULTY:Challenge;
+/// #METER:25;
+/// #CHARTKEY:X0123456789abcdef0123456789abcdef01234567;
+/// ");
+/// assert_eq!(charts.len(), 1);
+/// assert_eq!(charts[0].song_path.as_deref(), Some("/Songs/Pack/Song/song.sm"));
+/// assert_eq!(charts[0].difficulty, Some(Difficulty::Challenge));
+/// assert_eq!(charts[0].chartkey.as_str(), "X0123456789abcdef0123456789abcdef01234567");
+/// ```
+pub fn parse_song_cache_file(bytes: &[u8]) -> Vec<CachedChart> {
+	let mut song_path = None;
+	let mut title = None;
+	let mut artist = None;
+	// The chart of the current #NOTEDATA section, and all finished charts
+	let mut pending_chart: Option<PendingChart> = None;
+	let mut charts = Vec::new();
+
+	for (name, value) in crate::simfile::parse_tags(bytes) {
+		let value = crate::simfile::bytes_to_string(&value);
+		match (name.as_str(), &mut pending_chart) {
+			("SONGFILENAME", None) => song_path = Some(value),
+			("TITLE", None) => title = Some(value),
+			("ARTIST", None) => artist = Some(value),
+			("NOTEDATA", _) => charts.extend(pending_chart.replace(PendingChart::default())),
+			("STEPSTYPE", Some(chart)) => chart.steps_type = value,
+			("DIFFICULTY", Some(chart)) => {
+				chart.difficulty = crate::Difficulty::from_long_string(&value)
+			}
+			("METER", Some(chart)) => chart.meter = value.parse().ok(),
+			("CHARTKEY", Some(chart)) => chart.chartkey = crate::Chartkey::new(value),
+			_ => {}
+		}
+	}
+	charts.extend(pending_chart);
+
+	charts
+		.into_iter()
+		.filter_map(|chart| {
+			Some(CachedChart {
+				chartkey: chart.chartkey?,
+				song_path: song_path.clone(),
+				title: title.clone(),
+				artist: artist.clone(),
+				steps_type: chart.steps_type,
+				difficulty: chart.difficulty,
+				meter: chart.meter,
+			})
+		})
+		.collect()
+}
+
+/// Lookup from chartkey to the chart's song and difficulty, built from Etterna's song cache.
+///
+/// Only the text cache files in `Cache/Songs` are supported. Game versions that store the cache in
+/// an SQLite database (`Cache/cache.db`) can't be read.
+///
+/// ```rust
+/// # use etterna::*;
+/// let mut index = SongCacheIndex::new();
+/// index.add_cache_file(b"
+/// #SONGFILENAME:/Songs/Pack/Song/song.sm;
+/// #NOTEDATA:;
+/// #DIFFICULTY:Hard;
+/// #CHARTKEY:X0123456789abcdef0123456789abcdef01234567;
+/// ");
+///
+/// let chartkey = Chartkey::new("X0123456789abcdef0123456789abcdef01234567".into()).unwrap();
+/// assert_eq!(index.get(&chartkey).unwrap().difficulty, Some(Difficulty::Hard));
+/// assert_eq!(index.len(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SongCacheIndex {
+	charts: HashMap<crate::Chartkey, CachedChart>,
+}
+
+impl SongCacheIndex {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Reads every file in a song cache folder (usually `Cache/Songs` in the game directory)
+	pub fn read_folder(path: impl AsRef<Path>) -> Result<Self, SongCacheError> {
+		let path = path.as_ref();
+		let io_error = |path: &Path| {
+			let path = path.to_owned();
+			move |source| SongCacheError::Io { path, source }
+		};
+
+		let mut index = Self::new();
+		for entry in std::fs::read_dir(path).map_err(io_error(path))? {
+			let file_path = entry.map_err(io_error(path))?.path();
+			if file_path.is_file() {
+				index.add_cache_file(&std::fs::read(&file_path).map_err(io_error(&file_path))?);
+			}
+		}
+		Ok(index)
+	}
+
+	/// Adds the charts of a song cache file, see [`parse_song_cache_file`]. If a chartkey is
+	/// already known, e.g. because the same chart is in multiple packs, the existing entry is kept
+	pub fn add_cache_file(&mut self, bytes: &[u8]) {
+		for chart in parse_song_cache_file(bytes) {
+			self.charts.entry(chart.chartkey.clone()).or_insert(chart);
+		}
+	}
+
+	pub fn get(&self, chartkey: &crate::Chartkey) -> Option<&CachedChart> {
+		self.charts.get(chartkey)
+	}
+
+	/// Number of indexed charts
+	pub fn len(&self) -> usize {
+		self.charts.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.charts.is_empty()
+	}
+
+	/// Iterates over all indexed charts, in no particular order
+	pub fn iter(&self) -> impl Iterator<Item = &CachedChart> {
+		self.charts.values()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_song_cache() {
+		let chartkey = |digit: char| {
+			crate::Chartkey::new(format!(
+				"X{}",
+				std::iter::repeat_n(digit, 40).collect::<String>()
+			))
+			.unwrap()
+		};
+		let mut index = SongCacheIndex::new();
+		index.add_cache_file(
+			b"
+#SONGFILENAME:/Songs/A/Song/song.ssc;
+#TITLE:Song;
+#ARTIST:Artist;
+#NOTEDATA:;
+#STEPSTYPE:dance-single;
+#DIFFICULTY:Easy;
+#METER:3;
+#CHARTKEY:X1111111111111111111111111111111111111111;
+#NOTES:
+1000
+;
+#NOTEDATA:;
+#DIFFICULTY:Hard;
+#CHARTKEY:invalid;
+#NOTEDATA:;
+#STEPSTYPE:dance-solo;
+#CHARTKEY:X2222222222222222222222222222222222222222;
+",
+		);
+		// the same chart in another pack doesn't replace the first entry
+		index.add_cache_file(
+			b"#SONGFILENAME:/Songs/B/Song/song.sm;#NOTEDATA:;\
+			#CHARTKEY:X1111111111111111111111111111111111111111;",
+		);
+
+		assert_eq!(index.len(), 2);
+		assert_eq!(
+			index.get(&chartkey('1')),
+			Some(&CachedChart {
+				chartkey: chartkey('1'),
+				song_path: Some("/Songs/A/Song/song.ssc".into()),
+				title: Some("Song".into()),
+				artist: Some("Artist".into()),
+				steps_type: "dance-single".into(),
+				difficulty: Some(crate::Difficulty::Easy),
+				meter: Some(3),
+			})
+		);
+		let chart = index.get(&chartkey('2')).unwrap();
+		assert_eq!(chart.steps_type, "dance-solo");
+		assert_eq!(chart.difficulty, None);
+	}
+}
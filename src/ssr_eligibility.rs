@@ -0,0 +1,60 @@
+//! Rules for which scores get an SSR, and which wifescore the calculator actually rates, as they
+//! changed over game versions. Offline calculators should apply these before invoking an
+//! [`crate::SsrProvider`], to match the game.
+
+/// The highest wifescore that the difficulty calculator rates. Scores above it get the same SSR as
+/// a score exactly at the cap, which keeps near-perfect scores from scaling out of control.
+///
+/// 96.5% since 0.70, 96% before. The game applies it in `MinaSDCalc` (`MinaCalc.cpp`), by
+/// clamping the score goal before running the calculator.
+///
+/// ```rust
+/// # use etterna::*;
+/// assert_eq!(Some(ssr_wifescore_cap(EtternaVersion::LATEST)), Wifescore::from_percent(96.5));
+/// assert_eq!(Some(ssr_wifescore_cap(EtternaVersion::V0_68)), Wifescore::from_percent(96.0));
+/// ```
+pub fn ssr_wifescore_cap(version: crate::EtternaVersion) -> crate::Wifescore {
+	let cap = if version.is_pre_070() { 0.96 } else { 0.965 };
+	// UNWRAP: the caps are valid proportions
+	crate::Wifescore::from_proportion(cap).unwrap()
+}
+
+/// Whether a score with this wifescore gets an SSR at all. The game resets the SSR of scores at or
+/// below 0% to zero in every version; see `ScoreManager::RecalculateSSRs` (`ScoreManager.cpp`),
+/// which resets the skillsets of such scores instead of running the calculator.
+///
+/// This only covers the wifescore; scores can also be ineligible for reasons outside of this crate's
+/// knowledge, like invalidating mods or chord cohesion.
+///
+/// ```rust
+/// # use etterna::*;
+/// assert!(is_ssr_eligible(Wifescore::from_percent(50.0).unwrap()));
+/// assert!(!is_ssr_eligible(Wifescore::from_percent(0.0).unwrap()));
+/// assert!(!is_ssr_eligible(Wifescore::NEGATIVE_INFINITY));
+/// ```
+pub fn is_ssr_eligible(wifescore: crate::Wifescore) -> bool {
+	wifescore.as_proportion() > 0.0
+}
+
+/// Returns the wifescore that the calculator should rate, i.e. the wifescore capped at
+/// [`ssr_wifescore_cap`]. Returns None if the score isn't eligible for an SSR, see
+/// [`is_ssr_eligible`]
+///
+/// ```rust
+/// # use etterna::*;
+/// let capped = apply_ssr_cap(Wifescore::from_percent(99.7).unwrap(), EtternaVersion::LATEST);
+/// assert_eq!(capped, Some(ssr_wifescore_cap(EtternaVersion::LATEST)));
+///
+/// let wifescore = Wifescore::from_percent(93.0).unwrap();
+/// assert_eq!(apply_ssr_cap(wifescore, EtternaVersion::LATEST), Some(wifescore));
+/// assert_eq!(apply_ssr_cap(Wifescore::from_percent(-5.0).unwrap(), EtternaVersion::LATEST), None);
+/// ```
+pub fn apply_ssr_cap(
+	wifescore: crate::Wifescore,
+	version: crate::EtternaVersion,
+) -> Option<crate::Wifescore> {
+	if !is_ssr_eligible(wifescore) {
+		return None;
+	}
+	Some(wifescore.min(ssr_wifescore_cap(version)))
+}
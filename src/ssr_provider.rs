@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+/// Hook for plugging an MSD calculator (e.g. bindings to MinaCalc) into this crate. See
+/// [`SsrCache`] for a caching wrapper.
+///
+/// Implementors are responsible for looking up the note data of a chart by its chartkey.
+pub trait SsrProvider {
+	/// Calculates the MSD (difficulty) of the given chart at the given rate. Returns None if the
+	/// chart is unknown
+	fn calc_msd(&self, chartkey: &crate::Chartkey, rate: crate::Rate) -> Option<crate::Skillsets8>;
+
+	/// Calculates the SSR of a score with the given wifescore on the given chart and rate. Returns
+	/// None if the chart is unknown
+	fn calc_ssr(
+		&self,
+		chartkey: &crate::Chartkey,
+		rate: crate::Rate,
+		wifescore: crate::Wifescore,
+	) -> Option<crate::Skillsets8>;
+}
+
+/// A stand-in [`SsrProvider`] for tests. Every chart has the same MSD, which scales linearly with
+/// the rate, and SSRs scale linearly with the wifescore, reaching the MSD at 93%.
+///
+/// The numbers have nothing to do with the real difficulty calculator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DummySsrProvider {
+	/// MSD of every chart at 1.00x
+	pub msd: f32,
+}
+
+impl SsrProvider for DummySsrProvider {
+	fn calc_msd(&self, _: &crate::Chartkey, rate: crate::Rate) -> Option<crate::Skillsets8> {
+		Some(crate::Skillsets8::generate(|_| self.msd * rate.as_f32()))
+	}
+
+	fn calc_ssr(
+		&self,
+		chartkey: &crate::Chartkey,
+		rate: crate::Rate,
+		wifescore: crate::Wifescore,
+	) -> Option<crate::Skillsets8> {
+		let factor = wifescore.as_proportion().max(0.0) / 0.93;
+		let msd = self.calc_msd(chartkey, rate)?;
+		Some(crate::Skillsets8::generate(|ss| msd.get(ss) * factor))
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+struct CacheEntry {
+	msd: Option<Option<crate::Skillsets8>>,
+	// Keyed by the bits of the wifescore's proportion
+	ssrs: HashMap<u32, Option<crate::Skillsets8>>,
+}
+
+/// Wraps an [`SsrProvider`] and caches its results by chartkey and rate, so that repeated
+/// requests, e.g. when recalculating a player's entire score history, don't invoke the potentially
+/// expensive calculator again.
+///
+/// ```rust
+/// # use etterna::*;
+/// let chartkey = Chartkey::new("X0123456789abcdef0123456789abcdef01234567".into()).unwrap();
+/// let rate = Rate::from_f32(1.5).unwrap();
+///
+/// let mut cache = SsrCache::new(DummySsrProvider { msd: 20.0 });
+/// assert_eq!(cache.msd(&chartkey, rate).unwrap().stream, 30.0);
+///
+/// let ssr = cache.ssr(&chartkey, rate, Wifescore::from_percent(93.0).unwrap()).unwrap();
+/// assert_eq!(ssr.stream, 30.0);
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SsrCache<P> {
+	provider: P,
+	entries: HashMap<(crate::Chartkey, crate::Rate), CacheEntry>,
+}
+
+impl<P: SsrProvider> SsrCache<P> {
+	pub fn new(provider: P) -> Self {
+		Self {
+			provider,
+			entries: HashMap::new(),
+		}
+	}
+
+	pub fn provider(&self) -> &P {
+		&self.provider
+	}
+
+	/// Returns the MSD of the given chart and rate, invoking the provider only on a cache miss
+	pub fn msd(
+		&mut self,
+		chartkey: &crate::Chartkey,
+		rate: crate::Rate,
+	) -> Option<crate::Skillsets8> {
+		let provider = &self.provider;
+		self.entries
+			.entry((chartkey.clone(), rate))
+			.or_default()
+			.msd
+			.get_or_insert_with(|| provider.calc_msd(chartkey, rate))
+			.clone()
+	}
+
+	/// Returns the SSR of a score with the given wifescore on the given chart and rate, invoking
+	/// the provider only on a cache miss
+	pub fn ssr(
+		&mut self,
+		chartkey: &crate::Chartkey,
+		rate: crate::Rate,
+		wifescore: crate::Wifescore,
+	) -> Option<crate::Skillsets8> {
+		let provider = &self.provider;
+		self.entries
+			.entry((chartkey.clone(), rate))
+			.or_default()
+			.ssrs
+			.entry(wifescore.as_proportion().to_bits())
+			.or_insert_with(|| provider.calc_ssr(chartkey, rate, wifescore))
+			.clone()
+	}
+
+	/// Number of chart and rate combinations in the cache
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Removes all cached results
+	pub fn clear(&mut self) {
+		self.entries.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Counts how often the calculator was invoked
+	struct CountingProvider(std::cell::Cell<u32>);
+
+	impl SsrProvider for CountingProvider {
+		fn calc_msd(&self, c: &crate::Chartkey, r: crate::Rate) -> Option<crate::Skillsets8> {
+			self.0.set(self.0.get() + 1);
+			DummySsrProvider { msd: 10.0 }.calc_msd(c, r)
+		}
+
+		fn calc_ssr(
+			&self,
+			c: &crate::Chartkey,
+			r: crate::Rate,
+			w: crate::Wifescore,
+		) -> Option<crate::Skillsets8> {
+			self.0.set(self.0.get() + 1);
+			DummySsrProvider { msd: 10.0 }.calc_ssr(c, r, w)
+		}
+	}
+
+	#[test]
+	fn test_ssr_cache() {
+		let chartkey =
+			crate::Chartkey::new("X0123456789abcdef0123456789abcdef01234567".into()).unwrap();
+		let rate = |r| crate::Rate::from_f32(r).unwrap();
+		let wifescore = |percent| crate::Wifescore::from_percent(percent).unwrap();
+
+		let mut cache = SsrCache::new(CountingProvider(std::cell::Cell::new(0)));
+		cache.msd(&chartkey, rate(1.0));
+		cache.msd(&chartkey, rate(1.0));
+		cache.ssr(&chartkey, rate(1.0), wifescore(90.0));
+		cache.ssr(&chartkey, rate(1.0), wifescore(90.0));
+		assert_eq!(cache.provider().0.get(), 2);
+
+		let ssr_a = cache.ssr(&chartkey, rate(1.0), wifescore(95.0)).unwrap();
+		let ssr_b = cache.ssr(&chartkey, rate(1.1), wifescore(95.0)).unwrap();
+		assert!(ssr_b.overall > ssr_a.overall);
+		assert_eq!(cache.provider().0.get(), 4);
+		assert_eq!(cache.len(), 2);
+
+		cache.clear();
+		assert!(cache.is_empty());
+	}
+}
@@ -15,13 +15,13 @@ impl Difficulty {
 	///
 	/// This function is case insensitive
 	pub fn from_short_string(string: &str) -> Option<Self> {
-		match string.to_ascii_uppercase().as_str() {
-			"BG" => Some(Self::Beginner),
-			"EZ" => Some(Self::Easy),
-			"NM" => Some(Self::Medium),
-			"HD" => Some(Self::Hard),
-			"IN" => Some(Self::Challenge),
-			"ED" => Some(Self::Edit),
+		match crate::aliases::normalize_user_input(string).as_str() {
+			"bg" => Some(Self::Beginner),
+			"ez" => Some(Self::Easy),
+			"nm" => Some(Self::Medium),
+			"hd" => Some(Self::Hard),
+			"in" => Some(Self::Challenge),
+			"ed" => Some(Self::Edit),
 			_ => None,
 		}
 	}
@@ -31,7 +31,7 @@ impl Difficulty {
 	///
 	/// This function is case insensitive
 	pub fn from_long_string(string: &str) -> Option<Self> {
-		match string.to_ascii_lowercase().as_str() {
+		match crate::aliases::normalize_user_input(string).as_str() {
 			"beginner" | "novice" => Some(Self::Beginner),
 			"easy" => Some(Self::Easy),
 			"medium" | "normal" => Some(Self::Medium),
@@ -42,6 +42,21 @@ impl Difficulty {
 		}
 	}
 
+	/// Parses either a long or a short difficulty string, see [`Self::from_long_string`] and
+	/// [`Self::from_short_string`]. This function is case insensitive
+	pub fn from_user_input(input: &str) -> Option<Self> {
+		Self::from_long_string(input).or_else(|| Self::from_short_string(input))
+	}
+
+	/// Like [`Self::from_user_input`], but additionally recognizes the spellings registered in
+	/// `aliases`. See [`crate::AliasTable`]
+	pub fn from_user_input_with_aliases(
+		input: &str,
+		aliases: &crate::AliasTable<Self>,
+	) -> Option<Self> {
+		Self::from_user_input(input).or_else(|| aliases.get(input))
+	}
+
 	/// Generate a short difficulty string as found on the Etterna evaluation screen.
 	pub fn to_short_string(self) -> &'static str {
 		match self {
@@ -64,11 +79,27 @@ impl std::fmt::Display for DifficultyParseError {
 }
 impl std::error::Error for DifficultyParseError {}
 
+/// Accepts both long and short difficulty strings, case-insensitively
 impl std::str::FromStr for Difficulty {
 	type Err = DifficultyParseError;
 
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		Self::from_long_string(s).ok_or(DifficultyParseError)
+		Self::from_user_input(s).ok_or(DifficultyParseError)
+	}
+}
+
+impl std::convert::TryFrom<&str> for Difficulty {
+	type Error = DifficultyParseError;
+
+	fn try_from(s: &str) -> Result<Self, Self::Error> {
+		s.parse()
+	}
+}
+
+/// Prints the short difficulty string, see [`Difficulty::to_short_string`]
+impl std::fmt::Display for Difficulty {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.to_short_string())
 	}
 }
 
@@ -100,6 +131,198 @@ pub struct TapJudgements {
 	pub misses: u32,
 }
 
+impl FullJudgements {
+	/// Assembles judgement data from its parts. See also the conversions to [`TapJudgements`] and
+	/// [`HoldJudgements`]
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let taps = TapJudgements { marvelouses: 100, perfects: 3, ..Default::default() };
+	/// let holds = HoldJudgements { held_holds: 5, let_go_holds: 1, ..Default::default() };
+	///
+	/// let judgements = FullJudgements::from_parts(taps.clone(), holds.clone(), 2);
+	/// assert_eq!(judgements[TapJudgement::Perfect], 3);
+	/// assert_eq!(judgements[HoldJudgement::LetGo], 1);
+	/// assert_eq!(TapJudgements::from(judgements.clone()), taps);
+	/// assert_eq!(HoldJudgements::from(judgements), holds);
+	/// ```
+	pub fn from_parts(taps: TapJudgements, holds: HoldJudgements, hit_mines: u32) -> Self {
+		Self {
+			marvelouses: taps.marvelouses,
+			perfects: taps.perfects,
+			greats: taps.greats,
+			goods: taps.goods,
+			bads: taps.bads,
+			misses: taps.misses,
+			hit_mines,
+			held_holds: holds.held_holds,
+			let_go_holds: holds.let_go_holds,
+			missed_holds: holds.missed_holds,
+		}
+	}
+}
+
+impl std::ops::Index<crate::TapJudgement> for FullJudgements {
+	type Output = u32;
+
+	fn index(&self, index: crate::TapJudgement) -> &Self::Output {
+		match index {
+			crate::TapJudgement::Marvelous => &self.marvelouses,
+			crate::TapJudgement::Perfect => &self.perfects,
+			crate::TapJudgement::Great => &self.greats,
+			crate::TapJudgement::Good => &self.goods,
+			crate::TapJudgement::Bad => &self.bads,
+			crate::TapJudgement::Miss => &self.misses,
+		}
+	}
+}
+
+impl std::ops::IndexMut<crate::TapJudgement> for FullJudgements {
+	fn index_mut(&mut self, index: crate::TapJudgement) -> &mut Self::Output {
+		match index {
+			crate::TapJudgement::Marvelous => &mut self.marvelouses,
+			crate::TapJudgement::Perfect => &mut self.perfects,
+			crate::TapJudgement::Great => &mut self.greats,
+			crate::TapJudgement::Good => &mut self.goods,
+			crate::TapJudgement::Bad => &mut self.bads,
+			crate::TapJudgement::Miss => &mut self.misses,
+		}
+	}
+}
+
+impl std::ops::Index<HoldJudgement> for FullJudgements {
+	type Output = u32;
+
+	fn index(&self, index: HoldJudgement) -> &Self::Output {
+		match index {
+			HoldJudgement::Held => &self.held_holds,
+			HoldJudgement::LetGo => &self.let_go_holds,
+			HoldJudgement::Missed => &self.missed_holds,
+		}
+	}
+}
+
+impl std::ops::IndexMut<HoldJudgement> for FullJudgements {
+	fn index_mut(&mut self, index: HoldJudgement) -> &mut Self::Output {
+		match index {
+			HoldJudgement::Held => &mut self.held_holds,
+			HoldJudgement::LetGo => &mut self.let_go_holds,
+			HoldJudgement::Missed => &mut self.missed_holds,
+		}
+	}
+}
+
+/// Result of a hold note
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HoldJudgement {
+	/// The hold was held until the end
+	Held,
+	/// The hold was hit, but let go too early
+	LetGo,
+	/// The hold head was missed
+	Missed,
+}
+
+impl HoldJudgement {
+	/// Get a list of all hold judgements
+	pub fn list() -> &'static [Self] {
+		&[Self::Held, Self::LetGo, Self::Missed]
+	}
+}
+
+/// Judgement data, only the hold results
+#[derive(Debug, Eq, PartialEq, Clone, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HoldJudgements {
+	pub held_holds: u32,
+	pub let_go_holds: u32,
+	pub missed_holds: u32,
+}
+
+impl From<FullJudgements> for HoldJudgements {
+	fn from(judgements: FullJudgements) -> Self {
+		Self {
+			held_holds: judgements.held_holds,
+			let_go_holds: judgements.let_go_holds,
+			missed_holds: judgements.missed_holds,
+		}
+	}
+}
+
+impl std::ops::Index<HoldJudgement> for HoldJudgements {
+	type Output = u32;
+
+	fn index(&self, index: HoldJudgement) -> &Self::Output {
+		match index {
+			HoldJudgement::Held => &self.held_holds,
+			HoldJudgement::LetGo => &self.let_go_holds,
+			HoldJudgement::Missed => &self.missed_holds,
+		}
+	}
+}
+
+impl std::ops::IndexMut<HoldJudgement> for HoldJudgements {
+	fn index_mut(&mut self, index: HoldJudgement) -> &mut Self::Output {
+		match index {
+			HoldJudgement::Held => &mut self.held_holds,
+			HoldJudgement::LetGo => &mut self.let_go_holds,
+			HoldJudgement::Missed => &mut self.missed_holds,
+		}
+	}
+}
+
+// Implements element-wise Add, AddAssign and Sum for a judgements struct with the given fields
+macro_rules! impl_judgements_arithmetic {
+	($type:ty, $($field:ident),*) => {
+		impl std::ops::Add for $type {
+			type Output = Self;
+
+			fn add(self, other: Self) -> Self {
+				Self {
+					$($field: self.$field + other.$field,)*
+				}
+			}
+		}
+
+		impl std::ops::AddAssign for $type {
+			fn add_assign(&mut self, other: Self) {
+				$(self.$field += other.$field;)*
+			}
+		}
+
+		impl std::iter::Sum for $type {
+			fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+				iter.fold(Self::default(), |a, b| a + b)
+			}
+		}
+	};
+}
+
+impl_judgements_arithmetic!(
+	FullJudgements,
+	marvelouses,
+	perfects,
+	greats,
+	goods,
+	bads,
+	misses,
+	hit_mines,
+	held_holds,
+	let_go_holds,
+	missed_holds
+);
+impl_judgements_arithmetic!(
+	TapJudgements,
+	marvelouses,
+	perfects,
+	greats,
+	goods,
+	bads,
+	misses
+);
+impl_judgements_arithmetic!(HoldJudgements, held_holds, let_go_holds, missed_holds);
+
 impl From<FullJudgements> for TapJudgements {
 	fn from(judgements: FullJudgements) -> Self {
 		Self {
@@ -141,6 +364,152 @@ impl std::ops::IndexMut<crate::TapJudgement> for TapJudgements {
 	}
 }
 
+impl TapJudgements {
+	/// Total number of judged notes
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let scores = vec![
+	/// 	TapJudgements { marvelouses: 10, goods: 1, ..Default::default() },
+	/// 	TapJudgements { marvelouses: 5, misses: 2, ..Default::default() },
+	/// ];
+	/// let combined: TapJudgements = scores.into_iter().sum();
+	/// assert_eq!(combined.total(), 18);
+	/// assert_eq!(combined.cb_count(), 3);
+	/// assert_eq!(combined.ratio_of(TapJudgement::Marvelous), Some(15.0 / 18.0));
+	/// ```
+	pub fn total(&self) -> u32 {
+		self.marvelouses + self.perfects + self.greats + self.goods + self.bads + self.misses
+	}
+
+	/// Number of combo breakers, i.e. goods, bads and misses
+	pub fn cb_count(&self) -> u32 {
+		self.goods + self.bads + self.misses
+	}
+
+	/// Proportion of notes with the given judgement, from 0.0 to 1.0. Returns None if there are no
+	/// judgements
+	pub fn ratio_of(&self, judgement: crate::TapJudgement) -> Option<f32> {
+		match self.total() {
+			0 => None,
+			total => Some(self[judgement] as f32 / total as f32),
+		}
+	}
+
+	/// Ratio of marvelouses to perfects, a common measure of accuracy beyond the wifescore.
+	/// Infinite if there are no perfects, or NaN if there are neither marvelouses nor perfects
+	///
+	/// ```rust
+	/// # use etterna::TapJudgements;
+	/// let judgements = TapJudgements { marvelouses: 900, perfects: 100, ..Default::default() };
+	/// assert_eq!(judgements.marv_perf_ratio(), 9.0);
+	/// ```
+	pub fn marv_perf_ratio(&self) -> f32 {
+		self.marvelouses as f32 / self.perfects as f32
+	}
+
+	/// Calculates the lowest and highest wifescore that a score with these judgements could have,
+	/// using the given scoring system and judge. The bounds are reached if every note was hit at
+	/// the outer or inner edge of its judgement's window, respectively. Mine hits and hold drops
+	/// are not taken into account.
+	///
+	/// Useful for ranking scores for which no replay is available.
+	///
+	/// Returns None if there are no judgements
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let judgements = TapJudgements { marvelouses: 90, perfects: 9, misses: 1, ..Default::default() };
+	/// let (min, max) = judgements.wifescore_bounds::<Wife3>(J4).unwrap();
+	/// assert!(min < max);
+	///
+	/// let all_marvs = TapJudgements { marvelouses: 10, ..Default::default() };
+	/// let (min, max) = all_marvs.wifescore_bounds::<Wife3>(J4).unwrap();
+	/// assert_eq!(max, Wifescore::HUNDRED_PERCENT);
+	/// assert!(min > Wifescore::AA_THRESHOLD);
+	/// ```
+	pub fn wifescore_bounds<W: crate::Wife>(
+		&self,
+		judge: &crate::Judge,
+	) -> Option<(crate::Wifescore, crate::Wifescore)> {
+		use crate::TapJudgement::*;
+
+		let mut num_notes = 0;
+		let mut min_points = crate::util::WifeSum::default();
+		let mut max_points = crate::util::WifeSum::default();
+		for &(judgement, inner_edge, outer_edge) in &[
+			(Marvelous, 0.0, judge.marvelous_window),
+			(Perfect, judge.marvelous_window, judge.perfect_window),
+			(Great, judge.perfect_window, judge.great_window),
+			(Good, judge.great_window, judge.good_window),
+			(Bad, judge.good_window, judge.bad_window),
+		] {
+			let count = self[judgement];
+			num_notes += count;
+			// Wife curves are monotonically decreasing with the deviation
+			min_points += count as f32 * W::calc_deviation(outer_edge, judge);
+			max_points += count as f32 * W::calc_deviation(inner_edge, judge);
+		}
+		num_notes += self.misses;
+		min_points += self.misses as f32 * W::MISS_WEIGHT;
+		max_points += self.misses as f32 * W::MISS_WEIGHT;
+
+		if num_notes == 0 {
+			return None;
+		}
+		Some((
+			crate::Wifescore::from_proportion(min_points.get() / num_notes as f32)?,
+			crate::Wifescore::from_proportion(max_points.get() / num_notes as f32)?,
+		))
+	}
+}
+
+/// Wife points lost on notes of each judgement. See [`SimpleReplay::wife_points_lost_by_judgement`]
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WifeLossByJudgement {
+	pub marvelouses: f32,
+	pub perfects: f32,
+	pub greats: f32,
+	pub goods: f32,
+	pub bads: f32,
+	pub misses: f32,
+}
+
+impl WifeLossByJudgement {
+	pub fn total(&self) -> f32 {
+		self.marvelouses + self.perfects + self.greats + self.goods + self.bads + self.misses
+	}
+}
+
+impl std::ops::Index<crate::TapJudgement> for WifeLossByJudgement {
+	type Output = f32;
+
+	fn index(&self, index: crate::TapJudgement) -> &Self::Output {
+		match index {
+			crate::TapJudgement::Marvelous => &self.marvelouses,
+			crate::TapJudgement::Perfect => &self.perfects,
+			crate::TapJudgement::Great => &self.greats,
+			crate::TapJudgement::Good => &self.goods,
+			crate::TapJudgement::Bad => &self.bads,
+			crate::TapJudgement::Miss => &self.misses,
+		}
+	}
+}
+
+impl std::ops::IndexMut<crate::TapJudgement> for WifeLossByJudgement {
+	fn index_mut(&mut self, index: crate::TapJudgement) -> &mut Self::Output {
+		match index {
+			crate::TapJudgement::Marvelous => &mut self.marvelouses,
+			crate::TapJudgement::Perfect => &mut self.perfects,
+			crate::TapJudgement::Great => &mut self.greats,
+			crate::TapJudgement::Good => &mut self.goods,
+			crate::TapJudgement::Bad => &mut self.bads,
+			crate::TapJudgement::Miss => &mut self.misses,
+		}
+	}
+}
+
 /// Type of a note
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -156,8 +525,12 @@ pub enum NoteType {
 
 /// Wifescore struct. Guaranteed to be a valid value, i.e. <= 100% and not NaN (may be negative
 /// infinity though)
-#[derive(PartialEq, PartialOrd, Default, Copy, Clone, Debug)]
+///
+/// Wifescores are totally ordered, with [`Wifescore::NEGATIVE_INFINITY`] as the lowest and
+/// [`Wifescore::HUNDRED_PERCENT`] as the highest possible value
+#[derive(PartialEq, Default, Copy, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "f32", into = "f32"))]
 pub struct Wifescore {
 	proportion: f32,
 }
@@ -166,6 +539,8 @@ impl Wifescore {
 	pub const NEG_INFINITY: Self = Self {
 		proportion: f32::NEG_INFINITY,
 	};
+	/// Same as [`Self::NEG_INFINITY`]. The lowest possible wifescore
+	pub const NEGATIVE_INFINITY: Self = Self::NEG_INFINITY;
 	pub const C_THRESHOLD: Self = Self { proportion: 0.60 };
 	pub const B_THRESHOLD: Self = Self { proportion: 0.70 };
 	pub const A_THRESHOLD: Self = Self { proportion: 0.80 };
@@ -188,13 +563,88 @@ impl Wifescore {
 
 	/// Makes a Wifescore from a value, assumed to be scaled to a max of 1
 	///
-	/// Returns None if the proportion is over 1.0 (100%), or if it is infinite or NaN
+	/// Returns None if the proportion is over 1.0 (100%), or if it is NaN
 	pub fn from_proportion(proportion: f32) -> Option<Self> {
-		// if proportion.is_nan() || proportion > 1.0 {
-		// None
-		// } else {
-		Some(Self { proportion })
-		// }
+		if proportion.is_nan() || proportion > 1.0 {
+			None
+		} else {
+			Some(Self { proportion })
+		}
+	}
+
+	/// Makes a Wifescore from a value, assumed to be scaled to a max of 1. Unlike
+	/// [`Self::from_proportion`], this never fails: values over 1.0 are clamped to 100%, and NaN is
+	/// mapped to [`Self::NEGATIVE_INFINITY`]
+	///
+	/// ```rust
+	/// # use etterna::Wifescore;
+	/// assert_eq!(Wifescore::from_proportion_clamped(1.2), Wifescore::HUNDRED_PERCENT);
+	/// assert_eq!(Wifescore::from_proportion_clamped(f32::NAN), Wifescore::NEGATIVE_INFINITY);
+	/// assert_eq!(Wifescore::from_proportion_clamped(-0.5).as_percent(), -50.0);
+	/// ```
+	pub fn from_proportion_clamped(proportion: f32) -> Self {
+		if proportion.is_nan() {
+			Self::NEGATIVE_INFINITY
+		} else {
+			Self {
+				proportion: proportion.min(1.0),
+			}
+		}
+	}
+
+	/// Like [`Self::from_proportion_clamped`], but for a value scaled to a max of 100
+	pub fn from_percent_clamped(percent: f32) -> Self {
+		Self::from_proportion_clamped(percent / 100.0)
+	}
+
+	/// Averages the given wifescores. Returns None if the slice is empty
+	///
+	/// ```rust
+	/// # use etterna::Wifescore;
+	/// let wifescores = [Wifescore::from_percent(50.0).unwrap(), Wifescore::HUNDRED_PERCENT];
+	/// assert_eq!(Wifescore::average(&wifescores).unwrap().as_percent(), 75.0);
+	/// assert_eq!(Wifescore::average(&[]), None);
+	/// ```
+	pub fn average(wifescores: &[Self]) -> Option<Self> {
+		if wifescores.is_empty() {
+			return None;
+		}
+		let sum: f32 = wifescores.iter().map(|w| w.proportion).sum();
+		Some(Self::from_proportion_clamped(sum / wifescores.len() as f32))
+	}
+
+	/// Returns the highest of the given wifescores, or None if the slice is empty
+	///
+	/// ```rust
+	/// # use etterna::Wifescore;
+	/// let wifescores = [Wifescore::from_percent(93.0).unwrap(), Wifescore::NEGATIVE_INFINITY];
+	/// assert_eq!(Wifescore::max_of(&wifescores), Some(wifescores[0]));
+	/// assert_eq!(Wifescore::min_of(&wifescores), Some(Wifescore::NEGATIVE_INFINITY));
+	/// ```
+	pub fn max_of(wifescores: &[Self]) -> Option<Self> {
+		wifescores.iter().copied().max()
+	}
+
+	/// Returns the lowest of the given wifescores, or None if the slice is empty
+	pub fn min_of(wifescores: &[Self]) -> Option<Self> {
+		wifescores.iter().copied().min()
+	}
+
+	/// Returns the grade that this wifescore achieves
+	///
+	/// ```rust
+	/// # use etterna::{Grade, Wifescore};
+	/// assert_eq!(Wifescore::from_percent(93.0).unwrap().grade(), Grade::AA);
+	/// assert_eq!(Wifescore::from_percent(92.99).unwrap().grade(), Grade::A);
+	/// assert_eq!(Wifescore::NEGATIVE_INFINITY.grade(), Grade::D);
+	/// ```
+	pub fn grade(self) -> Grade {
+		Grade::list()
+			.iter()
+			.rev()
+			.copied()
+			.find(|grade| self.proportion >= grade.threshold().proportion)
+			.unwrap_or(Grade::D)
 	}
 
 	/// Returns the wifescore, scaled to a max of 100
@@ -206,26 +656,249 @@ impl Wifescore {
 	pub fn as_proportion(self) -> f32 {
 		self.proportion
 	}
+
+	/// Formats the wifescore as a percentage with the given number of decimal places. Same as the
+	/// [`Display`](std::fmt::Display) impl with a precision, e.g. `{:.4}`
+	///
+	/// ```rust
+	/// # use etterna::Wifescore;
+	/// let wifescore = Wifescore::from_percent(99.7512).unwrap();
+	/// assert_eq!(wifescore.format(4), "99.7512%");
+	/// assert_eq!(wifescore.format(0), "100%");
+	/// assert_eq!(wifescore.to_string(), "99.75%");
+	/// ```
+	pub fn format(self, precision: usize) -> String {
+		format!("{:.*}", precision, self)
+	}
+
+	/// Like [`Self::format`], followed by the grade in parentheses
+	///
+	/// ```rust
+	/// # use etterna::Wifescore;
+	/// let wifescore = Wifescore::from_percent(99.7512).unwrap();
+	/// assert_eq!(wifescore.format_with_grade(2), "99.75% (AAA)");
+	/// ```
+	pub fn format_with_grade(self, precision: usize) -> String {
+		format!("{:.*} ({})", precision, self, self.grade())
+	}
+
+	/// Linearly interpolates between two wifescores, e.g. to animate a score counter. `t` is
+	/// clamped to 0.0..=1.0, where 0.0 yields `a` and 1.0 yields `b`.
+	///
+	/// Interpolating from or to [`Self::NEGATIVE_INFINITY`] yields negative infinity for any `t`
+	/// below 1.0
+	///
+	/// ```rust
+	/// # use etterna::Wifescore;
+	/// let a = Wifescore::from_percent(90.0).unwrap();
+	/// let b = Wifescore::from_percent(100.0).unwrap();
+	/// assert_eq!(Wifescore::lerp(a, b, 0.5).as_percent(), 95.0);
+	/// assert_eq!(Wifescore::lerp(a, b, 2.0), b);
+	/// ```
+	pub fn lerp(a: Self, b: Self, t: f32) -> Self {
+		let t = t.clamp(0.0, 1.0);
+		if t == 0.0 {
+			a
+		} else if t == 1.0 {
+			b
+		} else {
+			Self::from_proportion_clamped(a.proportion + (b.proportion - a.proportion) * t)
+		}
+	}
 }
 
+/// Prints the percentage with two decimal places, or with the precision given in the format
+/// string. See also [`Wifescore::format`]
+///
+/// The output is rounded, so parsing it back with [`FromStr`](std::str::FromStr) generally yields a
+/// slightly different wifescore. To store wifescores losslessly, keep the proportion
+/// ([`Wifescore::as_proportion`]) or use the `serde` feature.
+///
+/// ```rust
+/// # use etterna::Wifescore;
+/// let wifescore = Wifescore::from_percent(93.456789).unwrap();
+/// assert_eq!(wifescore.to_string(), "93.46%");
+/// assert_ne!(wifescore.to_string().parse::<Wifescore>().unwrap(), wifescore);
+/// ```
 impl std::fmt::Display for Wifescore {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "{:.2}%", self.as_percent())
+		let precision = f.precision().unwrap_or(2);
+		write!(f, "{:.*}%", precision, self.as_percent())
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct WifescoreParseError;
+impl std::fmt::Display for WifescoreParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "invalid wifescore")
+	}
+}
+impl std::error::Error for WifescoreParseError {}
+
+/// Parses a percentage like `97.53%`, as printed by the [`Display`](std::fmt::Display) impl. The
+/// percent sign is optional. Since the printed percentage is rounded, this doesn't restore the
+/// exact original wifescore
+///
+/// ```rust
+/// # use etterna::Wifescore;
+/// let wifescore: Wifescore = "97.53%".parse().unwrap();
+/// assert_eq!(wifescore.to_string(), "97.53%");
+/// assert!("101%".parse::<Wifescore>().is_err());
+/// ```
+impl std::str::FromStr for Wifescore {
+	type Err = WifescoreParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let s = s.trim();
+		let s = s.strip_suffix('%').unwrap_or(s);
+		let percent: f32 = s.trim_end().parse().map_err(|_| WifescoreParseError)?;
+		Self::from_percent(percent).ok_or(WifescoreParseError)
+	}
+}
+
+impl std::convert::TryFrom<&str> for Wifescore {
+	type Error = WifescoreParseError;
+
+	fn try_from(s: &str) -> Result<Self, Self::Error> {
+		s.parse()
+	}
+}
+
+/// Interprets the value as a proportion, like [`Wifescore::from_proportion`]. This is also how
+/// wifescores are serialized
+impl std::convert::TryFrom<f32> for Wifescore {
+	type Error = WifescoreParseError;
+
+	fn try_from(proportion: f32) -> Result<Self, Self::Error> {
+		Self::from_proportion(proportion).ok_or(WifescoreParseError)
+	}
+}
+
+impl From<Wifescore> for f32 {
+	fn from(wifescore: Wifescore) -> f32 {
+		wifescore.as_proportion()
+	}
+}
+
+/// Adds up the proportions of both wifescores, saturating at 100%
+impl std::ops::Add for Wifescore {
+	type Output = Self;
+
+	fn add(self, other: Self) -> Self {
+		Self::from_proportion_clamped(self.proportion + other.proportion)
+	}
+}
+
+/// Grade of a score, determined by its wifescore. See [`Wifescore::grade`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Grade {
+	D,
+	C,
+	B,
+	A,
+	AA,
+	AAA,
+	AAAA,
+	AAAAA,
+}
+
+impl Grade {
+	/// Returns a list of all grades, from worst to best
+	pub fn list() -> &'static [Self] {
+		&[
+			Self::D,
+			Self::C,
+			Self::B,
+			Self::A,
+			Self::AA,
+			Self::AAA,
+			Self::AAAA,
+			Self::AAAAA,
+		]
+	}
+
+	/// Lowest wifescore that achieves this grade
+	pub fn threshold(self) -> Wifescore {
+		match self {
+			Self::D => Wifescore::NEGATIVE_INFINITY,
+			Self::C => Wifescore::C_THRESHOLD,
+			Self::B => Wifescore::B_THRESHOLD,
+			Self::A => Wifescore::A_THRESHOLD,
+			Self::AA => Wifescore::AA_THRESHOLD,
+			Self::AAA => Wifescore::AAA_THRESHOLD,
+			Self::AAAA => Wifescore::AAAA_THRESHOLD,
+			Self::AAAAA => Wifescore::AAAAA_THRESHOLD,
+		}
+	}
+}
+
+impl std::fmt::Display for Grade {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		std::fmt::Debug::fmt(self, f)
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct GradeParseError;
+impl std::fmt::Display for GradeParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "invalid grade")
+	}
+}
+impl std::error::Error for GradeParseError {}
+
+/// Parses a grade like `AAA` case-insensitively
+impl std::str::FromStr for Grade {
+	type Err = GradeParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::list()
+			.iter()
+			.copied()
+			.find(|grade| grade.to_string().eq_ignore_ascii_case(s))
+			.ok_or(GradeParseError)
+	}
+}
+
+impl std::convert::TryFrom<&str> for Grade {
+	type Error = GradeParseError;
+
+	fn try_from(s: &str) -> Result<Self, Self::Error> {
+		s.parse()
 	}
 }
 
-#[allow(clippy::derive_ord_xor_partial_ord)] // the reasoning doesn't apply here
 impl Ord for Wifescore {
 	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-		other
-			.partial_cmp(other)
+		// -0.0 and 0.0 compare equal here, which matches PartialEq
+		self.proportion
+			.partial_cmp(&other.proportion)
 			.expect("Can't happen; this wrapper guarantees non-NaN")
 	}
 }
 
+impl PartialOrd for Wifescore {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
 // This can't be a derive for whatever reason /shrug
 impl Eq for Wifescore {}
 
+/// Reason why a string isn't a valid [`Chartkey`] or [`Scorekey`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum KeyParseError {
+	#[error("Key must be 41 characters long, but is {0} characters long")]
+	InvalidLength(usize),
+	#[error("Key must start with '{expected}', but starts with '{found}'")]
+	InvalidPrefix { expected: char, found: char },
+	#[error("Invalid character '{character}' at index {index}, expected a lowercase hex digit")]
+	InvalidCharacter { index: usize, character: char },
+}
+
 // we need this wrapper because <!'#]]]A~REDÅCTED~{#"$ ")=}
 macro_rules! doc_comment {
 	($comment:expr, $($stuff:tt)*) => { #[doc = $comment] $($stuff)* };
@@ -236,8 +909,13 @@ macro_rules! doc_comment {
 macro_rules! etterna_data_key {
 	($name:ident, $name_lowercase:ident, $initial_letter:expr) => (
 		// TODO: maybe it's a good idea to represent this as [u8; 20] instead? not sure
-		doc_comment! { concat!("Represents an Etterna ", stringify!($name_lowercase)),
-			#[derive(Debug, Clone, Eq, PartialEq, Hash, /* NOT Default, it would produce an invalid state! */)]
+		doc_comment! { concat!(
+			"Represents an Etterna ", stringify!($name_lowercase), ".\n\n",
+			"Always stored in canonical form: the uppercase letter `", $initial_letter, "` followed by 40 ",
+			"lowercase hex digits. Use [`", stringify!($name), "::new_lenient`] to accept keys in ",
+			"other casings",
+		),
+			#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, /* NOT Default, it would produce an invalid state! */)]
 			#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 			pub struct $name(String);
 		}
@@ -245,11 +923,30 @@ macro_rules! etterna_data_key {
 		impl $name {
 			/// Check if the given string represents a valid key
 			pub fn is_valid(key: &str) -> bool {
+				Self::validate(key).is_ok()
+			}
+
+			/// Like [`Self::is_valid`], but describes what's wrong with an invalid key. Checks the
+			/// length first, then the initial letter, then the remaining characters
+			pub fn validate(key: &str) -> Result<(), KeyParseError> {
 				let initial_letter: char = $initial_letter;
 
-				key.len() == 41
-					&& key.starts_with(initial_letter)
-					&& !key[1..].contains(|c| (c < '0' || c > '9') && (c < 'a' || c > 'f'))
+				let length = key.chars().count();
+				if length != 41 {
+					return Err(KeyParseError::InvalidLength(length));
+				}
+				let mut chars = key.chars().enumerate();
+				// UNWRAP: the key has 41 characters
+				let (_, first) = chars.next().unwrap();
+				if first != initial_letter {
+					return Err(KeyParseError::InvalidPrefix { expected: initial_letter, found: first });
+				}
+				for (index, character) in chars {
+					if !character.is_ascii_digit() && !('a'..='f').contains(&character) {
+						return Err(KeyParseError::InvalidCharacter { index, character });
+					}
+				}
+				Ok(())
 			}
 
 			/// Create a new key from the given string, or None if the string is invalid.
@@ -261,6 +958,22 @@ macro_rules! etterna_data_key {
 				}
 			}
 
+			/// Like [`Self::new`], but accepts any casing by converting the key into canonical form
+			/// first. Useful when joining data from sources that disagree on casing
+			pub fn new_lenient(key: &str) -> Option<Self> {
+				let mut chars = key.chars();
+				let first = chars.next()?.to_ascii_uppercase();
+				let canonical: String = std::iter::once(first)
+					.chain(chars.map(|c| c.to_ascii_lowercase()))
+					.collect();
+				Self::new(canonical)
+			}
+
+			/// Compares this key with a string, ignoring ASCII case
+			pub fn eq_ignore_case(&self, other: &str) -> bool {
+				self.0.eq_ignore_ascii_case(other)
+			}
+
 			/// Get a reference to the underlying string
 			pub fn as_str(&self) -> &str {
 				&self.0
@@ -287,33 +1000,153 @@ macro_rules! etterna_data_key {
 		}
 
 		impl std::str::FromStr for $name {
-			type Err = ();
-			fn from_str(key: &str) -> Result<Self, ()> {
-				Self::new(key.to_owned()).ok_or(())
+			type Err = KeyParseError;
+			fn from_str(key: &str) -> Result<Self, KeyParseError> {
+				Self::validate(key)?;
+				Ok(Self(key.to_owned()))
 			}
 		}
-	)
+	)
+}
+
+etterna_data_key!(Scorekey, scorekey, 'S');
+etterna_data_key!(Chartkey, chartkey, 'X');
+
+/// Global ranks in each skillset category
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UserRank {
+	pub overall: u32,
+	pub stream: u32,
+	pub jumpstream: u32,
+	pub handstream: u32,
+	pub stamina: u32,
+	pub jackspeed: u32,
+	pub chordjack: u32,
+	pub technical: u32,
+}
+
+impl crate::GetSkillset for UserRank {
+	type Value = u32;
+
+	fn get_skillset(&self, skillset: impl Into<crate::Skillset8>) -> u32 {
+		self.get(skillset)
+	}
+}
+
+impl UserRank {
+	/// Get a specific skillset value
+	pub fn get(&self, skillset: impl Into<crate::Skillset8>) -> u32 {
+		match skillset.into() {
+			crate::Skillset8::Overall => self.overall,
+			crate::Skillset8::Stream => self.stream,
+			crate::Skillset8::Jumpstream => self.jumpstream,
+			crate::Skillset8::Handstream => self.handstream,
+			crate::Skillset8::Stamina => self.stamina,
+			crate::Skillset8::Jackspeed => self.jackspeed,
+			crate::Skillset8::Chordjack => self.chordjack,
+			crate::Skillset8::Technical => self.technical,
+		}
+	}
+
+	/// Iterate over all skillsets and their respective ranks, including overall
+	pub fn iter(&self) -> impl Iterator<Item = (crate::Skillset8, u32)> + '_ {
+		crate::Skillset8::iter().map(move |ss| (ss, self.get(ss)))
+	}
+
+	/// Returns the skillset in which the user is ranked best, i.e. has the lowest rank number. If
+	/// multiple skillsets share the best rank, the first in [`crate::Skillset8::list`] is returned.
+	///
+	/// ```rust
+	/// # use etterna::{UserRank, Skillset8};
+	/// let rank = UserRank {
+	/// 	overall: 50, stream: 40, jumpstream: 60, handstream: 70, stamina: 30,
+	/// 	jackspeed: 200, chordjack: 150, technical: 45,
+	/// };
+	/// assert_eq!(rank.best(), (Skillset8::Stamina, 30));
+	/// assert_eq!(rank.worst(), (Skillset8::Jackspeed, 200));
+	/// ```
+	pub fn best(&self) -> (crate::Skillset8, u32) {
+		// UNWRAP: there's always at least one skillset
+		self.iter().min_by_key(|&(_, rank)| rank).unwrap()
+	}
+
+	/// Returns the skillset in which the user is ranked worst, i.e. has the highest rank number.
+	/// If multiple skillsets share the worst rank, the first in [`crate::Skillset8::list`] is
+	/// returned.
+	pub fn worst(&self) -> (crate::Skillset8, u32) {
+		// max_by_key returns the last maximum element, so we search in reverse
+		// UNWRAP: there's always at least one skillset
+		crate::Skillset8::list()
+			.iter()
+			.rev()
+			.map(|&ss| (ss, self.get(ss)))
+			.max_by_key(|&(_, rank)| rank)
+			.unwrap()
+	}
+
+	/// Calculates the signed rank change per skillset from `other` to `self`. Negative values mean
+	/// that `self` is ranked better (the rank number decreased).
+	///
+	/// ```rust
+	/// # use etterna::UserRank;
+	/// let old = UserRank { overall: 50, stream: 40, ..Default::default() };
+	/// let new = UserRank { overall: 45, stream: 42, ..Default::default() };
+	/// let delta = new.delta(&old);
+	/// assert_eq!(delta.overall, -5);
+	/// assert_eq!(delta.stream, 2);
+	/// ```
+	pub fn delta(&self, other: &UserRank) -> UserRankDelta {
+		let diff = |ss| self.get(ss) as i64 - other.get(ss) as i64;
+		UserRankDelta {
+			overall: diff(crate::Skillset8::Overall),
+			stream: diff(crate::Skillset8::Stream),
+			jumpstream: diff(crate::Skillset8::Jumpstream),
+			handstream: diff(crate::Skillset8::Handstream),
+			stamina: diff(crate::Skillset8::Stamina),
+			jackspeed: diff(crate::Skillset8::Jackspeed),
+			chordjack: diff(crate::Skillset8::Chordjack),
+			technical: diff(crate::Skillset8::Technical),
+		}
+	}
 }
 
-etterna_data_key!(Scorekey, scorekey, 'S');
-etterna_data_key!(Chartkey, chartkey, 'X');
-
-/// Global ranks in each skillset category
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+/// Signed change in global ranks in each skillset category. See [`UserRank::delta`]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct UserRank {
-	pub overall: u32,
-	pub stream: u32,
-	pub jumpstream: u32,
-	pub handstream: u32,
-	pub stamina: u32,
-	pub jackspeed: u32,
-	pub chordjack: u32,
-	pub technical: u32,
+pub struct UserRankDelta {
+	pub overall: i64,
+	pub stream: i64,
+	pub jumpstream: i64,
+	pub handstream: i64,
+	pub stamina: i64,
+	pub jackspeed: i64,
+	pub chordjack: i64,
+	pub technical: i64,
 }
 
-impl UserRank {
-	crate::impl_get_skillset!(u32, a, a.overall);
+impl crate::GetSkillset for UserRankDelta {
+	type Value = i64;
+
+	fn get_skillset(&self, skillset: impl Into<crate::Skillset8>) -> i64 {
+		self.get(skillset)
+	}
+}
+
+impl UserRankDelta {
+	/// Get a specific skillset value
+	pub fn get(&self, skillset: impl Into<crate::Skillset8>) -> i64 {
+		match skillset.into() {
+			crate::Skillset8::Overall => self.overall,
+			crate::Skillset8::Stream => self.stream,
+			crate::Skillset8::Jumpstream => self.jumpstream,
+			crate::Skillset8::Handstream => self.handstream,
+			crate::Skillset8::Stamina => self.stamina,
+			crate::Skillset8::Jackspeed => self.jackspeed,
+			crate::Skillset8::Chordjack => self.chordjack,
+			crate::Skillset8::Technical => self.technical,
+		}
+	}
 }
 
 pub trait SimpleReplay {
@@ -322,6 +1155,38 @@ pub trait SimpleReplay {
 	// TODO
 	// fn rescore<W: crate::Wife>(&self) -> crate::Wifescore { todo!() }
 
+	/// Iterates over the deviations of all notes that were hit, in seconds. Misses are skipped
+	fn iter_deviations(&self) -> Box<dyn '_ + Iterator<Item = f32>> {
+		Box::new(self.iter_hits().filter_map(|hit| hit.deviation()))
+	}
+
+	/// Iterates over all hits whose absolute deviation is at most `window` seconds. Misses are
+	/// skipped
+	///
+	/// ```rust
+	/// # use etterna::{Hit, SimpleReplay};
+	/// struct Replay(Vec<Hit>);
+	/// impl SimpleReplay for Replay {
+	/// 	fn iter_hits(&self) -> Box<dyn '_ + Iterator<Item = Hit>> {
+	/// 		Box::new(self.0.iter().copied())
+	/// 	}
+	/// }
+	///
+	/// let replay = Replay(vec![
+	/// 	Hit::Hit { deviation: -0.010 },
+	/// 	Hit::Hit { deviation: 0.030 },
+	/// 	Hit::Miss,
+	/// ]);
+	/// assert_eq!(replay.iter_hits_within(0.0225).count(), 1);
+	/// assert_eq!(replay.iter_deviations().collect::<Vec<_>>(), vec![-0.010, 0.030]);
+	/// ```
+	fn iter_hits_within(&self, window: f32) -> Box<dyn '_ + Iterator<Item = crate::Hit>> {
+		Box::new(self.iter_hits().filter(move |hit| match hit.deviation() {
+			Some(deviation) => deviation.abs() <= window,
+			None => false,
+		}))
+	}
+
 	/// Finds the longest combo of notes evaluating to true in the given closure
 	///
 	/// The note deviations passed into the closure are always positive. In case of a miss, the
@@ -333,7 +1198,7 @@ pub trait SimpleReplay {
 	/// let longest_marvelous_combo = replay.longest_combo(|d| d.is_marv(etterna::J4));
 	/// ```
 	fn longest_combo(&self, hit_filter: impl FnMut(crate::Hit) -> bool) -> u32 {
-		crate::util::longest_true_sequence(self.iter_hits().map(hit_filter))
+		crate::sequences::longest_true_sequence(self.iter_hits().map(hit_filter))
 	}
 
 	/// Generate a [`crate::TapJudgements`] instance of this replay
@@ -358,6 +1223,349 @@ pub trait SimpleReplay {
 
 		deviations_sum / num_deviations as f32
 	}
+
+	/// Proportion of notes, from 0.0 to 1.0, that were hit within half of the marvelous window.
+	/// These are colloquially called "ridiculous" hits. Returns None if there are no notes
+	fn ridiculous_proportion(&self, judge: &crate::Judge) -> Option<f32> {
+		let mut num_notes = 0;
+		let mut num_ridiculous = 0;
+		for hit in self.iter_hits() {
+			num_notes += 1;
+			if let crate::Hit::Hit { deviation } = hit {
+				if deviation.abs() <= judge.marvelous_window / 2.0 {
+					num_ridiculous += 1;
+				}
+			}
+		}
+
+		if num_notes == 0 {
+			None
+		} else {
+			Some(num_ridiculous as f32 / num_notes as f32)
+		}
+	}
+
+	/// Sums up how many wife points were lost on the notes of each judgement, using the given
+	/// scoring system and judge. A perfectly hit note is worth 1.0 points, so divide by the number
+	/// of notes to get the loss in wifescore proportion.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"0 0.005 0\n48 0.03 1\n96 1.0 2\n").unwrap();
+	///
+	/// let loss = replay.wife_points_lost_by_judgement::<Wife3>(J4);
+	/// assert_eq!(loss.marvelouses, 0.0);
+	/// assert!(loss.perfects > 0.0);
+	/// assert_eq!(loss.misses, 1.0 - Wife3::MISS_WEIGHT);
+	/// assert_eq!(loss[TapJudgement::Miss], loss.misses);
+	/// ```
+	fn wife_points_lost_by_judgement<W: crate::Wife>(
+		&self,
+		judge: &crate::Judge,
+	) -> WifeLossByJudgement {
+		let mut loss = WifeLossByJudgement::default();
+		for hit in self.iter_hits() {
+			loss[hit.classify(judge)] += 1.0 - W::calc(hit, judge);
+		}
+		loss
+	}
+
+	/// Sorts the hit deviations into buckets of `bucket_ms` milliseconds each, spanning from
+	/// -180ms to +180ms. Bucket `i` covers deviations from `-180ms + i * bucket_ms` (inclusive) to
+	/// `-180ms + (i + 1) * bucket_ms` (exclusive). Deviations outside that range are counted in the
+	/// outermost buckets. Misses are ignored.
+	///
+	/// This is the data behind the classic offset histogram. Returns an empty list if `bucket_ms`
+	/// is zero, negative or NaN.
+	///
+	/// ```rust
+	/// # use etterna::{Hit, SimpleReplay};
+	/// struct Replay(Vec<Hit>);
+	/// impl SimpleReplay for Replay {
+	/// 	fn iter_hits(&self) -> Box<dyn '_ + Iterator<Item = Hit>> {
+	/// 		Box::new(self.0.iter().copied())
+	/// 	}
+	/// }
+	///
+	/// let replay = Replay(vec![
+	/// 	Hit::Hit { deviation: -0.175 },
+	/// 	Hit::Hit { deviation: 0.001 },
+	/// 	Hit::Hit { deviation: 0.009 },
+	/// 	Hit::Hit { deviation: 0.5 },
+	/// 	Hit::Miss,
+	/// ]);
+	/// let histogram = replay.deviation_histogram(10.0);
+	/// assert_eq!(histogram.len(), 36);
+	/// assert_eq!(histogram[0], 1);
+	/// assert_eq!(histogram[18], 2);
+	/// assert_eq!(histogram[35], 1);
+	/// ```
+	fn deviation_histogram(&self, bucket_ms: f32) -> Vec<u32> {
+		let num_buckets = num_histogram_buckets(bucket_ms);
+		let mut buckets = vec![0; num_buckets];
+		if num_buckets == 0 {
+			return buckets;
+		}
+		for hit in self.iter_hits() {
+			if let crate::Hit::Hit { deviation } = hit {
+				buckets[histogram_bucket(deviation, bucket_ms, num_buckets)] += 1;
+			}
+		}
+		buckets
+	}
+
+	/// Like [`Self::deviation_histogram`], but additionally splits each bucket's count by the
+	/// judgement the hits received on the given judge. Useful for coloring the histogram by
+	/// judgement (see [`TapJudgement::color`]). Returns an empty list if `bucket_ms` is zero,
+	/// negative or NaN.
+	fn deviation_histogram_by_judgement(
+		&self,
+		bucket_ms: f32,
+		judge: &crate::Judge,
+	) -> Vec<crate::TapJudgements> {
+		let num_buckets = num_histogram_buckets(bucket_ms);
+		let mut buckets = vec![crate::TapJudgements::default(); num_buckets];
+		if num_buckets == 0 {
+			return buckets;
+		}
+		for hit in self.iter_hits() {
+			if let crate::Hit::Hit { deviation } = hit {
+				buckets[histogram_bucket(deviation, bucket_ms, num_buckets)]
+					[judge.classify(deviation)] += 1;
+			}
+		}
+		buckets
+	}
+
+	/// Estimates how much the wifescore could improve, by computing what it would be if all hits
+	/// that aren't misses were marvelous (the accuracy ceiling), and if all combo breakers,
+	/// including misses, were greats instead. Comparing the two shows players whether working on
+	/// accuracy or on consistency matters more.
+	///
+	/// Only tap notes are considered; mine hits and hold drops aren't known to a
+	/// [`SimpleReplay`]. Returns None if there are no notes
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"0 0.03 0\n48 0.04 1\n96 0.12 2\n144 1.0 3\n").unwrap();
+	///
+	/// let ceilings = replay.wifescore_ceilings::<Wife3>(J4).unwrap();
+	/// assert!(ceilings.actual < ceilings.all_hits_marvelous);
+	/// assert!(ceilings.actual < ceilings.cbs_as_greats);
+	/// // the miss hurts more than the slightly off hits, so fixing CBs matters more here
+	/// assert!(ceilings.all_hits_marvelous < ceilings.cbs_as_greats);
+	/// ```
+	fn wifescore_ceilings<W: crate::Wife>(
+		&self,
+		judge: &crate::Judge,
+	) -> Option<WifescoreCeilings> {
+		let all_hits_marvelous = self.iter_hits().map(|hit| match hit {
+			crate::Hit::Hit { .. } => crate::Hit::Hit { deviation: 0.0 },
+			crate::Hit::Miss => crate::Hit::Miss,
+		});
+		// The worst possible great, so that the estimate stays conservative
+		let cbs_as_greats = self.iter_hits().map(|hit| {
+			if hit.is_cb(judge) {
+				crate::Hit::Hit {
+					deviation: judge.great_window,
+				}
+			} else {
+				hit
+			}
+		});
+
+		Some(WifescoreCeilings {
+			actual: W::apply(self.iter_hits(), 0, 0, judge)?,
+			all_hits_marvelous: W::apply(all_hits_marvelous, 0, 0, judge)?,
+			cbs_as_greats: W::apply(cbs_as_greats, 0, 0, judge)?,
+		})
+	}
+
+	/// Rescores this replay on every built-in judge from J1 to J9, like
+	/// [`crate::rescore_from_note_hits`] on each judge. This is the judge conversion table shown on
+	/// score pages. Returns None if there are no notes
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"0 0.01 0\n48 0.04 1\n96 0.1 2\n").unwrap();
+	///
+	/// let table = replay.judge_conversion_table::<Wife3>(replay.num_mine_hits, 0).unwrap();
+	/// assert_eq!(table.len(), 9);
+	/// assert_eq!(&table[3].judge, J4);
+	/// assert_eq!(table[3].judgements.goods, 1);
+	/// assert!(table[0].wifescore > table[8].wifescore);
+	/// assert_eq!(table[8].grade, table[8].wifescore.grade());
+	/// ```
+	fn judge_conversion_table<W: crate::Wife>(
+		&self,
+		num_mine_hits: u32,
+		num_hold_drops: u32,
+	) -> Option<Vec<JudgeConversion>> {
+		crate::Judge::list()
+			.iter()
+			.map(|&judge| {
+				let wifescore = W::apply(self.iter_hits(), num_mine_hits, num_hold_drops, judge)?;
+				Some(JudgeConversion {
+					judge: judge.clone(),
+					wifescore,
+					judgements: self.tap_judgements(judge),
+					grade: wifescore.grade(),
+				})
+			})
+			.collect()
+	}
+}
+
+/// Hypothetical wifescores of a replay. See [`SimpleReplay::wifescore_ceilings`]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WifescoreCeilings {
+	/// The wifescore as played
+	pub actual: crate::Wifescore,
+	/// The wifescore if every hit was marvelous, with misses still being misses
+	pub all_hits_marvelous: crate::Wifescore,
+	/// The wifescore if every combo breaker, including misses, was a great instead
+	pub cbs_as_greats: crate::Wifescore,
+}
+
+/// A replay's result on one judge. See [`SimpleReplay::judge_conversion_table`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JudgeConversion {
+	pub judge: crate::Judge,
+	pub wifescore: crate::Wifescore,
+	pub judgements: crate::TapJudgements,
+	pub grade: Grade,
+}
+
+/// Statistics of the notes played by one hand. See [`LaneReplay::hand_statistics`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandStatistics {
+	pub judgements: crate::TapJudgements,
+	/// Mean deviation of all hit notes in seconds, or None if there were no hits
+	pub mean_deviation: Option<f32>,
+}
+
+impl HandStatistics {
+	/// Number of combo breakers (goods, bads and misses)
+	pub fn num_cbs(&self) -> u32 {
+		self.judgements.goods + self.judgements.bads + self.judgements.misses
+	}
+}
+
+/// A replay that, unlike [`SimpleReplay`], knows which lane (column) each note was in
+pub trait LaneReplay {
+	/// Iterates over all notes, each with its lane and hit
+	fn iter_lane_hits(&self) -> Box<dyn '_ + Iterator<Item = (u8, crate::Hit)>>;
+
+	/// Generates a [`crate::TapJudgements`] instance for each lane. The returned vector is indexed
+	/// by lane and is long enough to contain the highest lane in the replay
+	fn lane_tap_judgements(&self, judge: &crate::Judge) -> Vec<crate::TapJudgements> {
+		let mut judgements = Vec::new();
+		for (lane, hit) in self.iter_lane_hits() {
+			let lane = lane as usize;
+			if judgements.len() <= lane {
+				judgements.resize(lane + 1, crate::TapJudgements::default());
+			}
+			judgements[lane][hit.classify(judge)] += 1;
+		}
+		judgements
+	}
+
+	/// Compares the left hand and right hand, for a chart with the given number of keys. The
+	/// lower half of the lanes is played by the left hand and the upper half by the right hand. For
+	/// odd keycounts, the middle lane is not counted for either hand (see [`crate::SplitHands`]).
+	///
+	/// Returns the statistics of the left hand and the right hand, in that order
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"0 0.01 0\n48 0.03 1\n96 -0.02 3\n144 1.0 2\n").unwrap();
+	///
+	/// let [left, right] = replay.hand_statistics(J4, 4);
+	/// assert_eq!(left.judgements.marvelouses, 1);
+	/// assert_eq!(left.judgements.perfects, 1);
+	/// assert_eq!(right.mean_deviation, Some(-0.02));
+	/// assert_eq!((left.num_cbs(), right.num_cbs()), (0, 1));
+	/// ```
+	fn hand_statistics(&self, judge: &crate::Judge, keycount: u8) -> [HandStatistics; 2] {
+		self.hand_statistics_with_mapping(judge, &crate::SplitHands { keycount })
+	}
+
+	/// Like [`Self::hand_statistics`], but with a custom mapping of lanes to hands
+	fn hand_statistics_with_mapping(
+		&self,
+		judge: &crate::Judge,
+		mapping: &impl crate::HandMapping,
+	) -> [HandStatistics; 2] {
+		let mut hands = [HandStatistics::default(), HandStatistics::default()];
+		let mut deviation_sums = [0.0, 0.0];
+		let mut num_hits = [0, 0];
+		for (lane, hit) in self.iter_lane_hits() {
+			let hand = crate::some_or_continue!(mapping.hand(lane)).index();
+
+			hands[hand].judgements[hit.classify(judge)] += 1;
+			if let Some(deviation) = hit.deviation() {
+				deviation_sums[hand] += deviation;
+				num_hits[hand] += 1;
+			}
+		}
+
+		for ((hand, deviation_sum), num_hits) in hands.iter_mut().zip(deviation_sums).zip(num_hits) {
+			if num_hits > 0 {
+				hand.mean_deviation = Some(deviation_sum / num_hits as f32);
+			}
+		}
+		hands
+	}
+
+	/// Calculates the wifescore of the notes played by each hand, left hand first. Mine hits and
+	/// hold drops are not included. A hand without notes has no wifescore
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let replay = parse_replay_v2_fast(b"0 0.0 0\n48 0.0 1\n96 1.0 3\n").unwrap();
+	///
+	/// let [left, right] = replay.hand_wifescores::<Wife3>(J4, &SplitHands { keycount: 4 });
+	/// assert_eq!(left, Some(Wifescore::HUNDRED_PERCENT));
+	/// assert!(right.unwrap() < Wifescore::from_percent(0.0).unwrap());
+	/// ```
+	fn hand_wifescores<W: crate::Wife>(
+		&self,
+		judge: &crate::Judge,
+		mapping: &impl crate::HandMapping,
+	) -> [Option<crate::Wifescore>; 2] {
+		let hand_wifescore = |hand: crate::Hand| {
+			let hits = self
+				.iter_lane_hits()
+				.filter(|&(lane, _)| mapping.hand(lane) == Some(hand))
+				.map(|(_, hit)| hit);
+			W::apply(hits, 0, 0, judge)
+		};
+		[
+			hand_wifescore(crate::Hand::Left),
+			hand_wifescore(crate::Hand::Right),
+		]
+	}
+}
+
+// Range of the deviation histograms, in milliseconds in each direction
+const DEVIATION_HISTOGRAM_RANGE_MS: f32 = 180.0;
+
+// Zero if the bucket size is zero, negative or NaN. Otherwise at least one, even if a single
+// bucket is wider than the whole range
+fn num_histogram_buckets(bucket_ms: f32) -> usize {
+	if bucket_ms.is_nan() || bucket_ms <= 0.0 {
+		return 0;
+	}
+	((DEVIATION_HISTOGRAM_RANGE_MS * 2.0 / bucket_ms).ceil() as usize).max(1)
+}
+
+fn histogram_bucket(deviation: f32, bucket_ms: f32, num_buckets: usize) -> usize {
+	let index = ((deviation * 1000.0 + DEVIATION_HISTOGRAM_RANGE_MS) / bucket_ms).floor();
+	// float to int casts saturate, so negative indices become 0
+	(index as usize).min(num_buckets - 1)
 }
 
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
@@ -372,6 +1580,67 @@ pub enum TapJudgement {
 }
 
 impl TapJudgement {
+	/// Get a list of all tap judgements, from best to worst
+	pub fn list() -> &'static [Self] {
+		&[
+			Self::Marvelous,
+			Self::Perfect,
+			Self::Great,
+			Self::Good,
+			Self::Bad,
+			Self::Miss,
+		]
+	}
+
+	/// Iterate all tap judgements, from best to worst
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let judgements = TapJudgements { marvelouses: 5, misses: 1, ..Default::default() };
+	/// for judgement in TapJudgement::iter() {
+	/// 	println!("{}: {}", judgement, judgements[judgement]);
+	/// }
+	/// ```
+	pub fn iter() -> impl Iterator<Item = Self> {
+		Self::list().iter().copied()
+	}
+
+	/// Parses a tap judgement from a human-written name, case-insensitively. Accepts full names,
+	/// common abbreviations and plurals, and the short codes from [`Self::short_code`]. Note that
+	/// "cb" is not a judgement.
+	///
+	/// ```rust
+	/// # use etterna::TapJudgement;
+	/// assert_eq!(TapJudgement::from_user_input("marv"), Some(TapJudgement::Marvelous));
+	/// assert_eq!(TapJudgement::from_user_input("W3"), Some(TapJudgement::Great));
+	/// assert_eq!(TapJudgement::from_user_input("boos"), Some(TapJudgement::Bad));
+	/// assert_eq!(TapJudgement::from_user_input("cb"), None);
+	/// ```
+	pub fn from_user_input(input: &str) -> Option<Self> {
+		match &input.to_lowercase() as &str {
+			"w1" | "marv" | "marvs" | "marvelous" | "marvelouses" => Some(Self::Marvelous),
+			"w2" | "perf" | "perfs" | "perfect" | "perfects" => Some(Self::Perfect),
+			"w3" | "great" | "greats" => Some(Self::Great),
+			"w4" | "good" | "goods" => Some(Self::Good),
+			"w5" | "bad" | "bads" | "boo" | "boos" => Some(Self::Bad),
+			"miss" | "misses" => Some(Self::Miss),
+			_ => None,
+		}
+	}
+
+	/// Short code of this judgement as used by StepMania internally, from "W1" for marvelous to
+	/// "W5" for bad, and "Miss"
+	pub fn short_code(self) -> &'static str {
+		match self {
+			Self::Marvelous => "W1",
+			Self::Perfect => "W2",
+			Self::Great => "W3",
+			Self::Good => "W4",
+			Self::Bad => "W5",
+			Self::Miss => "Miss",
+		}
+	}
+
 	pub fn color(self) -> (u8, u8, u8) {
 		match self {
 			Self::Marvelous => (0x99, 0xCC, 0xFF),
@@ -384,6 +1653,38 @@ impl TapJudgement {
 	}
 }
 
+impl std::fmt::Display for TapJudgement {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{:?}", self)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnrecognizedTapJudgement(String);
+impl std::fmt::Display for UnrecognizedTapJudgement {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Unrecognized tap judgement: \"{}\"", self.0)
+	}
+}
+impl std::error::Error for UnrecognizedTapJudgement {}
+
+/// Same as [`TapJudgement::from_user_input`]
+impl std::str::FromStr for TapJudgement {
+	type Err = UnrecognizedTapJudgement;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::from_user_input(s).ok_or_else(|| UnrecognizedTapJudgement(s.to_owned()))
+	}
+}
+
+impl std::convert::TryFrom<&str> for TapJudgement {
+	type Error = UnrecognizedTapJudgement;
+
+	fn try_from(s: &str) -> Result<Self, Self::Error> {
+		s.parse()
+	}
+}
+
 /// Represents a player hit of a single note
 ///
 /// The deviation value is in seconds and may be negative
@@ -418,10 +1719,19 @@ impl Hit {
 		}
 	}
 
+	/// Whether this hit breaks combo. Misses are combo breakers too; see [`crate::Judge::is_cb`]
+	/// for hits.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// assert!(Hit::Miss.is_cb(J4));
+	/// assert!(Hit::Hit { deviation: -0.12 }.is_cb(J4));
+	/// assert!(!Hit::Hit { deviation: 0.05 }.is_cb(J4));
+	/// ```
 	pub fn is_cb(&self, judge: &crate::Judge) -> bool {
 		match *self {
 			Self::Hit { deviation } => judge.is_cb(deviation),
-			Self::Miss => false,
+			Self::Miss => true,
 		}
 	}
 
@@ -501,6 +1811,8 @@ impl Hit {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct NoteRow {
 	// least significant bit is leftmost finger
 	bits: u32,
@@ -524,6 +1836,18 @@ impl NoteRow {
 		(self.bits & (1 << index)) > 0
 	}
 
+	/// Returns a copy of this row with a tap added at the given index, where 0 is the leftmost lane
+	pub fn with_tap_at(self, index: u32) -> Self {
+		Self {
+			bits: self.bits | (1 << index),
+		}
+	}
+
+	/// Returns the number of notes in this row
+	pub fn num_notes(self) -> u32 {
+		self.bits.count_ones()
+	}
+
 	/// Returns the number of notes that this row spans
 	///
 	/// ```rust
@@ -622,7 +1946,15 @@ pub enum ScrollDirection {
 	Downscroll,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+/// The snap (rhythmic subdivision) of a note. Ordered from coarse to fine
+///
+/// ```rust
+/// # use etterna::Snap;
+/// assert!(Snap::_16th < Snap::_24th);
+/// assert_eq!(Snap::_12th.to_string(), "12th");
+/// assert_eq!(Snap::_32th.to_string(), "32nd");
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Snap {
 	_4th,
@@ -637,25 +1969,350 @@ pub enum Snap {
 }
 
 impl Snap {
+	/// Get a list of all snaps, from coarse to fine
+	pub fn list() -> &'static [Self] {
+		&[
+			Self::_4th,
+			Self::_8th,
+			Self::_12th,
+			Self::_16th,
+			Self::_24th,
+			Self::_32th,
+			Self::_48th,
+			Self::_64th,
+			Self::_192th,
+		]
+	}
+
+	/// Iterate all snaps, from coarse to fine
+	pub fn iter() -> impl Iterator<Item = Self> {
+		Self::list().iter().copied()
+	}
+
 	pub fn from_row(row: u32) -> Self {
-		if row % (192 / 4) == 0 {
-			Self::_4th
-		} else if row % (192 / 8) == 0 {
-			Self::_8th
-		} else if row % (192 / 12) == 0 {
-			Self::_12th
-		} else if row % (192 / 16) == 0 {
-			Self::_16th
-		} else if row % (192 / 24) == 0 {
-			Self::_24th
-		} else if row % (192 / 32) == 0 {
-			Self::_32th
-		} else if row % (192 / 48) == 0 {
-			Self::_48th
-		} else if row % (192 / 64) == 0 {
-			Self::_64th
+		// _192th matches every row, so the unwrap_or is never reached
+		Self::iter()
+			.find(|snap| row % snap.rows_per_snap() == 0)
+			.unwrap_or(Self::_192th)
+	}
+
+	/// How many notes of this snap fit into a measure, e.g. 16 for 16ths
+	///
+	/// ```rust
+	/// # use etterna::Snap;
+	/// assert_eq!(Snap::_16th.as_denominator(), 16);
+	/// ```
+	pub fn as_denominator(self) -> u32 {
+		match self {
+			Self::_4th => 4,
+			Self::_8th => 8,
+			Self::_12th => 12,
+			Self::_16th => 16,
+			Self::_24th => 24,
+			Self::_32th => 32,
+			Self::_48th => 48,
+			Self::_64th => 64,
+			Self::_192th => 192,
+		}
+	}
+
+	/// Distance in rows between two consecutive notes of this snap, with 192 rows per measure
+	///
+	/// ```rust
+	/// # use etterna::Snap;
+	/// assert_eq!(Snap::_16th.rows_per_snap(), 12);
+	/// ```
+	pub fn rows_per_snap(self) -> u32 {
+		192 / self.as_denominator()
+	}
+
+	/// Notes per second of a continuous stream of this snap at the given BPM
+	///
+	/// ```rust
+	/// # use etterna::Snap;
+	/// assert_eq!(Snap::_16th.nps_at_bpm(150.0), 10.0);
+	/// ```
+	pub fn nps_at_bpm(self, bpm: f32) -> f32 {
+		let beats_per_second = bpm / 60.0;
+		beats_per_second * self.as_denominator() as f32 / 4.0
+	}
+}
+
+impl std::fmt::Display for Snap {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let denominator = self.as_denominator();
+		// 32nd and 192nd, but 12th
+		let suffix = if denominator % 10 == 2 && denominator % 100 != 12 {
+			"nd"
 		} else {
-			Self::_192th
+			"th"
+		};
+		write!(f, "{}{}", denominator, suffix)
+	}
+}
+
+/// How many rows of a chart fall on each snap. See [`SnapStatistics::from_rows`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapStatistics {
+	/// Number of rows per snap, indexed by the snap's position in the [`Snap`] enum
+	counts: [u32; 9],
+}
+
+impl SnapStatistics {
+	/// Counts the snaps of the given note rows, where each row is given by its position with 192
+	/// rows per measure, like in [`Snap::from_row`]. Every row is counted once, regardless of how
+	/// many notes it contains.
+	///
+	/// ```rust
+	/// # use etterna::{Snap, SnapStatistics};
+	/// let stats = SnapStatistics::from_rows(vec![0, 12, 24, 36]);
+	/// assert_eq!(stats.count(Snap::_16th), 2);
+	/// assert_eq!(stats.percentage(Snap::_4th), 25.0);
+	/// ```
+	pub fn from_rows(rows: impl IntoIterator<Item = u32>) -> Self {
+		let mut counts = [0; 9];
+		for row in rows {
+			counts[Snap::from_row(row) as usize] += 1;
+		}
+		Self { counts }
+	}
+
+	/// Number of rows on the given snap
+	pub fn count(&self, snap: Snap) -> u32 {
+		self.counts[snap as usize]
+	}
+
+	/// Total number of rows
+	pub fn total(&self) -> u32 {
+		self.counts.iter().sum()
+	}
+
+	/// Percentage (0-100) of rows on the given snap. Returns 0 if there are no rows
+	pub fn percentage(&self, snap: Snap) -> f32 {
+		let total = self.total();
+		if total == 0 {
+			return 0.0;
+		}
+		self.count(snap) as f32 / total as f32 * 100.0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
+	use std::convert::TryFrom;
+
+	fn any_wifescore() -> impl Strategy<Value = Wifescore> {
+		prop_oneof![
+			Just(Wifescore::NEGATIVE_INFINITY),
+			Just(Wifescore::HUNDRED_PERCENT),
+			(-10.0f32..=1.0).prop_map(|p| Wifescore::from_proportion(p).unwrap()),
+		]
+	}
+
+	#[test]
+	fn test_wifescore_validation() {
+		assert_eq!(Wifescore::from_proportion(f32::NAN), None);
+		assert_eq!(Wifescore::from_percent(100.01), None);
+		assert_eq!(
+			Wifescore::from_proportion(f32::NEG_INFINITY),
+			Some(Wifescore::NEGATIVE_INFINITY)
+		);
+		assert_eq!(
+			Wifescore::from_proportion(-0.0).unwrap().cmp(&Wifescore::default()),
+			std::cmp::Ordering::Equal
+		);
+	}
+
+	#[test]
+	fn test_key_parse_errors() {
+		let valid = "X0123456789abcdef0123456789abcdef01234567";
+		assert!(valid.parse::<Chartkey>().is_ok());
+		assert_eq!(
+			"X123".parse::<Chartkey>(),
+			Err(KeyParseError::InvalidLength(4))
+		);
+		assert_eq!(
+			valid.parse::<Scorekey>(),
+			Err(KeyParseError::InvalidPrefix {
+				expected: 'S',
+				found: 'X'
+			})
+		);
+		assert_eq!(
+			valid.to_uppercase().parse::<Chartkey>(),
+			Err(KeyParseError::InvalidCharacter {
+				index: 11,
+				character: 'A'
+			})
+		);
+		// length is counted in characters, not bytes
+		let non_ascii = format!("{}ä", &valid[..40]);
+		assert_eq!(
+			Chartkey::validate(&non_ascii),
+			Err(KeyParseError::InvalidCharacter {
+				index: 40,
+				character: 'ä'
+			})
+		);
+		assert_eq!(
+			KeyParseError::InvalidCharacter {
+				index: 11,
+				character: 'A'
+			}
+			.to_string(),
+			"Invalid character 'A' at index 11, expected a lowercase hex digit"
+		);
+	}
+
+	#[test]
+	fn test_lenient_keys() {
+		let canonical = "X0123456789abcdef0123456789abcdef01234567";
+		let chartkey = Chartkey::new_lenient("x0123456789ABCDEF0123456789abcdef01234567").unwrap();
+		assert_eq!(chartkey.as_str(), canonical);
+		assert_eq!(chartkey, Chartkey::new(canonical.into()).unwrap());
+		assert!(chartkey.eq_ignore_case("X0123456789ABCDEF0123456789ABCDEF01234567"));
+		assert!(!chartkey.eq_ignore_case("X0123456789abcdef0123456789abcdef01234568"));
+
+		assert_eq!(Chartkey::new_lenient(""), None);
+		assert_eq!(Scorekey::new_lenient(canonical), None);
+		assert_eq!(Chartkey::new_lenient("X0123456789abcdefg"), None);
+	}
+
+	#[test]
+	fn test_wifescore_format_and_lerp() {
+		let wifescore = Wifescore::from_percent(93.456).unwrap();
+		assert_eq!(format!("{:.1}", wifescore), "93.5%");
+		assert_eq!(wifescore.format(3), "93.456%");
+		assert_eq!(
+			Wifescore::NEGATIVE_INFINITY.format_with_grade(2),
+			"-inf% (D)"
+		);
+
+		let neg_inf = Wifescore::NEGATIVE_INFINITY;
+		assert_eq!(Wifescore::lerp(neg_inf, wifescore, 0.5), neg_inf);
+		assert_eq!(Wifescore::lerp(wifescore, neg_inf, 0.5), neg_inf);
+		assert_eq!(Wifescore::lerp(neg_inf, wifescore, 1.0), wifescore);
+		assert_eq!(Wifescore::lerp(wifescore, neg_inf, -1.0), wifescore);
+	}
+
+	#[test]
+	fn test_string_round_trips() {
+		fn round_trip<T>(value: T)
+		where
+			T: std::fmt::Display + std::str::FromStr + PartialEq + std::fmt::Debug,
+			T::Err: std::fmt::Debug,
+		{
+			assert_eq!(value.to_string().parse::<T>().unwrap(), value);
+		}
+
+		for &difficulty in &[
+			Difficulty::Beginner,
+			Difficulty::Easy,
+			Difficulty::Medium,
+			Difficulty::Hard,
+			Difficulty::Challenge,
+			Difficulty::Edit,
+		] {
+			round_trip(difficulty);
+		}
+		for &grade in Grade::list() {
+			round_trip(grade);
+		}
+		for &skillset in crate::Skillset8::list() {
+			round_trip(skillset);
+		}
+		for judgement in TapJudgement::iter() {
+			round_trip(judgement);
+			assert_eq!(judgement.short_code().parse(), Ok(judgement));
 		}
+		for x20 in 0..60 {
+			round_trip(crate::Rate::from_x20(x20));
+		}
+		for percent in &["97.53%", "-12.30%", "100.00%", "0.00%"] {
+			assert_eq!(Wifescore::try_from(*percent).unwrap().to_string(), *percent);
+		}
+
+		assert_eq!(Difficulty::try_from("in"), Ok(Difficulty::Challenge));
+		assert_eq!(Grade::try_from("aaa"), Ok(Grade::AAA));
+		assert_eq!(
+			crate::Rate::try_from("1.45x"),
+			Ok(crate::Rate::from_x20(29))
+		);
+		assert!(Wifescore::try_from("abc%").is_err());
+	}
+
+	proptest! {
+		#[test]
+		fn wifescore_order_is_total(
+			a in any_wifescore(),
+			b in any_wifescore(),
+			c in any_wifescore(),
+		) {
+			prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+			prop_assert_eq!(a.cmp(&b) == std::cmp::Ordering::Equal, a == b);
+			prop_assert_eq!(a.partial_cmp(&b), Some(a.cmp(&b)));
+			prop_assert_eq!(a.cmp(&b), a.as_proportion().partial_cmp(&b.as_proportion()).unwrap());
+			if a <= b && b <= c {
+				prop_assert!(a <= c);
+			}
+			prop_assert!(Wifescore::NEGATIVE_INFINITY <= a && a <= Wifescore::HUNDRED_PERCENT);
+		}
+
+		#[test]
+		fn wifescore_sorting(mut wifescores in prop::collection::vec(any_wifescore(), 1..50)) {
+			let max = Wifescore::max_of(&wifescores).unwrap();
+			let min = Wifescore::min_of(&wifescores).unwrap();
+			wifescores.sort();
+			prop_assert_eq!(wifescores[0], min);
+			prop_assert_eq!(*wifescores.last().unwrap(), max);
+			prop_assert!(wifescores
+				.windows(2)
+				.all(|w| w[0].as_proportion() <= w[1].as_proportion()));
+		}
+	}
+
+	#[test]
+	fn test_judgements_arithmetic() {
+		let a = FullJudgements {
+			marvelouses: 3,
+			hit_mines: 1,
+			missed_holds: 2,
+			..Default::default()
+		};
+		let mut b = a.clone() + a.clone();
+		assert_eq!((b.marvelouses, b.hit_mines, b.missed_holds), (6, 2, 4));
+		b += a.clone();
+		assert_eq!(b, vec![a.clone(), a.clone(), a].into_iter().sum());
+
+		let taps = TapJudgements::from(b);
+		assert_eq!(taps.total(), 9);
+		assert_eq!(taps.cb_count(), 0);
+		assert_eq!(TapJudgements::default().ratio_of(TapJudgement::Miss), None);
+	}
+
+	#[test]
+	fn test_deviation_histogram_bucket_sizes() {
+		let replay = crate::parse_replay_v2_fast(b"0 0.01 0\n48 -0.05 1\n96 1.0 2\n").unwrap();
+		for &bucket_ms in &[0.0, -10.0, f32::NAN] {
+			assert!(replay.deviation_histogram(bucket_ms).is_empty());
+			assert!(replay
+				.deviation_histogram_by_judgement(bucket_ms, crate::J4)
+				.is_empty());
+		}
+		assert_eq!(replay.deviation_histogram(1000.0), vec![2]);
+		assert_eq!(replay.deviation_histogram(f32::INFINITY), vec![2]);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn test_wifescore_serde() {
+		let wifescore = Wifescore::from_percent(93.5).unwrap();
+		let json = serde_json::to_string(&wifescore).unwrap();
+		assert_eq!(serde_json::from_str::<Wifescore>(&json).unwrap(), wifescore);
+		assert_eq!(serde_json::from_str::<Wifescore>("0.5").unwrap().as_percent(), 50.0);
+		assert!(serde_json::from_str::<Wifescore>("1.5").is_err());
 	}
 }
@@ -1,12 +1,34 @@
 use thiserror::Error;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BpmChange {
 	beat: f64,
 	bpm: f64,
 }
 
-#[derive(Debug)]
+/// A section of a chart that is skipped over instantly, created by a negative BPM. See
+/// [`TimingInfo::warps`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Warp {
+	/// Beat of the negative BPM change where the warp starts (inclusive)
+	pub start_beat: f64,
+	/// Beat where the warp ends (exclusive). Infinite if the chart never recovers from the warp
+	pub end_beat: f64,
+}
+
+impl Warp {
+	pub fn contains_beat(&self, beat: f64) -> bool {
+		beat >= self.start_beat && beat < self.end_beat
+	}
+}
+
+/// BPM information of a chart, used to convert ticks to seconds.
+///
+/// Like in StepMania and Etterna, negative BPMs are interpreted as warps: the chart skips from the
+/// negative BPM change to the point where the time, had it actually run backwards, would have
+/// caught up again. See [`Self::warps`].
+#[derive(Debug, Clone, PartialEq)]
 pub struct TimingInfo {
 	first_bpm: f64,
 	// Must be chronologically ordered!
@@ -28,6 +50,45 @@ pub enum SmBpmStringParseError {
 }
 
 impl TimingInfo {
+	/// Creates timing info for a chart that stays on the given BPM all the way through
+	pub fn from_constant_bpm(bpm: f64) -> Self {
+		Self {
+			first_bpm: bpm,
+			changes: vec![],
+		}
+	}
+
+	/// Creates timing info from the initial BPM and a list of `(beat, bpm)` changes. The changes
+	/// don't need to be sorted. A change at beat zero replaces the initial BPM.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let timing_info = TimingInfo::from_bpm_changes(120.0, vec![(4.0, 240.0)]);
+	/// assert_eq!(timing_info.to_sm_bpm_string(), "0.000=120.000,4.000=240.000");
+	/// ```
+	pub fn from_bpm_changes(first_bpm: f64, changes: Vec<(f64, f64)>) -> Self {
+		let mut first_bpm = first_bpm;
+		let mut changes: Vec<BpmChange> = changes
+			.into_iter()
+			.map(|(beat, bpm)| BpmChange { beat, bpm })
+			.collect();
+		changes.sort_by(|a, b| {
+			a.beat
+				.partial_cmp(&b.beat)
+				.unwrap_or(std::cmp::Ordering::Equal)
+		});
+
+		while let Some(change) = changes.first() {
+			if change.beat > 0.0 {
+				break;
+			}
+			first_bpm = change.bpm;
+			changes.remove(0);
+		}
+
+		Self { first_bpm, changes }
+	}
+
 	pub fn from_sm_bpm_string(string: &[u8]) -> Result<Self, SmBpmStringParseError> {
 		// rough capacity approximation
 		let mut changes = Vec::with_capacity(string.len() / 13);
@@ -65,23 +126,127 @@ impl TimingInfo {
 		Ok(TimingInfo { changes, first_bpm })
 	}
 
+	/// Returns a copy of this timing info as it would be when played at the given rate. Every BPM
+	/// is multiplied by the rate, so all ticks stay where they are and only their timestamps change.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let timing_info = TimingInfo::from_sm_bpm_string(b"0.000=120.000,4.000=240.000").unwrap();
+	/// let rated = timing_info.with_rate(Rate::from_f32(2.0).unwrap());
+	///
+	/// assert_eq!(rated.ticks_to_seconds(&[192, 384]), vec![1.0, 1.5]);
+	/// assert_eq!(rated.to_sm_bpm_string(), "0.000=240.000,4.000=480.000");
+	/// ```
+	pub fn with_rate(&self, rate: crate::Rate) -> Self {
+		let rate = rate.as_f32() as f64;
+		Self {
+			first_bpm: self.first_bpm * rate,
+			changes: self
+				.changes
+				.iter()
+				.map(|change| BpmChange {
+					beat: change.beat,
+					bpm: change.bpm * rate,
+				})
+				.collect(),
+		}
+	}
+
+	/// Formats this timing info in the format of the `#BPMS` tag of .sm files, i.e. the inverse of
+	/// [`Self::from_sm_bpm_string`]
+	pub fn to_sm_bpm_string(&self) -> String {
+		let mut string = format!("0.000={:.3}", self.first_bpm);
+		for change in &self.changes {
+			string += &format!(",{:.3}={:.3}", change.beat, change.bpm);
+		}
+		string
+	}
+
+	/// Returns the warps caused by negative BPMs, in chronological order.
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// // one beat at -120 BPM takes back half a second, which two beats at 120 BPM make up for
+	/// let timing_info = TimingInfo::from_sm_bpm_string(b"0=120,4=-120,5=120").unwrap();
+	/// assert_eq!(timing_info.warps(), vec![Warp { start_beat: 4.0, end_beat: 6.0 }]);
+	///
+	/// // notes inside the warp are skipped over instantly
+	/// let seconds = timing_info.ticks_to_seconds(&[0, 192, 216, 264, 288, 336]);
+	/// assert_eq!(seconds, vec![0.0, 2.0, 2.0, 2.0, 2.0, 2.5]);
+	/// assert!(timing_info.is_tick_warped(264));
+	/// assert!(!timing_info.is_tick_warped(288));
+	/// ```
+	pub fn warps(&self) -> Vec<Warp> {
+		let segment_starts = std::iter::once((0.0, self.first_bpm))
+			.chain(self.changes.iter().map(|change| (change.beat, change.bpm)));
+		let segment_ends = self
+			.changes
+			.iter()
+			.map(|change| change.beat)
+			.chain(std::iter::once(f64::INFINITY));
+
+		let mut warps = Vec::new();
+		// start beat and start second of the warp we're currently in
+		let mut current_warp: Option<(f64, f64)> = None;
+		let mut cursor_second = 0.0;
+		for ((start_beat, bpm), end_beat) in segment_starts.zip(segment_ends) {
+			let beat_time = 60.0 / bpm;
+			match current_warp {
+				Some((warp_start_beat, warp_start_second)) if beat_time > 0.0 => {
+					let recovery_beat =
+						start_beat + (warp_start_second - cursor_second) / beat_time;
+					if recovery_beat <= end_beat {
+						warps.push(Warp {
+							start_beat: warp_start_beat,
+							end_beat: recovery_beat,
+						});
+						current_warp = None;
+					}
+				}
+				None if beat_time < 0.0 => current_warp = Some((start_beat, cursor_second)),
+				_ => {}
+			}
+			cursor_second += beat_time * (end_beat - start_beat);
+		}
+		if let Some((warp_start_beat, _)) = current_warp {
+			warps.push(Warp {
+				start_beat: warp_start_beat,
+				end_beat: f64::INFINITY,
+			});
+		}
+
+		warps
+	}
+
+	/// Whether the given tick lies inside a warp, i.e. whether a note there is skipped. Like in
+	/// Etterna, such notes should not be judged.
+	pub fn is_tick_warped(&self, tick: u32) -> bool {
+		let beat = tick as f64 / 48.0;
+		self.warps().iter().any(|warp| warp.contains_beat(beat))
+	}
+
 	/// Input slice must be sorted!
+	///
+	/// Ticks inside a warp (see [`Self::warps`]) are placed at the start of the warp.
 	pub fn ticks_to_seconds(&self, ticks: &[u32]) -> Vec<f32> {
 		assert!(crate::util::is_sorted(ticks));
 
 		let mut cursor_beat: f64 = 0.0;
 		let mut cursor_second: f64 = 0.0;
 		let mut beat_time = 60.0 / self.first_bpm;
+		// Latest point in time reached so far. Negative BPMs make the time run backwards, which
+		// this is used to clamp away: time stands still until it catches up again, i.e. a warp
+		let mut max_second: f64 = 0.0;
 
 		// if a tick lies exactly on the boundary, if will _not_ be processed
 		let mut ticks_i = 0;
 		let mut seconds_vec = Vec::with_capacity(ticks.len());
 		let mut convert_ticks_up_to =
-			|beat: f64, cursor_second: f64, cursor_beat: f64, beat_time: f64| {
+			|beat: f64, cursor_second: f64, cursor_beat: f64, beat_time: f64, max_second: f64| {
 				while ticks_i < ticks.len() && ticks[ticks_i] as f64 / 48.0 < beat {
 					let beat = ticks[ticks_i] as f64 / 48.0;
 					let second = cursor_second + (beat - cursor_beat) * beat_time;
-					seconds_vec.push(second as f32);
+					seconds_vec.push(second.max(max_second) as f32);
 
 					ticks_i += 1;
 				}
@@ -92,18 +257,103 @@ impl TimingInfo {
 			bpm: change_bpm,
 		} in &self.changes
 		{
-			convert_ticks_up_to(*change_beat, cursor_second, cursor_beat, beat_time);
+			convert_ticks_up_to(
+				*change_beat,
+				cursor_second,
+				cursor_beat,
+				beat_time,
+				max_second,
+			);
 
 			cursor_second += beat_time * (change_beat - cursor_beat);
 			cursor_beat = *change_beat;
 			beat_time = 60.0 / change_bpm;
+			max_second = max_second.max(cursor_second);
 		}
 
 		// process all remaining ticks (i.e. all ticks coming after the last bpm change
-		convert_ticks_up_to(f64::INFINITY, cursor_second, cursor_beat, beat_time);
+		convert_ticks_up_to(
+			f64::INFINITY,
+			cursor_second,
+			cursor_beat,
+			beat_time,
+			max_second,
+		);
 
 		assert!(ticks.len() == seconds_vec.len()); // If this panics, the above code is wrong
 
 		seconds_vec
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
+
+	proptest! {
+		#[test]
+		fn test_sm_bpm_string_doesnt_panic(
+			string in proptest::collection::vec(any::<u8>(), 0..64),
+		) {
+			if let Ok(timing_info) = TimingInfo::from_sm_bpm_string(&string) {
+				let _ = timing_info.ticks_to_seconds(&[0, 48, 4800]);
+			}
+		}
+
+		#[test]
+		fn test_sm_bpm_string_structured_doesnt_panic(
+			string in "([-0-9.e]{0,6}=[-0-9.e]{0,6},?){0,4}",
+		) {
+			if let Ok(timing_info) = TimingInfo::from_sm_bpm_string(string.as_bytes()) {
+				let _ = timing_info.ticks_to_seconds(&[0, 48, 4800]);
+			}
+		}
+
+		#[test]
+		fn test_sm_bpm_string_roundtrip(
+			first_bpm in 1u32..1000,
+			changes in proptest::collection::vec((1u32..100_000, 1u32..1000), 0..8),
+		) {
+			let mut string = format!("0.000={}.000", first_bpm);
+			for (beat, bpm) in &changes {
+				string += &format!(",{}.000={}.000", beat, bpm);
+			}
+
+			let timing_info = TimingInfo::from_sm_bpm_string(string.as_bytes()).unwrap();
+			let string = timing_info.to_sm_bpm_string();
+			let reparsed = TimingInfo::from_sm_bpm_string(string.as_bytes()).unwrap();
+			prop_assert_eq!(reparsed, timing_info);
+		}
+	}
+
+	#[test]
+	fn test_warps() {
+		// warp that needs multiple positive segments to recover
+		let timing_info = TimingInfo::from_sm_bpm_string(b"0=60,2=-60,3=120,4=60").unwrap();
+		assert_eq!(
+			timing_info.warps(),
+			vec![Warp {
+				start_beat: 2.0,
+				end_beat: 4.5,
+			}]
+		);
+		let seconds = timing_info.ticks_to_seconds(&[48, 96, 168, 192, 216, 264]);
+		assert_eq!(seconds, vec![1.0, 2.0, 2.0, 2.0, 2.0, 3.0]);
+
+		// two separate warps
+		let timing_info = TimingInfo::from_sm_bpm_string(b"0=120,1=-120,2=120,8=-60,9=60").unwrap();
+		let warps = timing_info.warps();
+		assert_eq!(warps.len(), 2);
+		assert_eq!((warps[0].start_beat, warps[0].end_beat), (1.0, 3.0));
+		assert_eq!((warps[1].start_beat, warps[1].end_beat), (8.0, 10.0));
+
+		// never recovers
+		let timing_info = TimingInfo::from_sm_bpm_string(b"0=120,4=-120").unwrap();
+		assert_eq!(timing_info.warps()[0].end_beat, f64::INFINITY);
+		assert!(timing_info.is_tick_warped(u32::MAX));
+		assert_eq!(timing_info.ticks_to_seconds(&[192, 480]), vec![2.0, 2.0]);
+
+		assert!(TimingInfo::from_constant_bpm(120.0).warps().is_empty());
+	}
+}
@@ -0,0 +1,163 @@
+use crate::{Skillset7, Skillsets7, Skillsets8};
+
+/// Keeps track of a player's top N SSRs in each skillset.
+///
+/// Useful for calculating a player's rating incrementally, and for cheap "what if" queries like
+/// "how much would my rating increase with a 30 technical score?"
+///
+/// Only the top N scores of each skillset are kept; all others are discarded. Because the
+/// rating algorithm gives very little weight to scores far below the player's rating, the rating
+/// calculated from the top few hundred scores is practically identical to the rating calculated
+/// from all scores.
+///
+/// ```rust
+/// # use etterna::*;
+/// let mut top_scores = TopScores::new(250);
+/// top_scores.insert(&Skillsets7 { technical: 25.0, ..Default::default() });
+/// top_scores.insert(&Skillsets7 { technical: 27.0, ..Default::default() });
+///
+/// assert_eq!(top_scores.rank_of(Skillset7::Technical, 26.0), Some(1));
+/// assert!(top_scores.skillset_rating_with(Skillset7::Technical, 30.0)
+/// 	> top_scores.skillset_rating(Skillset7::Technical));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopScores {
+	capacity: usize,
+	/// One list per skillset, each sorted descendingly
+	ssrs: [Vec<f32>; 7],
+}
+
+impl TopScores {
+	/// Create an empty collection which keeps the top `n` scores per skillset
+	pub fn new(n: usize) -> Self {
+		Self {
+			capacity: n,
+			ssrs: Default::default(),
+		}
+	}
+
+	/// The maximum number of scores kept per skillset
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// Returns the currently kept SSRs of the given skillset, sorted from highest to lowest
+	pub fn get(&self, skillset: Skillset7) -> &[f32] {
+		&self.ssrs[skillset as usize]
+	}
+
+	/// Returns the position (zero-based) that the given SSR would take in the skillset's top
+	/// scores, or `None` if it's not high enough to make it into the top N
+	pub fn rank_of(&self, skillset: Skillset7, ssr: f32) -> Option<usize> {
+		let ssrs = self.get(skillset);
+		// Equal SSRs are placed after the existing ones
+		let rank = ssrs.iter().take_while(|&&other| other >= ssr).count();
+		if rank < self.capacity {
+			Some(rank)
+		} else {
+			None
+		}
+	}
+
+	/// Insert a single SSR into a skillset's top scores
+	pub fn insert_skillset(&mut self, skillset: Skillset7, ssr: f32) {
+		if let Some(rank) = self.rank_of(skillset, ssr) {
+			let ssrs = &mut self.ssrs[skillset as usize];
+			ssrs.insert(rank, ssr);
+			ssrs.truncate(self.capacity);
+		}
+	}
+
+	/// Insert a score's SSRs into the respective skillsets' top scores
+	pub fn insert(&mut self, ssrs: &Skillsets7) {
+		for ss in Skillset7::iter() {
+			self.insert_skillset(ss, ssrs.get(ss));
+		}
+	}
+
+	/// Calculate the player's current rating in a single skillset
+	pub fn skillset_rating(&self, skillset: Skillset7) -> f32 {
+		crate::calculate_player_skillset_rating(self.get(skillset))
+	}
+
+	/// Calculate the rating the player would have in a skillset, if they had an additional score
+	/// with the given SSR. The collection itself is not modified
+	pub fn skillset_rating_with(&self, skillset: Skillset7, ssr: f32) -> f32 {
+		match self.rank_of(skillset, ssr) {
+			Some(rank) => {
+				let mut ssrs = self.get(skillset).to_vec();
+				ssrs.insert(rank, ssr);
+				ssrs.truncate(self.capacity);
+				crate::calculate_player_skillset_rating(&ssrs)
+			}
+			None => self.skillset_rating(skillset),
+		}
+	}
+
+	/// Calculate the player's current rating in all skillsets, including overall
+	pub fn rating(&self) -> Skillsets8 {
+		Skillsets7::generate(|ss| self.skillset_rating(ss)).calc_player_overall()
+	}
+
+	/// This is the pre-0.70 variant of [`Self::rating`]
+	pub fn rating_pre_070(&self) -> Skillsets8 {
+		Skillsets7::generate(|ss| crate::calculate_player_skillset_rating_pre_070(self.get(ss)))
+			.calc_player_overall_pre_070()
+	}
+
+	/// Calculate the player's rating with the algorithm of the given game version, i.e.
+	/// [`Self::rating`] or [`Self::rating_pre_070`]
+	pub fn rating_for_version(&self, version: crate::EtternaVersion) -> Skillsets8 {
+		if version.is_pre_070() {
+			self.rating_pre_070()
+		} else {
+			self.rating()
+		}
+	}
+}
+
+impl Extend<Skillsets7> for TopScores {
+	fn extend<I: IntoIterator<Item = Skillsets7>>(&mut self, iter: I) {
+		for ssrs in iter {
+			self.insert(&ssrs);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_top_scores() {
+		let mut top_scores = TopScores::new(3);
+		for &stream in &[20.0, 25.0, 15.0, 22.0, 25.0] {
+			top_scores.insert(&Skillsets7 {
+				stream,
+				..Default::default()
+			});
+		}
+
+		assert_eq!(top_scores.get(Skillset7::Stream), &[25.0, 25.0, 22.0]);
+		assert_eq!(top_scores.get(Skillset7::Jackspeed), &[0.0, 0.0, 0.0]);
+		assert_eq!(top_scores.rank_of(Skillset7::Stream, 22.0), None);
+		assert_eq!(top_scores.rank_of(Skillset7::Stream, 23.0), Some(2));
+		assert_eq!(top_scores.rank_of(Skillset7::Stream, 30.0), Some(0));
+
+		// Scores outside the top N don't affect the rating
+		assert_eq!(
+			top_scores.skillset_rating_with(Skillset7::Stream, 10.0),
+			top_scores.skillset_rating(Skillset7::Stream),
+		);
+		assert!(
+			top_scores.skillset_rating_with(Skillset7::Stream, 30.0)
+				> top_scores.skillset_rating(Skillset7::Stream)
+		);
+
+		assert_eq!(
+			top_scores.rating().stream,
+			crate::calculate_player_skillset_rating(&[25.0, 25.0, 22.0]),
+		);
+	}
+}
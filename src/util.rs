@@ -1,3 +1,7 @@
+//! Small helpers for parsing and processing data, especially byte strings. These are used
+//! internally by the parsers in this crate, and are exposed for downstream parsers of custom
+//! formats.
+
 #![allow(unused)] // for now
 
 #[doc(hidden)]
@@ -24,6 +28,7 @@ macro_rules! some_or_continue {
 	};
 }
 
+/// Iterator over the lines of a byte string. See [`split_newlines`]
 pub struct SplitNewlines<'a> {
 	bytes: &'a [u8],
 	min_line_length: usize,
@@ -40,14 +45,13 @@ impl<'a> Iterator for SplitNewlines<'a> {
 		}
 
 		let start_pos = self.current_pos;
-		self.current_pos += self.min_line_length; // skip ahead as far as we can get away with
+		// skip ahead as far as we can get away with, but not beyond the end of the input
+		self.current_pos = (self.current_pos + self.min_line_length).min(self.bytes.len());
 
-		while let Some(&c) = self.bytes.get(self.current_pos) {
-			if c == b'\n' {
-				break;
-			}
-			self.current_pos += 1;
-		}
+		// memchr searches with SIMD where available, which beats a byte-by-byte loop even for
+		// short replay lines
+		self.current_pos += memchr::memchr(b'\n', &self.bytes[self.current_pos..])
+			.unwrap_or(self.bytes.len() - self.current_pos);
 		let line = &self.bytes[start_pos..self.current_pos];
 
 		self.current_pos += 1; // Advance one to be on the start of a line again
@@ -55,7 +59,7 @@ impl<'a> Iterator for SplitNewlines<'a> {
 	}
 }
 
-pub struct CountInto<'a, I: Iterator> {
+pub(crate) struct CountInto<'a, I: Iterator> {
 	iterator: I,
 	count_variable: &'a mut usize,
 }
@@ -72,9 +76,26 @@ impl<'a, I: Iterator> Iterator for CountInto<'a, I> {
 	}
 }
 
-// Like slice.split(b'\n'), but with optimizations based on a minimum line length assumption
-// When min_line_length is zero, the expected result for "xxx\n" would be ["xxx", ""]. However,
-// the result is gonna be just ["xxx"]. I know it's unintuitive, but I dunno how to fix
+/// Like `bytes.split(|&c| c == b'\n')`, but faster, by assuming that every line is at least
+/// `min_line_length` bytes long. That many bytes are skipped at the start of each line without
+/// looking for newlines.
+///
+/// After the skipped bytes, the newline is searched with [`memchr`](https://docs.rs/memchr), which
+/// uses SIMD instructions where available.
+///
+/// If a line is shorter than `min_line_length`, it will be merged with the following line(s).
+/// Line endings are not included in the yielded lines, and a trailing newline at the end of the
+/// input does not produce an empty last line.
+///
+/// ```rust
+/// # use etterna::util::split_newlines;
+/// let lines: Vec<&[u8]> = split_newlines(b"first line\nsecond line\n", 5).collect();
+/// assert_eq!(lines, vec![&b"first line"[..], &b"second line"[..]]);
+///
+/// // "ab" is shorter than the minimum line length of 5, so the newline after it is skipped
+/// let lines: Vec<&[u8]> = split_newlines(b"ab\ncdefgh\nijklmn", 5).collect();
+/// assert_eq!(lines, vec![&b"ab\ncdefgh"[..], &b"ijklmn"[..]]);
+/// ```
 pub fn split_newlines(bytes: &[u8], min_line_length: usize) -> SplitNewlines<'_> {
 	SplitNewlines {
 		bytes,
@@ -83,8 +104,13 @@ pub fn split_newlines(bytes: &[u8], min_line_length: usize) -> SplitNewlines<'_>
 	}
 }
 
-// Extracts a string based on a prefix and a postfix. If prefix or postfix couldn't be found,
-// returns None. UTF-8 safe too I think, even though it slices on byte indices.
+/// Extracts the part of a string between the first occurrence of `before` and the next
+/// occurrence of `after`. Returns None if either couldn't be found.
+///
+/// ```rust
+/// # use etterna::util::extract_str;
+/// assert_eq!(extract_str("#TITLE:Hello;", "#TITLE:", ";"), Some("Hello"));
+/// ```
 pub fn extract_str<'a>(string: &'a str, before: &str, after: &str) -> Option<&'a str> {
 	let before_index = twoway::find_str(string, before)?;
 	let start_index = before_index + before.len();
@@ -94,7 +120,7 @@ pub fn extract_str<'a>(string: &'a str, before: &str, after: &str) -> Option<&'a
 	Some(&string[start_index..end_index])
 }
 
-// The cooler ~~daniel~~ extract_str
+/// The cooler ~~daniel~~ [`extract_str`], which operates on byte strings
 pub fn extract_bstr<'a>(string: &'a [u8], before: &[u8], after: &[u8]) -> Option<&'a [u8]> {
 	let before_index = twoway::find_bytes(string, before)?;
 	let start_index = before_index + before.len();
@@ -108,7 +134,7 @@ pub fn extract_bstr<'a>(string: &'a [u8], before: &[u8], after: &[u8]) -> Option
 /// iterator. In case the iterator is empty or has only one element, None is returned instead of
 /// the first and last element.
 #[allow(clippy::type_complexity)]
-pub fn first_and_last_and_count<I: std::iter::Iterator>(
+pub(crate) fn first_and_last_and_count<I: std::iter::Iterator>(
 	mut iterator: I,
 ) -> (Option<(I::Item, I::Item)>, u64) {
 	// exception case handling
@@ -134,11 +160,25 @@ pub fn first_and_last_and_count<I: std::iter::Iterator>(
 	(Some((first_elem, last_elem)), count)
 }
 
-/// Does exactly what it says on the box
+/// Returns whether the given slice is sorted in ascending order. Equal neighboring elements are
+/// allowed.
+///
+/// ```rust
+/// # use etterna::util::is_sorted;
+/// assert!(is_sorted(&[1, 2, 2, 3]));
+/// assert!(!is_sorted(&[1.0, 0.5]));
+/// ```
 pub fn is_sorted<T: PartialOrd>(data: &[T]) -> bool {
 	data.windows(2).all(|w| w[0] <= w[1])
 }
 
+/// Removes leading and trailing ASCII whitespace, as defined by [`is_ascii_whitespace`], from a
+/// byte string
+///
+/// ```rust
+/// # use etterna::util::trim_bstr;
+/// assert_eq!(trim_bstr(b"  \t120.000\r\n"), b"120.000");
+/// ```
 pub fn trim_bstr(bstr: &[u8]) -> &[u8] {
 	let start_index = match bstr.iter().position(|&c| !is_ascii_whitespace(c)) {
 		Some(a) => a,
@@ -149,8 +189,60 @@ pub fn trim_bstr(bstr: &[u8]) -> &[u8] {
 	&bstr[start_index..=end_index]
 }
 
+/// Accumulator for wife points.
+///
+/// By default, this sums naively in f32, mirroring the game's summation for bit-compatible
+/// results. With the `precise-summation` feature, Neumaier compensated summation is used instead,
+/// which avoids the accumulated rounding error of summing tens of thousands of values in f32.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct WifeSum {
+	sum: f32,
+	compensation: f32,
+}
+
+impl WifeSum {
+	pub fn add(&mut self, value: f32) {
+		if cfg!(feature = "precise-summation") {
+			self.add_compensated(value);
+		} else {
+			self.sum += value;
+		}
+	}
+
+	// Neumaier's improved variant of Kahan summation
+	fn add_compensated(&mut self, value: f32) {
+		let new_sum = self.sum + value;
+		if self.sum.abs() >= value.abs() {
+			self.compensation += (self.sum - new_sum) + value;
+		} else {
+			self.compensation += (value - new_sum) + self.sum;
+		}
+		self.sum = new_sum;
+	}
+
+	pub fn get(self) -> f32 {
+		self.sum + self.compensation
+	}
+}
+
+impl std::ops::AddAssign<f32> for WifeSum {
+	fn add_assign(&mut self, value: f32) {
+		self.add(value);
+	}
+}
+
+impl std::iter::Sum<f32> for WifeSum {
+	fn sum<I: Iterator<Item = f32>>(iter: I) -> Self {
+		let mut sum = Self::default();
+		for value in iter {
+			sum += value;
+		}
+		sum
+	}
+}
+
 // I wish I knew how to make this properly generic, over arbitrary number types
-pub fn mean<I: Iterator>(iterator: I) -> f32
+pub(crate) fn mean<I: Iterator>(iterator: I) -> f32
 where
 	I::Item: std::ops::Deref<Target = f32>,
 {
@@ -163,41 +255,16 @@ where
 	sum / count as f32
 }
 
+/// Returns whether the given byte is ASCII whitespace: space, tab, line feed, carriage return,
+/// form feed, or vertical tab
 pub fn is_ascii_whitespace(c: u8) -> bool {
 	c == b' ' || c == b'\t' || c == b'\n' || c == b'\r'
 			|| c == 0x0c // form feed; an ASCII control symbol for a page break
 			|| c == 0x0b // vertical tab
 }
 
-#[allow(clippy::collapsible_if)]
-pub fn longest_true_sequence(iterator: impl IntoIterator<Item = bool>) -> u32 {
-	let mut longest_so_far = 0;
-	let mut current_run = 0;
-	// let mut current_run_start = 0;
-	// for (i, is_true) in iterator.into_iter().enumerate() {
-	for is_true in iterator {
-		if is_true {
-			current_run += 1;
-		} else {
-			if current_run > longest_so_far {
-				// println!("New best run from {} to {} - {} items!", current_run_start, i, current_run);
-				longest_so_far = current_run;
-			}
-			// current_run_start = i;
-			current_run = 0;
-		}
-	}
-	// println!();
-
-	if current_run > longest_so_far {
-		longest_so_far = current_run;
-	}
-
-	longest_so_far
-}
-
 /// Checks whether two slices are equal to one another, disregarding order and duplicates
-pub fn is_equal_no_order_no_duplicates<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+pub(crate) fn is_equal_no_order_no_duplicates<T: PartialEq>(a: &[T], b: &[T]) -> bool {
 	a.iter().all(|a_elem| b.contains(a_elem)) && b.iter().all(|b_elem| a.contains(b_elem))
 }
 
@@ -251,6 +318,10 @@ mod tests {
 			]
 		); // we expect the \n in here because it's
 		 // covered by the skip-ahead length of 7
+
+		// the last line is shorter than the skip-ahead length
+		let lines: Vec<&[u8]> = split_newlines(b"10charssss\nab" as &[u8], 6).collect();
+		assert_eq!(lines, vec![b"10charssss" as &[u8], b"ab" as &[u8]]);
 	}
 
 	#[test]
@@ -283,8 +354,8 @@ mod tests {
 
 	#[test]
 	fn test_is_sorted() {
-		assert_eq!(is_sorted(&[1, 2, 3, 2]), false);
-		assert_eq!(is_sorted(&[1, 2, 2, 3]), true);
+		assert!(!is_sorted(&[1, 2, 3, 2]));
+		assert!(is_sorted(&[1, 2, 2, 3]));
 	}
 
 	#[test]
@@ -306,6 +377,18 @@ mod tests {
 				epsilon=10.0); // heh, what a large epsilon value. needed though
 	}
 
+	#[test]
+	fn test_wife_sum() {
+		let mut naive = 0.0f32;
+		let mut compensated = WifeSum::default();
+		for _ in 0..100_000 {
+			naive += 0.1;
+			compensated.add_compensated(0.1);
+		}
+		assert!((naive - 10_000.0).abs() > 1.0); // naive f32 summation is way off
+		assert_float_eq!(compensated.get(), 10_000.0; epsilon=0.01);
+	}
+
 	#[test]
 	fn test_is_ascii_whitespace() {
 		let whitespace_chars: &[u8] = b" \t\n\r\x0c\x0b";
@@ -0,0 +1,197 @@
+/// Notes at or above this local density (notes per second, in real time) count as high-NPS notes
+const HIGH_NPS: f32 = 20.0;
+
+/// Minimum number of high-NPS notes before their marvelous rate is judged
+const MIN_HIGH_NPS_NOTES: u32 = 100;
+
+/// J4 marvelous rates above this, sustained across all high-NPS notes, are not humanly achievable
+const MAX_PLAUSIBLE_HIGH_NPS_MARV_RATE: f32 = 0.99;
+
+/// A single suspicious finding in a replay. See [`check_replay_validity`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValidityIssue {
+	/// A note was hit with a deviation that lies outside of every judge's hit window, or is NaN
+	ImpossibleDeviation {
+		/// Index of the note in the replay
+		index: usize,
+		deviation: f32,
+	},
+	/// The replay contains the same note (tick and column) multiple times
+	DuplicatedTick { tick: u32, column: u8 },
+	/// The replay contains more notes than the chart
+	TooManyNotes {
+		num_replay_notes: usize,
+		num_chart_notes: usize,
+	},
+	/// The proportion of J4 marvelouses in dense sections is too high to be humanly achievable
+	ImpossibleMarvRate {
+		/// Number of notes in sections at or above the high-NPS threshold
+		num_high_nps_notes: u32,
+		/// J4 marvelous rate within those notes, from 0.0 to 1.0
+		marv_rate: f32,
+	},
+}
+
+/// Result of [`check_replay_validity`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidityReport {
+	pub issues: Vec<ValidityIssue>,
+}
+
+impl ValidityReport {
+	/// Whether no issues were found
+	pub fn is_valid(&self) -> bool {
+		self.issues.is_empty()
+	}
+}
+
+/// Runs a series of sanity checks over a replay and returns all findings. Intended as a foundation
+/// for anti-cheat tooling.
+///
+/// `timing_info` and `num_chart_notes` describe the chart that the replay was played on, and
+/// `rate` is the music rate the replay was played at.
+///
+/// Some of the checks are heuristics, so a finding isn't necessarily proof of cheating.
+///
+/// ```rust
+/// # use etterna::*;
+/// let replay = parse_replay_v2_fast(b"0 0.01 0\n48 0.5 1\n48 0.02 1\n").unwrap();
+/// let timing_info = TimingInfo::from_constant_bpm(120.0);
+///
+/// let report = check_replay_validity(&replay, &timing_info, 3, Rate::from_f32(1.0).unwrap());
+/// assert_eq!(report.issues, vec![
+/// 	ValidityIssue::ImpossibleDeviation { index: 1, deviation: 0.5 },
+/// 	ValidityIssue::DuplicatedTick { tick: 48, column: 1 },
+/// ]);
+/// ```
+pub fn check_replay_validity(
+	replay: &crate::ReplayV2Fast,
+	timing_info: &crate::TimingInfo,
+	num_chart_notes: usize,
+	rate: crate::Rate,
+) -> ValidityReport {
+	let mut issues = Vec::new();
+
+	// J1 has the widest hit windows of all judges
+	let max_deviation = crate::J1.bad_window;
+	for (index, &deviation) in replay.deviations.iter().enumerate() {
+		if deviation.is_nan()
+			|| (deviation.abs() > max_deviation && deviation < crate::replay::MISS_DEVIATION)
+		{
+			issues.push(ValidityIssue::ImpossibleDeviation { index, deviation });
+		}
+	}
+
+	let mut notes: Vec<(u32, u8)> = replay
+		.ticks
+		.iter()
+		.copied()
+		.zip(replay.columns.iter().copied())
+		.collect();
+	notes.sort_unstable();
+	for pair in notes.windows(2) {
+		// only report the first duplicate of each note
+		if pair[0] == pair[1] && issues.last() != Some(&duplicated_tick_issue(pair[0])) {
+			issues.push(duplicated_tick_issue(pair[0]));
+		}
+	}
+
+	if replay.ticks.len() > num_chart_notes {
+		issues.push(ValidityIssue::TooManyNotes {
+			num_replay_notes: replay.ticks.len(),
+			num_chart_notes,
+		});
+	}
+
+	if let Some(issue) = check_high_nps_marv_rate(replay, timing_info, rate) {
+		issues.push(issue);
+	}
+
+	ValidityReport { issues }
+}
+
+fn duplicated_tick_issue((tick, column): (u32, u8)) -> ValidityIssue {
+	ValidityIssue::DuplicatedTick { tick, column }
+}
+
+fn check_high_nps_marv_rate(
+	replay: &crate::ReplayV2Fast,
+	timing_info: &crate::TimingInfo,
+	rate: crate::Rate,
+) -> Option<ValidityIssue> {
+	let points = replay.scatter_plot(timing_info, rate, crate::J4);
+
+	// Local NPS is measured in a one second window centered on each note
+	let mut num_high_nps_notes = 0;
+	let mut num_high_nps_marvs = 0;
+	let (mut window_start, mut window_end) = (0, 0);
+	for point in &points {
+		while points[window_start].second < point.second - 0.5 {
+			window_start += 1;
+		}
+		while window_end < points.len() && points[window_end].second <= point.second + 0.5 {
+			window_end += 1;
+		}
+
+		if (window_end - window_start) as f32 >= HIGH_NPS {
+			num_high_nps_notes += 1;
+			if point.judgement == crate::TapJudgement::Marvelous {
+				num_high_nps_marvs += 1;
+			}
+		}
+	}
+
+	if num_high_nps_notes < MIN_HIGH_NPS_NOTES {
+		return None;
+	}
+	let marv_rate = num_high_nps_marvs as f32 / num_high_nps_notes as f32;
+	if marv_rate > MAX_PLAUSIBLE_HIGH_NPS_MARV_RATE {
+		Some(ValidityIssue::ImpossibleMarvRate {
+			num_high_nps_notes,
+			marv_rate,
+		})
+	} else {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_check_replay_validity() {
+		let timing_info = crate::TimingInfo::from_constant_bpm(150.0);
+		let rate = crate::Rate::from_f32(1.0).unwrap();
+
+		// 200 32nd notes at 150 BPM, which is 20 NPS
+		let mut replay = crate::ReplayV2Fast::default();
+		for i in 0..200 {
+			replay.ticks.push(i * 6);
+			replay.columns.push((i % 2) as u8);
+			replay.deviations.push(0.003);
+		}
+		let report = check_replay_validity(&replay, &timing_info, 200, rate);
+		assert!(matches!(
+			report.issues[..],
+			[ValidityIssue::ImpossibleMarvRate { num_high_nps_notes, .. }] if num_high_nps_notes > 100
+		));
+
+		// With realistic accuracy, the replay is fine
+		for deviation in replay.deviations.iter_mut().step_by(10) {
+			*deviation = 0.04;
+		}
+		assert!(check_replay_validity(&replay, &timing_info, 200, rate).is_valid());
+
+		// The same replay on a chart with fewer notes is not
+		assert_eq!(
+			check_replay_validity(&replay, &timing_info, 150, rate).issues,
+			vec![ValidityIssue::TooManyNotes {
+				num_replay_notes: 200,
+				num_chart_notes: 150,
+			}],
+		);
+	}
+}
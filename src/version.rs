@@ -0,0 +1,98 @@
+//! Game versions, for the calculations whose rules changed between versions of Etterna. See
+//! [`EtternaVersion`].
+
+/// A version of Etterna whose rules differ from the previous one in at least one calculation this
+/// crate does. Game versions in between map to the closest older variant; for example, use
+/// [`Self::V0_68`] for 0.69.
+///
+/// Ordered chronologically, so the rules of a range of versions can be checked with comparisons.
+///
+/// Currently, every rule in this crate only distinguishes versions before 0.70 from 0.70 and later
+/// (see [`Self::is_pre_070`]). [`Self::V0_65`] behaves like [`Self::V0_68`], and [`Self::V0_70`]
+/// like [`Self::V0_71`]; the finer variants exist so that callers can already record the exact
+/// version, and rules can be split further without changing their signatures.
+///
+/// ```rust
+/// # use etterna::*;
+/// assert!(EtternaVersion::V0_68.is_pre_070());
+/// assert_eq!(EtternaVersion::V0_68.wife_version(), WifeVersion::Wife2);
+/// assert_eq!(EtternaVersion::LATEST.wife_version(), WifeVersion::Wife3);
+/// assert!(EtternaVersion::V0_65 < EtternaVersion::V0_71);
+/// assert_eq!(EtternaVersion::V0_70.to_string(), "0.70");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EtternaVersion {
+	V0_65,
+	V0_68,
+	/// Introduced Wife3, universal mine timing and the current rating and overall algorithms
+	V0_70,
+	V0_71,
+}
+
+impl EtternaVersion {
+	/// The most recent version known to this crate. Its rules apply to all newer game versions too
+	pub const LATEST: Self = Self::V0_71;
+
+	/// Get a list of all versions, from oldest to newest
+	pub fn list() -> &'static [Self] {
+		&[Self::V0_65, Self::V0_68, Self::V0_70, Self::V0_71]
+	}
+
+	/// Converts the `pre_070` flag of older APIs: [`Self::V0_68`] if true, otherwise
+	/// [`Self::LATEST`]
+	pub fn from_pre_070(pre_070: bool) -> Self {
+		if pre_070 {
+			Self::V0_68
+		} else {
+			Self::LATEST
+		}
+	}
+
+	/// Whether this version predates the 0.70 rating and scoring overhaul
+	pub fn is_pre_070(self) -> bool {
+		self < Self::V0_70
+	}
+
+	/// The wifescore algorithm that this version uses to score hits
+	pub fn wife_version(self) -> crate::WifeVersion {
+		if self.is_pre_070() {
+			crate::WifeVersion::Wife2
+		} else {
+			crate::WifeVersion::Wife3
+		}
+	}
+
+	/// The window in which a mine can be hit, as a +/- value in seconds. Before universal mine
+	/// timing, this was the judge's great window; since then, it's [`crate::Judge::mine_window`].
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// assert_eq!(EtternaVersion::V0_68.mine_window(J7), J7.great_window);
+	/// assert_eq!(EtternaVersion::LATEST.mine_window(J7), J7.mine_window);
+	/// ```
+	pub fn mine_window(self, judge: &crate::Judge) -> f32 {
+		if self.is_pre_070() {
+			judge.great_window
+		} else {
+			judge.mine_window
+		}
+	}
+}
+
+impl Default for EtternaVersion {
+	fn default() -> Self {
+		Self::LATEST
+	}
+}
+
+impl std::fmt::Display for EtternaVersion {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::V0_65 => "0.65",
+			Self::V0_68 => "0.68",
+			Self::V0_70 => "0.70",
+			Self::V0_71 => "0.71",
+		})
+	}
+}
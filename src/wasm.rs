@@ -0,0 +1,200 @@
+//! wasm-bindgen wrappers around the core scoring and rating functions, for use from JavaScript.
+//!
+//! The wrappers only use types that can cross the wasm boundary: judges are referred to by name
+//! ("J4"), missed notes are represented by NaN deviations, and skillsets are passed as flat arrays
+//! in the order of [`crate::Skillset7::list`]/[`crate::Skillset8::list`].
+
+use wasm_bindgen::prelude::*;
+
+fn judge_from_name(name: &str) -> Result<&'static crate::Judge, JsValue> {
+	crate::Judge::from_name(name)
+		.ok_or_else(|| JsValue::from_str(&format!("Unknown judge: \"{}\"", name)))
+}
+
+fn deviation_to_hit(deviation: f32) -> crate::Hit {
+	if deviation.is_nan() {
+		crate::Hit::Miss
+	} else {
+		crate::Hit::Hit { deviation }
+	}
+}
+
+/// Calculates the Wife3 points (max 1.0) for a single hit deviation in seconds. A NaN deviation
+/// counts as a miss.
+#[wasm_bindgen]
+pub fn wife3(deviation: f32, judge: &str) -> Result<f32, JsValue> {
+	Ok(crate::wife3(
+		deviation_to_hit(deviation),
+		judge_from_name(judge)?,
+	))
+}
+
+/// Calculates a Wife3 score (max 1.0) from the note deviations in seconds, mine hits and hold
+/// drops of a replay. NaN deviations count as misses.
+///
+/// Returns undefined if `deviations` is empty
+#[wasm_bindgen]
+pub fn rescore_from_note_hits(
+	deviations: &[f32],
+	num_mine_hits: u32,
+	num_hold_drops: u32,
+	judge: &str,
+) -> Result<Option<f32>, JsValue> {
+	let wifescore = crate::rescore_from_note_hits::<crate::Wife3, _>(
+		deviations.iter().map(|&d| deviation_to_hit(d)),
+		num_mine_hits,
+		num_hold_drops,
+		judge_from_name(judge)?,
+	);
+	Ok(wifescore.map(|wifescore| wifescore.as_proportion()))
+}
+
+/// Result of [`skill_timeline`]
+#[wasm_bindgen]
+pub struct SkillTimeline {
+	inner: crate::SkillTimeline<u32>,
+}
+
+#[wasm_bindgen]
+impl SkillTimeline {
+	/// Number of rating changes in this timeline
+	pub fn len(&self) -> usize {
+		self.inner.changes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.inner.changes.is_empty()
+	}
+
+	/// Group identifier of the rating change at the given index
+	pub fn group_id(&self, index: usize) -> Option<u32> {
+		Some(self.inner.changes.get(index)?.0)
+	}
+
+	/// Overall and the seven skillset ratings after the rating change at the given index
+	pub fn ratings(&self, index: usize) -> Option<Vec<f32>> {
+		let ratings = &self.inner.changes.get(index)?.1;
+		Some(crate::Skillset8::iter().map(|ss| ratings.get(ss)).collect())
+	}
+}
+
+/// Calculates a player's ratings over time; see [`crate::SkillTimeline::calculate`].
+///
+/// `group_ids` contains one group identifier per score (e.g. days since some epoch), and `ssrs`
+/// contains seven skillset values per score, laid out one score after another.
+#[wasm_bindgen]
+pub fn skill_timeline(
+	group_ids: &[u32],
+	ssrs: &[f32],
+	pre_070: bool,
+) -> Result<SkillTimeline, JsValue> {
+	if ssrs.len() != group_ids.len() * 7 {
+		return Err(JsValue::from_str(
+			"Expected exactly seven skillset values per group id",
+		));
+	}
+
+	let scores = group_ids
+		.iter()
+		.zip(ssrs.chunks_exact(7))
+		.map(|(&id, ssr)| {
+			(
+				id,
+				crate::Skillsets7 {
+					stream: ssr[0],
+					jumpstream: ssr[1],
+					handstream: ssr[2],
+					stamina: ssr[3],
+					jackspeed: ssr[4],
+					chordjack: ssr[5],
+					technical: ssr[6],
+				},
+			)
+		});
+	Ok(SkillTimeline {
+		inner: crate::SkillTimeline::calculate(
+			scores,
+			crate::EtternaVersion::from_pre_070(pre_070),
+		),
+	})
+}
+
+// These call the wrappers natively. The error paths create a `JsValue`, which only works on wasm
+// targets, so only valid input is tested here
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_wife3() {
+		assert_eq!(wife3(0.0, "J4").unwrap(), 1.0);
+		assert_eq!(
+			wife3(0.05, "J7").unwrap(),
+			crate::wife3(crate::Hit::Hit { deviation: 0.05 }, crate::J7)
+		);
+		assert_eq!(
+			wife3(f32::NAN, "J4").unwrap(),
+			crate::wife3(crate::Hit::Miss, crate::J4)
+		);
+	}
+
+	#[test]
+	fn test_rescore_from_note_hits() {
+		let deviations = [0.0, 0.02, f32::NAN];
+		let expected = crate::rescore_from_note_hits::<crate::Wife3, _>(
+			vec![
+				crate::Hit::Hit { deviation: 0.0 },
+				crate::Hit::Hit { deviation: 0.02 },
+				crate::Hit::Miss,
+			],
+			1,
+			2,
+			crate::J4,
+		)
+		.unwrap();
+		assert_eq!(
+			rescore_from_note_hits(&deviations, 1, 2, "J4").unwrap(),
+			Some(expected.as_proportion())
+		);
+		assert_eq!(rescore_from_note_hits(&[], 0, 0, "J4").unwrap(), None);
+	}
+
+	#[test]
+	fn test_skill_timeline() {
+		let mut ssrs = [0.0; 14];
+		ssrs[0] = 20.0; // stream of the first score
+		ssrs[7 + 4] = 25.0; // jackspeed of the second score
+		let timeline = skill_timeline(&[1, 2], &ssrs, false).unwrap();
+
+		assert_eq!(timeline.len(), 2);
+		assert!(!timeline.is_empty());
+		assert_eq!(timeline.group_id(1), Some(2));
+		assert_eq!(timeline.group_id(2), None);
+
+		let expected = crate::SkillTimeline::calculate(
+			vec![
+				(
+					1,
+					crate::Skillsets7 {
+						stream: 20.0,
+						..Default::default()
+					},
+				),
+				(
+					2,
+					crate::Skillsets7 {
+						jackspeed: 25.0,
+						..Default::default()
+					},
+				),
+			],
+			crate::EtternaVersion::LATEST,
+		);
+		let ratings = timeline.ratings(1).unwrap();
+		assert_eq!(ratings.len(), 8);
+		assert_eq!(ratings[0], expected.changes[1].1.overall);
+		assert_eq!(ratings[1], expected.changes[1].1.stream);
+		assert_eq!(ratings[5], expected.changes[1].1.jackspeed);
+		assert_eq!(timeline.ratings(2), None);
+	}
+}
@@ -35,7 +35,7 @@ pub trait Wife {
 		judge: &crate::Judge,
 	) -> Option<crate::Wifescore> {
 		let mut num_note_hits = 0;
-		let mut wifescore_sum = 0.0;
+		let mut wifescore_sum = crate::util::WifeSum::default();
 		for hit in note_hits {
 			wifescore_sum += Self::calc(hit, judge);
 			num_note_hits += 1;
@@ -44,7 +44,74 @@ pub trait Wife {
 		wifescore_sum += num_mine_hits as f32 * Self::MINE_HIT_WEIGHT;
 		wifescore_sum += num_hold_drops as f32 * Self::HOLD_DROP_WEIGHT;
 
-		crate::Wifescore::from_proportion(wifescore_sum / num_note_hits as f32)
+		crate::Wifescore::from_proportion(wifescore_sum.get() / num_note_hits as f32)
+	}
+}
+
+/// Runtime counterpart of the [`Wife`] implementors, for when the wifescore algorithm is only known
+/// at runtime, e.g. via [`crate::EtternaVersion::wife_version`]
+///
+/// ```rust
+/// # use etterna::*;
+/// let hits = vec![Hit::Hit { deviation: 0.03 }, Hit::Miss];
+/// assert_eq!(
+/// 	WifeVersion::Wife2.apply(hits.clone(), 1, 0, J4),
+/// 	Wife2::apply(hits, 1, 0, J4),
+/// );
+/// assert_eq!(WifeVersion::Wife3.mine_hit_weight(), Wife3::MINE_HIT_WEIGHT);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WifeVersion {
+	Wife2,
+	Wife3,
+}
+
+impl WifeVersion {
+	/// See [`Wife::MINE_HIT_WEIGHT`]
+	pub fn mine_hit_weight(self) -> f32 {
+		match self {
+			Self::Wife2 => Wife2::MINE_HIT_WEIGHT,
+			Self::Wife3 => Wife3::MINE_HIT_WEIGHT,
+		}
+	}
+
+	/// See [`Wife::HOLD_DROP_WEIGHT`]
+	pub fn hold_drop_weight(self) -> f32 {
+		match self {
+			Self::Wife2 => Wife2::HOLD_DROP_WEIGHT,
+			Self::Wife3 => Wife3::HOLD_DROP_WEIGHT,
+		}
+	}
+
+	/// See [`Wife::MISS_WEIGHT`]
+	pub fn miss_weight(self) -> f32 {
+		match self {
+			Self::Wife2 => Wife2::MISS_WEIGHT,
+			Self::Wife3 => Wife3::MISS_WEIGHT,
+		}
+	}
+
+	/// See [`Wife::calc`]
+	pub fn calc(self, hit: impl Into<crate::Hit>, judge: &crate::Judge) -> f32 {
+		match self {
+			Self::Wife2 => Wife2::calc(hit.into(), judge),
+			Self::Wife3 => Wife3::calc(hit.into(), judge),
+		}
+	}
+
+	/// See [`Wife::apply`]
+	pub fn apply(
+		self,
+		note_hits: impl IntoIterator<Item = crate::Hit>,
+		num_mine_hits: u32,
+		num_hold_drops: u32,
+		judge: &crate::Judge,
+	) -> Option<crate::Wifescore> {
+		match self {
+			Self::Wife2 => Wife2::apply(note_hits, num_mine_hits, num_hold_drops, judge),
+			Self::Wife3 => Wife3::apply(note_hits, num_mine_hits, num_hold_drops, judge),
+		}
 	}
 }
 
@@ -1,9 +1,8 @@
 use super::Wife;
 
 // lol who the fuck cares about excessive precision
-#[allow(clippy::excessive_precision)]
-
 // erf approxmation function, as used in Etterna (same file as in the link below)
+#[allow(clippy::excessive_precision)]
 fn ett_erf(x: f32) -> f32 {
 	let exp = |x| std::f32::consts::E.powf(x);
 
@@ -0,0 +1,205 @@
+use crate::{Grade, Wifescore};
+
+/// Distribution of many wifescores, e.g. all scores on a chart's leaderboard, for questions like
+/// "what percentile is this score in" or "how many players got an AA".
+///
+/// The mean and grade histogram are computed in the same pass that collects the wifescores, which
+/// are then sorted once, so every other query is a lookup or binary search.
+///
+/// ```rust
+/// # use etterna::*;
+/// let distribution: WifescoreDistribution = [85.0, 93.5, 96.0, 99.8, 70.0]
+/// 	.iter()
+/// 	.map(|&percent| Wifescore::from_percent(percent).unwrap())
+/// 	.collect();
+///
+/// assert_eq!(distribution.median(), Wifescore::from_percent(93.5));
+/// assert_eq!(distribution.percentile_of(Wifescore::from_percent(95.0).unwrap()), Some(60.0));
+/// assert_eq!(distribution.num_with_grade(Grade::AA), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(from = "Vec<Wifescore>", into = "Vec<Wifescore>")
+)]
+pub struct WifescoreDistribution {
+	/// Sorted ascendingly
+	wifescores: Vec<Wifescore>,
+	/// Indexed by grade, worst first
+	grade_counts: [u32; 8],
+	/// Sum of all finite wifescore proportions
+	proportion_sum: f64,
+}
+
+impl WifescoreDistribution {
+	pub fn new(wifescores: impl IntoIterator<Item = Wifescore>) -> Self {
+		let mut distribution = Self::default();
+		for wifescore in wifescores {
+			distribution.grade_counts[wifescore.grade() as usize] += 1;
+			if wifescore.as_proportion().is_finite() {
+				distribution.proportion_sum += wifescore.as_proportion() as f64;
+			}
+			distribution.wifescores.push(wifescore);
+		}
+		distribution.wifescores.sort_unstable();
+		distribution
+	}
+
+	/// Number of wifescores
+	pub fn len(&self) -> usize {
+		self.wifescores.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.wifescores.is_empty()
+	}
+
+	/// All wifescores, sorted from worst to best
+	pub fn wifescores(&self) -> &[Wifescore] {
+		&self.wifescores
+	}
+
+	/// Percentage of wifescores that are strictly lower than the given one, i.e. 100 means that the
+	/// wifescore beats every score. None if the distribution is empty
+	pub fn percentile_of(&self, wifescore: Wifescore) -> Option<f32> {
+		if self.is_empty() {
+			return None;
+		}
+		let num_lower = self.wifescores.partition_point(|&other| other < wifescore);
+		Some(num_lower as f32 * 100.0 / self.len() as f32)
+	}
+
+	/// The wifescore at the given quantile (0.0 to 1.0), using the nearest-rank method. `0.0` is the
+	/// lowest wifescore and `1.0` the highest. None if the distribution is empty
+	///
+	/// ```rust
+	/// # use etterna::*;
+	/// let distribution = WifescoreDistribution::new(
+	/// 	(1..=100).map(|percent| Wifescore::from_percent(percent as f32).unwrap()),
+	/// );
+	/// assert_eq!(distribution.quantile(0.9), Wifescore::from_percent(90.0));
+	/// assert_eq!(distribution.quantile(0.0), Wifescore::from_percent(1.0));
+	/// assert_eq!(distribution.quantile(1.0), Some(Wifescore::HUNDRED_PERCENT));
+	/// ```
+	pub fn quantile(&self, quantile: f32) -> Option<Wifescore> {
+		let rank = (quantile.clamp(0.0, 1.0) * self.len() as f32).ceil() as usize;
+		self.wifescores.get(rank.saturating_sub(1)).copied()
+	}
+
+	/// Shorthand for [`Self::quantile`] with 0.5
+	pub fn median(&self) -> Option<Wifescore> {
+		self.quantile(0.5)
+	}
+
+	/// Average of all finite wifescores, or None if there are none
+	pub fn mean(&self) -> Option<Wifescore> {
+		let num_finite = self
+			.wifescores
+			.iter()
+			.filter(|wifescore| wifescore.as_proportion().is_finite())
+			.count();
+		if num_finite == 0 {
+			return None;
+		}
+		Some(Wifescore::from_proportion_clamped(
+			(self.proportion_sum / num_finite as f64) as f32,
+		))
+	}
+
+	/// Number of wifescores that achieve exactly the given grade
+	pub fn num_with_grade(&self, grade: Grade) -> u32 {
+		self.grade_counts[grade as usize]
+	}
+
+	/// Number of wifescores in each grade bucket, from [`Grade::D`] to [`Grade::AAAAA`]. The
+	/// bucket boundaries are the grade thresholds
+	pub fn grade_histogram(&self) -> Vec<(Grade, u32)> {
+		Grade::list()
+			.iter()
+			.map(|&grade| (grade, self.num_with_grade(grade)))
+			.collect()
+	}
+}
+
+impl std::iter::FromIterator<Wifescore> for WifescoreDistribution {
+	fn from_iter<I: IntoIterator<Item = Wifescore>>(iter: I) -> Self {
+		Self::new(iter)
+	}
+}
+
+/// Serialized as the plain list of [`WifescoreDistribution::wifescores`], so that the
+/// precomputed statistics are rebuilt with [`WifescoreDistribution::new`] on deserialization
+impl From<Vec<Wifescore>> for WifescoreDistribution {
+	fn from(wifescores: Vec<Wifescore>) -> Self {
+		Self::new(wifescores)
+	}
+}
+
+impl From<WifescoreDistribution> for Vec<Wifescore> {
+	fn from(distribution: WifescoreDistribution) -> Self {
+		distribution.wifescores
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_wifescore_distribution() {
+		let percent = |percent| Wifescore::from_percent(percent).unwrap();
+		let distribution = WifescoreDistribution::new(vec![
+			percent(93.0),
+			Wifescore::NEGATIVE_INFINITY,
+			percent(50.0),
+			percent(93.0),
+			percent(100.0),
+		]);
+
+		assert_eq!(distribution.len(), 5);
+		assert_eq!(distribution.wifescores()[0], Wifescore::NEGATIVE_INFINITY);
+		// ties don't count as beaten
+		assert_eq!(distribution.percentile_of(percent(93.0)), Some(40.0));
+		assert_eq!(distribution.percentile_of(percent(100.0)), Some(80.0));
+		assert_eq!(distribution.percentile_of(percent(-1000.0)), Some(20.0));
+		assert!((distribution.mean().unwrap().as_percent() - 84.0).abs() < 0.001);
+		assert_eq!(
+			distribution.quantile(0.2),
+			Some(Wifescore::NEGATIVE_INFINITY)
+		);
+		assert_eq!(distribution.quantile(0.21), Some(percent(50.0)));
+
+		let histogram = distribution.grade_histogram();
+		assert_eq!(histogram.len(), 8);
+		assert_eq!(histogram[0], (Grade::D, 2));
+		assert_eq!(histogram[4], (Grade::AA, 2));
+		assert_eq!(histogram[7], (Grade::AAAAA, 1));
+
+		let empty = WifescoreDistribution::default();
+		assert_eq!(empty.percentile_of(percent(50.0)), None);
+		assert_eq!(empty.quantile(0.5), None);
+		assert_eq!(empty.mean(), None);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn test_serde() {
+		let distribution = WifescoreDistribution::new(vec![
+			Wifescore::HUNDRED_PERCENT,
+			Wifescore::from_percent(50.0).unwrap(),
+		]);
+		let json = serde_json::to_string(&distribution).unwrap();
+		assert_eq!(json, "[0.5,1.0]");
+		assert_eq!(
+			serde_json::from_str::<WifescoreDistribution>(&json).unwrap(),
+			distribution
+		);
+
+		// the statistics are computed from the wifescores, not taken from the input
+		let unsorted: WifescoreDistribution = serde_json::from_str("[1.0,0.5,0.95]").unwrap();
+		assert_eq!(unsorted.median(), Wifescore::from_percent(95.0));
+		assert_eq!(unsorted.num_with_grade(Grade::AA), 1);
+		assert!(serde_json::from_str::<WifescoreDistribution>("[1.5]").is_err());
+	}
+}